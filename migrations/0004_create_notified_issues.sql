@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS notified_issues (
+    chat_id BIGINT NOT NULL,
+    repository_full_name TEXT NOT NULL,
+    issue_id TEXT NOT NULL,
+    notified_at INTEGER NOT NULL,
+    PRIMARY KEY (chat_id, repository_full_name, issue_id)
+);
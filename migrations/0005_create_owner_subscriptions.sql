@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS owner_subscriptions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_id BIGINT NOT NULL,
+    owner TEXT NOT NULL COLLATE NOCASE,
+    tracked_labels TEXT NOT NULL DEFAULT '[]',
+    UNIQUE(chat_id, owner)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS search_subscriptions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_id BIGINT NOT NULL,
+    label TEXT NOT NULL,
+    language TEXT NOT NULL DEFAULT '',
+    topic TEXT NOT NULL DEFAULT '',
+    min_stars INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    last_poll_time INTEGER NOT NULL,
+    UNIQUE(chat_id, label, language, topic, min_stars)
+);
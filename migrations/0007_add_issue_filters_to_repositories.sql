@@ -0,0 +1,6 @@
+-- Per-subscription options to skip issues someone is already working on
+ALTER TABLE repositories
+ADD COLUMN exclude_assigned BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE repositories
+ADD COLUMN exclude_linked_prs BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Unix timestamp until which a tracked repository is not notified about
+ALTER TABLE repositories
+ADD COLUMN muted_until BIGINT;
@@ -0,0 +1,5 @@
+-- Chats the bot can no longer deliver to, e.g. because the user blocked it
+CREATE TABLE IF NOT EXISTS inactive_chats (
+    chat_id BIGINT PRIMARY KEY,
+    deactivated_at INTEGER NOT NULL
+);
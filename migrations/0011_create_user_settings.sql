@@ -0,0 +1,17 @@
+-- Per-chat preferences, for chats that changed the defaults
+CREATE TABLE IF NOT EXISTS user_settings (
+    chat_id BIGINT PRIMARY KEY,
+    delivery_mode TEXT NOT NULL DEFAULT 'instant',
+    last_digest_at INTEGER
+);
+
+-- Issues waiting to be sent in a chat's next digest
+CREATE TABLE IF NOT EXISTS digest_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chat_id BIGINT NOT NULL,
+    repository_full_name TEXT NOT NULL,
+    issue_id TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    added_at INTEGER NOT NULL,
+    UNIQUE(chat_id, repository_full_name, issue_id)
+);
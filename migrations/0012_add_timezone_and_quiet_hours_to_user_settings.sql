@@ -0,0 +1,7 @@
+-- The IANA timezone the digest schedule and quiet hours of a chat are in
+ALTER TABLE user_settings
+ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
+
+-- When a chat does not want to be notified, as HH:MM-HH:MM
+ALTER TABLE user_settings
+ADD COLUMN quiet_hours TEXT;
@@ -0,0 +1,15 @@
+-- Send notifications without sound
+ALTER TABLE user_settings
+ADD COLUMN silent_notifications BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Show a preview of the first link of notifications
+ALTER TABLE user_settings
+ADD COLUMN link_previews BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- The language notifications are sent in, as an ISO 639-1 code
+ALTER TABLE user_settings
+ADD COLUMN language TEXT NOT NULL DEFAULT 'en';
+
+-- The number of items per page of the repository and label lists
+ALTER TABLE user_settings
+ADD COLUMN page_size INTEGER NOT NULL DEFAULT 10;
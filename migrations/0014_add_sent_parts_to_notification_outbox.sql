@@ -0,0 +1,4 @@
+-- The number of parts of a message too long for one that were delivered, so
+-- a retry only sends the rest
+ALTER TABLE notification_outbox
+ADD COLUMN sent_parts INTEGER NOT NULL DEFAULT 0;
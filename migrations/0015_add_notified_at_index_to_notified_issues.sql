@@ -0,0 +1,2 @@
+-- Lets the retention sweep find old entries of the ledger without scanning it
+CREATE INDEX IF NOT EXISTS idx_notified_issues_notified_at ON notified_issues (notified_at);
@@ -1,6 +1,17 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    bot_handler::commands::settings::SettingField,
+    storage::{IssueFilter, Language, NotificationOption},
+};
 
 /// Represents the actions that can be triggered by an inline keyboard button.
+///
+/// Repository and label names are sent as-is when the serialized action fits
+/// into Telegram's callback data, and as a reference built by `callback_ref`
+/// otherwise. Actions naming both a repository and a label shorten the
+/// repository first.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum CallbackAction<'a> {
@@ -25,6 +36,34 @@ pub enum CallbackAction<'a> {
     /// Go back to the main repository list view.
     #[serde(rename = "brl")]
     BackToRepoList(usize), // (page)
+    /// Turn an issue filter of a repository on or off.
+    #[serde(rename = "tif")]
+    ToggleIssueFilter(&'a str, IssueFilter, usize), // ("owner/repo", filter, from_page)
+    /// Remove a search subscription.
+    #[serde(rename = "rs")]
+    RemoveSearch(i64), // (search id)
+    /// Stop tracking a label of a repository, from an issue notification.
+    #[serde(rename = "ml")]
+    MuteLabel(&'a str, &'a str), // ("owner/repo", "label")
+    /// Stop notifying about a repository for a while, from an issue
+    /// notification.
+    #[serde(rename = "mr")]
+    MuteRepo(&'a str), // ("owner/repo")
+    /// Stop tracking a repository, from an issue notification.
+    #[serde(rename = "ur")]
+    UntrackRepo(&'a str), // ("owner/repo")
+    /// Prompt the user for the new value of a setting.
+    #[serde(rename = "es")]
+    EditSetting(SettingField),
+    /// Turn a notification option on or off.
+    #[serde(rename = "tno")]
+    ToggleNotificationOption(NotificationOption),
+    /// Choose the language notifications are sent in.
+    #[serde(rename = "sl")]
+    SetLanguage(Language),
+    /// Choose the number of items per page of lists.
+    #[serde(rename = "sps")]
+    SetPageSize(usize), // (page size)
     /// A command to show the help message, triggered from a button.
     CmdHelp,
     /// A command to list all repositories, triggered from a button.
@@ -34,3 +73,115 @@ pub enum CallbackAction<'a> {
     /// A command to show the overview, triggered from a button.
     CmdOverview,
 }
+
+/// Telegram rejects buttons whose callback data is longer than this, in bytes.
+pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+/// Marks a callback reference that stands in for a name too long to fit.
+const REF_PREFIX: char = '#';
+
+/// The number of bytes of the name's SHA-256 digest kept in a reference.
+const REF_DIGEST_LEN: usize = 8;
+
+/// Builds a short reference to a repository or label name, e.g.
+/// `#1a2b3c4d5e6f7a8b`. The reference can not be turned back into the name; it
+/// is resolved by matching it against the names the button could refer to.
+pub fn callback_ref(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    format!("{REF_PREFIX}{}", hex::encode(&digest[..REF_DIGEST_LEN]))
+}
+
+/// Returns `true` if `value` is a reference built by `callback_ref`, rather
+/// than a name.
+pub fn is_callback_ref(value: &str) -> bool {
+    value.starts_with(REF_PREFIX)
+}
+
+/// Returns `true` if `value`, either a name or a reference, refers to `name`.
+pub fn refers_to(value: &str, name: &str) -> bool {
+    if is_callback_ref(value) { callback_ref(name) == value } else { value == name }
+}
+
+impl<'a> CallbackAction<'a> {
+    /// The repository or label name the action refers to, if any.
+    pub fn target(&self) -> Option<&'a str> {
+        match *self {
+            Self::ViewRepoDetails(target, _)
+            | Self::ViewRepoLabels(target, _, _)
+            | Self::RemoveRepoPrompt(target)
+            | Self::ToggleLabel(target, _, _)
+            | Self::BackToRepoDetails(target, _)
+            | Self::ToggleIssueFilter(target, _, _)
+            | Self::MuteLabel(target, _)
+            | Self::MuteRepo(target)
+            | Self::UntrackRepo(target) => Some(target),
+            Self::ListReposPage(_)
+            | Self::BackToRepoList(_)
+            | Self::RemoveSearch(_)
+            | Self::EditSetting(_)
+            | Self::ToggleNotificationOption(_)
+            | Self::SetLanguage(_)
+            | Self::SetPageSize(_)
+            | Self::CmdHelp
+            | Self::CmdList
+            | Self::CmdAdd
+            | Self::CmdOverview => None,
+        }
+    }
+
+    /// The label name the action refers to next to its target, if any.
+    pub fn label(&self) -> Option<&'a str> {
+        match *self {
+            Self::MuteLabel(_, label) => Some(label),
+            _ => None,
+        }
+    }
+
+    /// The same action, referring to `label` instead. Actions without a label
+    /// are returned unchanged.
+    pub fn with_label<'b>(&self, label: &'b str) -> CallbackAction<'b>
+    where
+        'a: 'b,
+    {
+        match *self {
+            Self::MuteLabel(target, _) => CallbackAction::MuteLabel(target, label),
+            _ => self.clone(),
+        }
+    }
+
+    /// The same action, referring to `target` instead. Actions without a
+    /// target are returned unchanged.
+    pub fn with_target<'b>(&self, target: &'b str) -> CallbackAction<'b>
+    where
+        'a: 'b,
+    {
+        match *self {
+            Self::ViewRepoDetails(_, from_page) =>
+                CallbackAction::ViewRepoDetails(target, from_page),
+            Self::ViewRepoLabels(_, page, from_page) =>
+                CallbackAction::ViewRepoLabels(target, page, from_page),
+            Self::RemoveRepoPrompt(_) => CallbackAction::RemoveRepoPrompt(target),
+            Self::ToggleLabel(_, page, from_page) =>
+                CallbackAction::ToggleLabel(target, page, from_page),
+            Self::BackToRepoDetails(_, from_page) =>
+                CallbackAction::BackToRepoDetails(target, from_page),
+            Self::ToggleIssueFilter(_, filter, from_page) =>
+                CallbackAction::ToggleIssueFilter(target, filter, from_page),
+            Self::ListReposPage(page) => CallbackAction::ListReposPage(page),
+            Self::BackToRepoList(page) => CallbackAction::BackToRepoList(page),
+            Self::RemoveSearch(id) => CallbackAction::RemoveSearch(id),
+            Self::MuteLabel(_, label) => CallbackAction::MuteLabel(target, label),
+            Self::MuteRepo(_) => CallbackAction::MuteRepo(target),
+            Self::UntrackRepo(_) => CallbackAction::UntrackRepo(target),
+            Self::EditSetting(field) => CallbackAction::EditSetting(field),
+            Self::ToggleNotificationOption(option) =>
+                CallbackAction::ToggleNotificationOption(option),
+            Self::SetLanguage(language) => CallbackAction::SetLanguage(language),
+            Self::SetPageSize(page_size) => CallbackAction::SetPageSize(page_size),
+            Self::CmdHelp => CallbackAction::CmdHelp,
+            Self::CmdList => CallbackAction::CmdList,
+            Self::CmdAdd => CallbackAction::CmdAdd,
+            Self::CmdOverview => CallbackAction::CmdOverview,
+        }
+    }
+}
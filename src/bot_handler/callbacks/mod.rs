@@ -1,7 +1,13 @@
 //! This module contains handlers for callback queries.
 
 pub mod list;
+pub mod mute_label;
+pub mod mute_repo;
 pub mod remove;
+pub mod remove_search;
+pub mod settings;
+pub mod toggle_issue_filter;
 pub mod toggle_label;
+pub mod untrack_repo;
 pub mod view_labels;
 pub mod view_repo;
@@ -0,0 +1,127 @@
+use std::str::FromStr;
+
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, Context, callback_actions},
+    storage::RepoEntity,
+};
+
+pub async fn handle(ctx: Context<'_>, repo_id: &str, label_ref: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let query = ctx
+        .query
+        .ok_or_else(|| BotHandlerError::InvalidInput("Callback query is missing".to_string()))?;
+
+    let repo =
+        RepoEntity::from_str(repo_id).map_err(|e| BotHandlerError::InvalidInput(e.to_string()))?;
+
+    // Labels of repositories that are only tracked through an owner subscription
+    // are shared by all of the owner's repositories
+    let user_repos = ctx.handler.repository_service.get_user_repos(chat_id, 1).await?;
+    if !user_repos.items.contains(&repo) {
+        let text =
+            format!("❓ {repo_id} is tracked through its owner, manage its labels with /org.");
+        ctx.handler.messaging_service.answer_callback_query(&query.id, &Some(text)).await?;
+        return Ok(());
+    }
+
+    // Long label names are sent as references, look them up among the tracked
+    // labels
+    let tracked_labels =
+        ctx.handler.repository_service.get_user_repo_labels(chat_id, &repo).await?;
+    let label_name =
+        tracked_labels.into_iter().find(|name| callback_actions::refers_to(label_ref, name));
+
+    let text = match label_name {
+        Some(label_name) => {
+            ctx.handler.repository_service.toggle_label(chat_id, &repo, &label_name).await?;
+            format!("🔇 No longer tracking {label_name} in {repo_id}.")
+        }
+        None => format!("➡️ This label is not tracked in {repo_id}."),
+    };
+    ctx.handler.messaging_service.answer_callback_query(&query.id, &Some(text)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        pagination::Paginated,
+        repository::MockRepositoryService,
+    };
+
+    #[tokio::test]
+    async fn test_handle_callback_mute_label() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let repo_id = "owner/repo";
+        let repo_entity = RepoEntity::from_str(repo_id).unwrap();
+
+        mock_repository.expect_get_user_repos().with(eq(CHAT_ID), eq(1)).times(1).returning(
+            |_, _| Ok(Paginated::new(vec![RepoEntity::from_str("owner/repo").unwrap()], 1)),
+        );
+        mock_repository
+            .expect_get_user_repo_labels()
+            .with(eq(CHAT_ID), eq(repo_entity.clone()))
+            .times(1)
+            .returning(|_, _| Ok(vec!["bug".to_string(), "good first issue".to_string()]));
+        mock_repository
+            .expect_toggle_label()
+            .with(eq(CHAT_ID), eq(repo_entity), eq("bug"))
+            .times(1)
+            .returning(|_, _, _| Ok(false));
+
+        mock_messaging
+            .expect_answer_callback_query()
+            .withf(|_, text| text.is_none())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_answer_callback_query()
+            .withf(|_, text| text.as_deref() == Some("🔇 No longer tracking bug in owner/repo."))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let action = CallbackAction::MuteLabel(repo_id, "bug");
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handle_callback_mute_label_of_owner_repo() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_get_user_repos()
+            .times(1)
+            .returning(|_, _| Ok(Paginated::new(Vec::new(), 1)));
+        mock_repository.expect_toggle_label().times(0);
+
+        mock_messaging.expect_answer_callback_query().times(2).returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let action = CallbackAction::MuteLabel("owner/repo", "bug");
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,79 @@
+use std::{str::FromStr, time::Duration};
+
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, Context},
+    storage::RepoEntity,
+};
+
+/// How long a repository stays muted.
+const MUTE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub async fn handle(ctx: Context<'_>, repo_id: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let query = ctx
+        .query
+        .ok_or_else(|| BotHandlerError::InvalidInput("Callback query is missing".to_string()))?;
+
+    let repo =
+        RepoEntity::from_str(repo_id).map_err(|e| BotHandlerError::InvalidInput(e.to_string()))?;
+
+    let muted = ctx.handler.repository_service.mute_repo(chat_id, &repo, MUTE_DURATION).await?;
+
+    let text = if muted {
+        format!("🔕 {repo_id} is muted for 24 hours.")
+    } else {
+        format!("❓ {repo_id} is tracked through its owner and can not be muted.")
+    };
+    ctx.handler.messaging_service.answer_callback_query(&query.id, &Some(text)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+    };
+
+    #[tokio::test]
+    async fn test_handle_callback_mute_repo() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let repo_id = "owner/repo";
+
+        mock_repository
+            .expect_mute_repo()
+            .with(eq(CHAT_ID), eq(RepoEntity::from_str(repo_id).unwrap()), eq(MUTE_DURATION))
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+
+        mock_messaging
+            .expect_answer_callback_query()
+            .withf(|_, text| text.is_none())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_answer_callback_query()
+            .withf(|_, text| text.as_deref() == Some("🔕 owner/repo is muted for 24 hours."))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let action = CallbackAction::MuteRepo(repo_id);
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::bot_handler::{BotHandlerError, BotHandlerResult, Context};
+
+pub async fn handle(ctx: Context<'_>, id: i64) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let query = ctx
+        .query
+        .ok_or_else(|| BotHandlerError::InvalidInput("Callback query is missing".to_string()))?;
+
+    let removed = ctx.handler.repository_service.remove_search(chat_id, id).await?;
+
+    let text = if removed { "✅ Search removed." } else { "❌ Search not found." };
+    ctx.handler.messaging_service.answer_callback_query(&query.id, &Some(text.to_string())).await?;
+
+    // Update the list, since the search may have been removed from another
+    // message as well.
+    let searches = ctx.handler.repository_service.get_user_searches(chat_id).await?;
+    ctx.handler.messaging_service.edit_search_list_msg(chat_id, ctx.message.id, searches).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+    };
+
+    #[tokio::test]
+    async fn test_remove_search_updates_list() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_remove_search()
+            .with(eq(CHAT_ID), eq(7))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        mock_repository.expect_get_user_searches().times(1).returning(|_| Ok(Vec::new()));
+        mock_messaging.expect_answer_callback_query().times(2).returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_edit_search_list_msg()
+            .withf(|&chat_id, _, searches| chat_id == CHAT_ID && searches.is_empty())
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness.handle_callback(&CallbackAction::RemoveSearch(7)).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
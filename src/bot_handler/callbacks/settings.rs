@@ -0,0 +1,202 @@
+use crate::{
+    bot_handler::{
+        BotHandlerError, BotHandlerResult, CommandState, Context, commands::settings::SettingField,
+    },
+    storage::{Language, NotificationOption, PAGE_SIZES},
+};
+
+/// Prompt for the new value of a setting that is typed in.
+pub async fn handle_edit(ctx: Context<'_>, field: SettingField) -> BotHandlerResult<()> {
+    ctx.handler.messaging_service.prompt_for_setting_input(ctx.message.chat.id, field).await?;
+    ctx.dialogue
+        .update(CommandState::EditingSetting { field })
+        .await
+        .map_err(BotHandlerError::DialogueError)?;
+    Ok(())
+}
+
+pub async fn handle_toggle_option(
+    ctx: Context<'_>,
+    option: NotificationOption,
+) -> BotHandlerResult<()> {
+    ctx.handler.repository_service.toggle_notification_option(ctx.message.chat.id, option).await?;
+    show_settings(ctx).await
+}
+
+pub async fn handle_set_language(ctx: Context<'_>, language: Language) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let settings = ctx.handler.repository_service.get_user_settings(chat_id).await?;
+    // Telegram rejects edits that leave the message as it is
+    if settings.language == language {
+        return Ok(());
+    }
+
+    ctx.handler.repository_service.set_language(chat_id, language).await?;
+    show_settings(ctx).await
+}
+
+pub async fn handle_set_page_size(ctx: Context<'_>, page_size: usize) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    if !PAGE_SIZES.contains(&page_size) {
+        return Err(BotHandlerError::InvalidInput(format!("Unsupported page size: {page_size}")));
+    }
+
+    let settings = ctx.handler.repository_service.get_user_settings(chat_id).await?;
+    if settings.page_size == page_size {
+        return Ok(());
+    }
+
+    ctx.handler.repository_service.set_page_size(chat_id, page_size).await?;
+    show_settings(ctx).await
+}
+
+// Show the settings again, so the buttons reflect the new state.
+async fn show_settings(ctx: Context<'_>) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let settings = ctx.handler.repository_service.get_user_settings(chat_id).await?;
+    ctx.handler.messaging_service.edit_settings_msg(chat_id, ctx.message.id, settings).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+        storage::UserSettings,
+    };
+
+    #[tokio::test]
+    async fn test_edit_setting_prompts_for_value() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mock_repository = MockRepositoryService::new();
+
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_prompt_for_setting_input()
+            .with(eq(CHAT_ID), eq(SettingField::Timezone))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result =
+            harness.handle_callback(&CallbackAction::EditSetting(SettingField::Timezone)).await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            harness.dialogue.get().await.unwrap(),
+            Some(CommandState::EditingSetting { field: SettingField::Timezone })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_toggle_option_shows_new_state() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let settings = UserSettings { silent_notifications: true, ..Default::default() };
+
+        mock_repository
+            .expect_toggle_notification_option()
+            .with(eq(CHAT_ID), eq(NotificationOption::Silent))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        let shown = settings.clone();
+        mock_repository.expect_get_user_settings().times(1).returning(move |_| Ok(shown.clone()));
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_edit_settings_msg()
+            .withf(move |&chat_id, _, shown| chat_id == CHAT_ID && *shown == settings)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_callback(&CallbackAction::ToggleNotificationOption(NotificationOption::Silent))
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_language() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_get_user_settings()
+            .times(2)
+            .returning(|_| Ok(UserSettings::default()));
+        mock_repository
+            .expect_set_language()
+            .with(eq(CHAT_ID), eq(Language::Spanish))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging.expect_edit_settings_msg().times(1).returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness.handle_callback(&CallbackAction::SetLanguage(Language::Spanish)).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_current_page_size_keeps_message() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_get_user_settings()
+            .times(1)
+            .returning(|_| Ok(UserSettings { page_size: 5, ..Default::default() }));
+        mock_repository.expect_set_page_size().never();
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging.expect_edit_settings_msg().never();
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness.handle_callback(&CallbackAction::SetPageSize(5)).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_unsupported_page_size() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository.expect_set_page_size().never();
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness.handle_callback(&CallbackAction::SetPageSize(1000)).await;
+
+        // Assert
+        assert!(matches!(result, Err(BotHandlerError::InvalidInput(_))));
+    }
+}
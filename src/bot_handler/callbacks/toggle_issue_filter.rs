@@ -0,0 +1,97 @@
+use std::str::FromStr;
+
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, Context, callbacks::view_repo},
+    storage::{IssueFilter, RepoEntity},
+};
+
+pub async fn handle(
+    ctx: Context<'_>,
+    repo_id: &str,
+    filter: IssueFilter,
+    from_page: usize,
+) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let query = ctx
+        .query
+        .ok_or_else(|| BotHandlerError::InvalidInput("Callback query is missing".to_string()))?;
+
+    let repo =
+        RepoEntity::from_str(repo_id).map_err(|e| BotHandlerError::InvalidInput(e.to_string()))?;
+
+    let enabled =
+        ctx.handler.repository_service.toggle_issue_filter(chat_id, &repo, filter).await?;
+
+    let text = match (filter, enabled) {
+        (IssueFilter::Assigned, true) => "✅ Assigned issues will be skipped.",
+        (IssueFilter::Assigned, false) => "✅ Assigned issues will be notified.",
+        (IssueFilter::LinkedPr, true) => "✅ Issues with an open pull request will be skipped.",
+        (IssueFilter::LinkedPr, false) => "✅ Issues with an open pull request will be notified.",
+    };
+    ctx.handler.messaging_service.answer_callback_query(&query.id, &Some(text.to_string())).await?;
+
+    // Show the details again, so the buttons reflect the new state
+    view_repo::handle(ctx, repo_id, from_page, &query.id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use mockall::predicate::eq;
+
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        pagination::Paginated,
+        repository::MockRepositoryService,
+        storage::{IssueFilter, IssueFilters, RepoEntity},
+    };
+
+    #[tokio::test]
+    async fn test_toggle_issue_filter_shows_new_state() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let repo_id = "owner/repo";
+        let filters = IssueFilters { exclude_assigned: true, exclude_linked_prs: false };
+
+        mock_repository
+            .expect_toggle_issue_filter()
+            .with(
+                eq(CHAT_ID),
+                eq(RepoEntity::from_str(repo_id).unwrap()),
+                eq(IssueFilter::Assigned),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        mock_repository
+            .expect_get_repo_github_labels()
+            .times(1)
+            .returning(|_, _, _| Ok(Paginated::new(vec![], 1)));
+        mock_repository.expect_get_issue_filters().times(1).returning(move |_, _| Ok(filters));
+        mock_messaging
+            .expect_answer_callback_query()
+            .withf(|_, text| text.as_deref() == Some("✅ Assigned issues will be skipped."))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_answer_details_callback_query()
+            .withf(move |_, _, _, _, shown, &page| *shown == filters && page == 2)
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let action = CallbackAction::ToggleIssueFilter(repo_id, IssueFilter::Assigned, 2);
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -3,11 +3,11 @@ use std::str::FromStr;
 use futures::{TryFutureExt, try_join};
 
 use crate::{
-    bot_handler::{BotHandlerError, BotHandlerResult, CommandState, Context},
+    bot_handler::{BotHandlerError, BotHandlerResult, CommandState, Context, callback_actions},
     storage::RepoEntity,
 };
 
-pub async fn handle(ctx: Context<'_>, label_name: &str, label_page: usize) -> BotHandlerResult<()> {
+pub async fn handle(ctx: Context<'_>, label_ref: &str, label_page: usize) -> BotHandlerResult<()> {
     let chat_id = ctx.message.chat.id;
     let query = ctx
         .query
@@ -28,6 +28,22 @@ pub async fn handle(ctx: Context<'_>, label_name: &str, label_page: usize) -> Bo
     let repo =
         RepoEntity::from_str(&repo_id).map_err(|e| BotHandlerError::InvalidInput(e.to_string()))?;
 
+    // Long label names are sent as references, look them up among the labels of
+    // the repository
+    let label_name = if callback_actions::is_callback_ref(label_ref) {
+        &ctx.handler
+            .repository_service
+            .get_repo_github_labels(chat_id, &repo, label_page)
+            .await?
+            .items
+            .into_iter()
+            .map(|label| label.name)
+            .find(|name| callback_actions::refers_to(label_ref, name))
+            .ok_or_else(|| BotHandlerError::InvalidInput("Label no longer exists".to_string()))?
+    } else {
+        label_ref
+    };
+
     // Try to toggle the label for the repository and handle potential limit errors.
     let is_selected =
         ctx.handler.repository_service.toggle_label(chat_id, &repo, label_name).await?;
@@ -52,3 +68,84 @@ pub async fn handle(ctx: Context<'_>, label_name: &str, label_page: usize) -> Bo
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            callback_actions::callback_ref,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        pagination::Paginated,
+        repository::{LabelNormalized, MockRepositoryService},
+    };
+
+    #[tokio::test]
+    async fn test_handle_callback_toggle_label_by_reference() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let repo_id = "owner/repo";
+        let label_name = "l".repeat(50);
+        let repo_entity = RepoEntity::from_str(repo_id).unwrap();
+
+        let labels = Paginated::new(
+            vec![
+                LabelNormalized {
+                    name: "bug".to_string(),
+                    color: "d73a4a".to_string(),
+                    count: 3,
+                    is_selected: false,
+                },
+                LabelNormalized {
+                    name: label_name.clone(),
+                    color: "0e8a16".to_string(),
+                    count: 1,
+                    is_selected: false,
+                },
+            ],
+            1,
+        );
+        mock_repository
+            .expect_get_repo_github_labels()
+            .with(eq(CHAT_ID), eq(repo_entity.clone()), eq(1))
+            .times(2)
+            .returning(move |_, _, _| Ok(labels.clone()));
+        mock_repository
+            .expect_toggle_label()
+            .with(eq(CHAT_ID), eq(repo_entity), eq(label_name.clone()))
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_answer_toggle_label_callback_query()
+            .withf({
+                let label_name = label_name.clone();
+                move |_, name, is_selected| name == label_name && *is_selected
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        mock_messaging.expect_edit_labels_msg().times(1).returning(|_, _, _, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        harness
+            .dialogue
+            .update(CommandState::ViewingRepoLabels { repo_id: repo_id.to_string(), from_page: 1 })
+            .await
+            .unwrap();
+        let label_ref = callback_ref(&label_name);
+        let action = CallbackAction::ToggleLabel(&label_ref, 1, 1);
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::bot_handler::{BotHandlerError, BotHandlerResult, Context};
+
+pub async fn handle(ctx: Context<'_>, repo_id: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let query = ctx
+        .query
+        .ok_or_else(|| BotHandlerError::InvalidInput("Callback query is missing".to_string()))?;
+
+    // Unlike removing from the repository list, the notification is left as is.
+    let removed = ctx.handler.repository_service.remove_repo(chat_id, repo_id).await?;
+
+    let text = if removed {
+        format!("✅ Stopped tracking {repo_id}.")
+    } else {
+        format!("❓ {repo_id} is not tracked directly, use /unorg to stop tracking its owner.")
+    };
+    ctx.handler.messaging_service.answer_callback_query(&query.id, &Some(text)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use crate::{
+        bot_handler::{
+            CallbackAction,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+    };
+
+    #[tokio::test]
+    async fn test_handle_callback_untrack_repo() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let repo_id = "owner/repo";
+
+        mock_repository
+            .expect_remove_repo()
+            .with(eq(CHAT_ID), eq(repo_id))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        mock_messaging.expect_answer_callback_query().times(2).returning(|_, _| Ok(()));
+        mock_messaging.expect_edit_list_msg().times(0);
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let action = CallbackAction::UntrackRepo(repo_id);
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
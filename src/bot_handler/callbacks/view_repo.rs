@@ -28,10 +28,19 @@ pub async fn handle(
         .filter(|l| l.is_selected)
         .collect::<Vec<_>>();
 
+    let filters = ctx.handler.repository_service.get_issue_filters(chat_id, &repo).await?;
+
     // Answer the callback query to clear the spinner.
     ctx.handler
         .messaging_service
-        .answer_details_callback_query(chat_id, ctx.message.id, &repo, &repo_labels, from_page)
+        .answer_details_callback_query(
+            chat_id,
+            ctx.message.id,
+            &repo,
+            &repo_labels,
+            &filters,
+            from_page,
+        )
         .await?;
 
     // Reset the dialogue state
@@ -48,11 +57,13 @@ mod tests {
     use crate::{
         bot_handler::{
             CallbackAction,
+            callback_actions::callback_ref,
             test_helpers::{CHAT_ID, TestHarness},
         },
         messaging::MockMessagingService,
         pagination::Paginated,
         repository::MockRepositoryService,
+        storage::IssueFilters,
     };
 
     #[tokio::test]
@@ -69,19 +80,23 @@ mod tests {
             .with(eq(CHAT_ID), eq(repo_entity.clone()), eq(1))
             .times(1)
             .returning(|_, _, _| Ok(Paginated::new(vec![], 1)));
+        mock_repository
+            .expect_get_issue_filters()
+            .times(1)
+            .returning(|_, _| Ok(IssueFilters::default()));
 
         mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
 
         mock_messaging
             .expect_answer_details_callback_query()
-            .withf(move |&cid, _, repo, labels, page| {
+            .withf(move |&cid, _, repo, labels, _, page| {
                 cid == CHAT_ID
                     && repo.name_with_owner == repo_id
                     && labels.is_empty()
                     && *page == from_page
             })
             .times(1)
-            .returning(|_, _, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _| Ok(()));
 
         let harness = TestHarness::new(mock_messaging, mock_repository).await;
         let action = CallbackAction::ViewRepoDetails(repo_id, from_page);
@@ -111,19 +126,23 @@ mod tests {
             .with(eq(CHAT_ID), eq(RepoEntity::from_str(repo_id).unwrap()), eq(1))
             .times(1)
             .returning(|_, _, _| Ok(Paginated::new(vec![], 1)));
+        mock_repository
+            .expect_get_issue_filters()
+            .times(1)
+            .returning(|_, _| Ok(IssueFilters::default()));
 
         mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
 
         mock_messaging
             .expect_answer_details_callback_query()
-            .withf(move |&cid, _, repo, labels, page| {
+            .withf(move |&cid, _, repo, labels, _, page| {
                 cid == CHAT_ID
                     && repo.name_with_owner == repo_id
                     && labels.is_empty()
                     && *page == from_page
             })
             .times(1)
-            .returning(|_, _, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _| Ok(()));
 
         let harness = TestHarness::new(mock_messaging, mock_repository).await;
         let action = CallbackAction::BackToRepoDetails(repo_id, from_page);
@@ -139,4 +158,75 @@ mod tests {
             "Dialogue state should be reset to None"
         );
     }
+
+    #[tokio::test]
+    async fn test_handle_callback_view_repo_details_by_reference() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let repo_id = format!("{}/{}", "o".repeat(39), "r".repeat(100));
+        let repo_entity = RepoEntity::from_str(&repo_id).unwrap();
+
+        let tracked = vec![RepoEntity::from_str("owner/repo").unwrap(), repo_entity.clone()];
+        mock_repository
+            .expect_get_user_repos()
+            .with(eq(CHAT_ID), eq(1))
+            .times(1)
+            .returning(move |_, page| Ok(Paginated::new(tracked.clone(), page)));
+        mock_repository
+            .expect_get_repo_github_labels()
+            .with(eq(CHAT_ID), eq(repo_entity), eq(1))
+            .times(1)
+            .returning(|_, _, _| Ok(Paginated::new(vec![], 1)));
+        mock_repository
+            .expect_get_issue_filters()
+            .times(1)
+            .returning(|_, _| Ok(IssueFilters::default()));
+
+        mock_messaging.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_answer_details_callback_query()
+            .withf({
+                let repo_id = repo_id.clone();
+                move |_, _, repo, _, _, _| repo.name_with_owner == repo_id
+            })
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let repo_ref = callback_ref(&repo_id);
+        let action = CallbackAction::ViewRepoDetails(&repo_ref, 1);
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handle_callback_view_repo_details_unknown_reference() {
+        // Arrange
+        let mock_messaging = {
+            let mut mock = MockMessagingService::new();
+            mock.expect_answer_callback_query().times(1).returning(|_, _| Ok(()));
+            mock
+        };
+        let mut mock_repository = MockRepositoryService::new();
+        mock_repository
+            .expect_get_user_repos()
+            .times(1)
+            .returning(|_, page| Ok(Paginated::new(vec![], page)));
+        mock_repository.expect_get_repo_github_labels().times(0);
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let repo_ref = callback_ref("owner/removed");
+        let action = CallbackAction::ViewRepoDetails(&repo_ref, 1);
+
+        // Act
+        let result = harness.handle_callback(&action).await;
+
+        // Assert
+        assert!(matches!(result, Err(BotHandlerError::InvalidInput(_))));
+    }
 }
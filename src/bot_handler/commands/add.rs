@@ -4,7 +4,7 @@ use futures::{StreamExt, stream};
 
 use crate::{
     bot_handler::{BotHandlerError, BotHandlerResult, CommandState, commands::Context},
-    storage::RepoEntity,
+    storage::{RepoEntity, RepoInputNormalization},
 };
 
 /// A struct to hold the summary of the add operation.
@@ -20,6 +20,9 @@ pub struct AddSummary {
     pub invalid_urls: HashSet<String>,
     /// Repositories that failed to be added due to an error.
     pub errors: HashSet<(String, String)>,
+    /// Inputs that had to be normalized to find the repository, with the
+    /// repository and the normalizations that were applied.
+    pub normalized: HashSet<(String, String)>,
 }
 
 pub async fn handle(ctx: Context<'_>) -> BotHandlerResult<()> {
@@ -40,6 +43,13 @@ enum AddRepoResult {
     Error(String, String),
 }
 
+// Describe how an input was normalized, e.g. "owner/repo (SSH URL, .git suffix
+// removed)".
+fn describe_normalizations(repo: &RepoEntity, normalizations: &[RepoInputNormalization]) -> String {
+    let applied = normalizations.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+    format!("{} ({applied})", repo.name_with_owner)
+}
+
 /// Handle the reply message when we're waiting for repository input.
 /// It processes the input, checks each URL, and adds the repositories
 /// accordingly.
@@ -67,28 +77,46 @@ pub async fn handle_reply(ctx: Context<'_>, text: &str) -> BotHandlerResult<()>
 
     let summary = stream::iter(urls)
         .map(|url| async move {
-            let repo = match RepoEntity::from_url(&url) {
-                Ok(repo) => repo,
-                Err(_) => return AddRepoResult::InvalidUrl(url),
+            let (repo, mut normalizations) = match RepoEntity::from_input(&url) {
+                Ok(parsed) => parsed,
+                Err(_) => return (AddRepoResult::InvalidUrl(url), None),
             };
 
-            match ctx.handler.repository_service.repo_exists(&repo.owner, &repo.name).await {
-                Ok(true) => match ctx
-                    .handler
-                    .repository_service
-                    .add_repo(ctx.message.chat.id, repo.clone())
-                    .await
-                {
-                    Ok(true) => AddRepoResult::Success(repo.name_with_owner),
-                    Ok(false) => AddRepoResult::AlreadyTracked(repo.name_with_owner),
-                    Err(e) => AddRepoResult::Error(repo.name_with_owner, e.to_string()),
-                },
-                Ok(false) => AddRepoResult::NotFound(repo.name_with_owner),
+            // Store the repository under its canonical name, so it matches what
+            // GitHub reports later on
+            let repo =
+                match ctx.handler.repository_service.resolve_repo(&repo.owner, &repo.name).await {
+                    Ok(Some(canonical)) => {
+                        if canonical.name_with_owner != repo.name_with_owner {
+                            normalizations.push(RepoInputNormalization::CanonicalName);
+                        }
+                        canonical
+                    }
+                    Ok(None) => return (AddRepoResult::NotFound(repo.name_with_owner), None),
+                    Err(e) => {
+                        return (AddRepoResult::Error(repo.name_with_owner, e.to_string()), None);
+                    }
+                };
+            let normalized = (!normalizations.is_empty())
+                .then(|| (url, describe_normalizations(&repo, &normalizations)));
+
+            let result = match ctx
+                .handler
+                .repository_service
+                .add_repo(ctx.message.chat.id, repo.clone())
+                .await
+            {
+                Ok(true) => AddRepoResult::Success(repo.name_with_owner),
+                Ok(false) => AddRepoResult::AlreadyTracked(repo.name_with_owner),
                 Err(e) => AddRepoResult::Error(repo.name_with_owner, e.to_string()),
-            }
+            };
+            (result, normalized)
         })
         .buffer_unordered(ctx.handler.max_concurrency)
-        .fold(AddSummary::default(), |mut summary, res| async move {
+        .fold(AddSummary::default(), |mut summary, (res, normalized)| async move {
+            if let Some(normalized) = normalized {
+                summary.normalized.insert(normalized);
+            }
             match res {
                 AddRepoResult::Success(name) => {
                     summary.successfully_added.insert(name);
@@ -127,7 +155,8 @@ mod tests {
         bot_handler::{
             Command,
             test_helpers::{
-                CHAT_ID, TestHarness, setup_add_repo_mocks, str_hashset, str_tuple_hashset,
+                CHAT_ID, TestHarness, resolve_as_is, setup_add_repo_mocks, str_hashset,
+                str_tuple_hashset,
             },
         },
         github::GithubError,
@@ -150,10 +179,10 @@ mod tests {
         setup_add_repo_mocks(&mut mock_messaging);
 
         mock_repository
-            .expect_repo_exists()
+            .expect_resolve_repo()
             .with(eq(repo_owner), eq(repo_name))
             .times(1)
-            .returning(|_, _| Ok(true));
+            .returning(resolve_as_is);
         mock_repository
             .expect_add_repo()
             .withf(move |&id, entity| {
@@ -183,6 +212,86 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_add_repos_normalized_inputs() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let ssh_url = "git@github.com:owner/repo.git";
+        let shorthand = "owner/other";
+        let issue_url = "https://www.github.com/owner/third/issues/7";
+
+        setup_add_repo_mocks(&mut mock_messaging);
+
+        mock_repository.expect_resolve_repo().times(3).returning(resolve_as_is);
+        mock_repository.expect_add_repo().times(3).returning(|_, _| Ok(true));
+
+        let expected_summary = AddSummary {
+            successfully_added: str_hashset(&["owner/repo", "owner/other", "owner/third"]),
+            normalized: str_tuple_hashset(&[
+                (ssh_url, "owner/repo (SSH URL, .git suffix removed)"),
+                (shorthand, "owner/other (owner/repo shorthand)"),
+                (issue_url, "owner/third (www. removed, taken from issue URL)"),
+            ]),
+            ..Default::default()
+        };
+        mock_messaging
+            .expect_edit_add_summary_msg()
+            .withf(move |&chat_id_param, _, summary| {
+                chat_id_param == CHAT_ID && summary == &expected_summary
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness.handle_add_reply(&format!("{ssh_url} {shorthand}\n{issue_url}")).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_add_repos_stores_canonical_name() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let input = "https://github.com/Rust-Lang/RUST";
+
+        setup_add_repo_mocks(&mut mock_messaging);
+
+        mock_repository
+            .expect_resolve_repo()
+            .with(eq("Rust-Lang"), eq("RUST"))
+            .times(1)
+            .returning(|_, _| resolve_as_is("rust-lang", "rust"));
+        mock_repository
+            .expect_add_repo()
+            .withf(|&id, entity| id == CHAT_ID && entity.name_with_owner == "rust-lang/rust")
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        let expected_summary = AddSummary {
+            successfully_added: str_hashset(&["rust-lang/rust"]),
+            normalized: str_tuple_hashset(&[(input, "rust-lang/rust (canonical name on GitHub)")]),
+            ..Default::default()
+        };
+        mock_messaging
+            .expect_edit_add_summary_msg()
+            .withf(move |_, _, summary| summary == &expected_summary)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness.handle_add_reply(input).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_add_repos_already_tracked() {
         // Arrange
@@ -193,7 +302,7 @@ mod tests {
 
         setup_add_repo_mocks(&mut mock_messaging);
 
-        mock_repository.expect_repo_exists().returning(|_, _| Ok(true));
+        mock_repository.expect_resolve_repo().returning(resolve_as_is);
         mock_repository
             .expect_add_repo()
             .withf(move |&id, entity| {
@@ -232,7 +341,7 @@ mod tests {
 
         setup_add_repo_mocks(&mut mock_messaging);
 
-        mock_repository.expect_repo_exists().returning(|_, _| Ok(false));
+        mock_repository.expect_resolve_repo().returning(|_, _| Ok(None));
 
         let expected_summary =
             AddSummary { not_found: str_hashset(&[repo_name_with_owner]), ..Default::default() };
@@ -290,7 +399,7 @@ mod tests {
 
         setup_add_repo_mocks(&mut mock_messaging);
 
-        mock_repository.expect_repo_exists().returning(move |_, _| {
+        mock_repository.expect_resolve_repo().returning(move |_, _| {
             Err(RepositoryServiceError::GithubClientError(GithubError::Unauthorized))
         });
 
@@ -328,7 +437,7 @@ repositories.";
 
         setup_add_repo_mocks(&mut mock_messaging);
 
-        mock_repository.expect_repo_exists().returning(|_, _| Ok(true));
+        mock_repository.expect_resolve_repo().returning(resolve_as_is);
         mock_repository.expect_add_repo().returning(move |_, _| {
             Err(RepositoryServiceError::LimitExceeded(limit_error_msg.to_string()))
         });
@@ -376,33 +485,30 @@ repositories.";
 
         setup_add_repo_mocks(&mut mock_messaging);
 
-        mock_repository
-            .expect_repo_exists()
-            .with(eq("owner"), eq("new"))
-            .returning(|_, _| Ok(true));
+        mock_repository.expect_resolve_repo().with(eq("owner"), eq("new")).returning(resolve_as_is);
         mock_repository
             .expect_add_repo()
             .withf(move |_, e: &RepoEntity| e.name_with_owner == name_new)
             .returning(|_, _| Ok(true));
         mock_repository
-            .expect_repo_exists()
+            .expect_resolve_repo()
             .with(eq("owner"), eq("tracked"))
-            .returning(|_, _| Ok(true));
+            .returning(resolve_as_is);
         mock_repository
             .expect_add_repo()
             .withf(move |_, e: &RepoEntity| e.name_with_owner == name_tracked)
             .returning(|_, _| Ok(false));
         mock_repository
-            .expect_repo_exists()
+            .expect_resolve_repo()
             .with(eq("owner"), eq("notfound"))
-            .returning(|_, _| Ok(false));
-        mock_repository.expect_repo_exists().with(eq("owner"), eq("gh-error")).returning(
+            .returning(|_, _| Ok(None));
+        mock_repository.expect_resolve_repo().with(eq("owner"), eq("gh-error")).returning(
             move |_, _| Err(RepositoryServiceError::GithubClientError(GithubError::Unauthorized)),
         );
         mock_repository
-            .expect_repo_exists()
+            .expect_resolve_repo()
             .with(eq("owner"), eq("add-error"))
-            .returning(|_, _| Ok(true));
+            .returning(resolve_as_is);
         mock_repository
             .expect_add_repo()
             .withf(move |_, e: &RepoEntity| e.name_with_owner == name_add_error)
@@ -421,6 +527,7 @@ repositories.";
                 (name_gh_error, gh_error_msg),
                 (name_add_error, &add_error_msg),
             ]),
+            normalized: HashSet::new(),
         };
 
         mock_messaging
@@ -463,10 +570,10 @@ repositories.";
 
         // Expect the repository to exist
         mock_repository
-            .expect_repo_exists()
+            .expect_resolve_repo()
             .with(eq("owner"), eq("repo"))
             .times(1)
-            .returning(|_, _| Ok(true));
+            .returning(resolve_as_is);
 
         // Expect the repository to be added
         mock_repository
@@ -546,10 +653,10 @@ repositories.";
 
         // Mock the repository interactions for a successful add
         mock_repository
-            .expect_repo_exists()
+            .expect_resolve_repo()
             .with(eq("owner"), eq("repo"))
             .times(1)
-            .returning(|_, _| Ok(true));
+            .returning(resolve_as_is);
         mock_repository
             .expect_add_repo()
             .withf(move |&id, e| id == CHAT_ID && e.name_with_owner == repo_name_with_owner)
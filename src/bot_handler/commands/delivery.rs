@@ -0,0 +1,150 @@
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, commands::Context},
+    storage::DeliveryMode,
+};
+
+/// Handle `/delivery [mode]`. Without arguments the current delivery mode is
+/// shown; otherwise notifications are delivered as chosen from now on.
+pub async fn handle(ctx: Context<'_>, args: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let args = args.trim();
+
+    if args.is_empty() {
+        let settings = ctx.handler.repository_service.get_user_settings(chat_id).await?;
+        ctx.handler.messaging_service.send_delivery_mode_msg(chat_id, settings, false).await?;
+        return Ok(());
+    }
+
+    let delivery_mode = match args.parse::<DeliveryMode>() {
+        Ok(delivery_mode) => delivery_mode,
+        Err(e) => {
+            ctx.handler
+                .messaging_service
+                .send_error_msg(chat_id, BotHandlerError::InvalidInput(e.to_string()))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    ctx.handler.repository_service.set_delivery_mode(chat_id, delivery_mode).await?;
+    let settings = ctx.handler.repository_service.get_user_settings(chat_id).await?;
+    ctx.handler.messaging_service.send_delivery_mode_msg(chat_id, settings, true).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            Command,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+        storage::UserSettings,
+    };
+
+    #[tokio::test]
+    async fn test_delivery_sets_mode() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let daily = DeliveryMode::Daily(NaiveTime::from_hms_opt(18, 30, 0).unwrap());
+
+        mock_repository
+            .expect_set_delivery_mode()
+            .with(eq(CHAT_ID), eq(daily))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let settings = UserSettings { delivery_mode: daily, ..Default::default() };
+        let returned = settings.clone();
+        mock_repository
+            .expect_get_user_settings()
+            .with(eq(CHAT_ID))
+            .times(1)
+            .returning(move |_| Ok(returned.clone()));
+        mock_messaging
+            .expect_send_delivery_mode_msg()
+            .with(eq(CHAT_ID), eq(settings), eq(true))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Delivery("daily 18:30".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_without_args_shows_mode() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_get_user_settings()
+            .with(eq(CHAT_ID))
+            .times(1)
+            .returning(|_| Ok(UserSettings::default()));
+        mock_messaging
+            .expect_send_delivery_mode_msg()
+            .with(eq(CHAT_ID), eq(UserSettings::default()), eq(false))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Delivery(String::new()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_invalid_mode() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository.expect_set_delivery_mode().never();
+        mock_messaging
+            .expect_send_error_msg()
+            .withf(|&chat_id, error| {
+                chat_id == CHAT_ID && matches!(error, BotHandlerError::InvalidInput(_))
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Delivery("monthly".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -1,10 +1,15 @@
 //! This module contains handlers for bot commands.
 
 pub mod add;
+pub mod delivery;
 pub mod help;
 pub mod list;
+pub mod org;
 pub mod overview;
+pub mod search;
+pub mod settings;
 pub mod start;
+pub mod status;
 
 use async_trait::async_trait;
 
@@ -26,6 +31,13 @@ impl CommandHandler for super::Command {
             super::Command::Add => add::handle(ctx).await,
             super::Command::Start => start::handle(ctx).await,
             super::Command::Overview => overview::handle(ctx).await,
+            super::Command::Org(args) => org::handle(ctx, &args).await,
+            super::Command::Unorg(args) => org::handle_remove(ctx, &args).await,
+            super::Command::Search => search::handle(ctx).await,
+            super::Command::Searches => search::handle_list(ctx).await,
+            super::Command::Delivery(args) => delivery::handle(ctx, &args).await,
+            super::Command::Settings(args) => settings::handle(ctx, &args).await,
+            super::Command::Status => status::handle(ctx).await,
         }
     }
 }
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, commands::Context},
+    repository::RepositoryServiceError,
+};
+
+/// The longest login GitHub allows.
+const MAX_LOGIN_LEN: usize = 39;
+
+/// Handle `/org [name] [labels]`. Without arguments the tracked organizations
+/// and users are listed; otherwise every repository of the named owner is
+/// tracked, with the given comma separated labels if there are any.
+pub async fn handle(ctx: Context<'_>, args: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let args = args.trim();
+
+    if args.is_empty() {
+        let owners = ctx.handler.repository_service.get_user_owners(chat_id).await?;
+        ctx.handler.messaging_service.send_owner_list_msg(chat_id, owners).await?;
+        return Ok(());
+    }
+
+    let (input, labels) = split_args(args);
+    let Some(login) = parse_owner(input) else {
+        ctx.handler
+            .messaging_service
+            .send_error_msg(
+                chat_id,
+                BotHandlerError::InvalidInput(format!("Invalid organization or user: {input}")),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let Some(login) = ctx.handler.repository_service.resolve_owner(login).await? else {
+        ctx.handler
+            .messaging_service
+            .send_error_msg(
+                chat_id,
+                BotHandlerError::InvalidInput(format!(
+                    "Organization or user not found on GitHub: {login}"
+                )),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let result = async {
+        let added = ctx.handler.repository_service.add_owner(chat_id, &login).await?;
+        if let Some(labels) = labels {
+            ctx.handler.repository_service.set_owner_labels(chat_id, &login, labels).await?;
+        }
+        Ok(added)
+    }
+    .await;
+
+    let added = match result {
+        Ok(added) => added,
+        Err(e @ RepositoryServiceError::LimitExceeded(_)) => {
+            ctx.handler.messaging_service.send_error_msg(chat_id, e.into()).await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let owner = ctx
+        .handler
+        .repository_service
+        .get_user_owners(chat_id)
+        .await?
+        .into_iter()
+        .find(|owner| owner.owner.eq_ignore_ascii_case(&login))
+        .ok_or_else(|| BotHandlerError::InvalidInput(format!("{login} is not tracked")))?;
+
+    ctx.handler.messaging_service.send_owner_tracked_msg(chat_id, &owner, added).await?;
+
+    Ok(())
+}
+
+/// Handle `/unorg name`, which stops tracking the repositories of an owner.
+pub async fn handle_remove(ctx: Context<'_>, args: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let input = args.trim();
+
+    let Some(login) = parse_owner(input) else {
+        ctx.handler
+            .messaging_service
+            .send_error_msg(
+                chat_id,
+                BotHandlerError::InvalidInput(format!("Invalid organization or user: {input}")),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let removed = ctx.handler.repository_service.remove_owner(chat_id, login).await?;
+    ctx.handler.messaging_service.send_owner_removed_msg(chat_id, login, removed).await?;
+
+    Ok(())
+}
+
+// Split the arguments into the owner and the labels following it, e.g.
+// "rust-lang E-easy, help wanted".
+fn split_args(args: &str) -> (&str, Option<HashSet<String>>) {
+    let Some((input, labels)) = args.split_once(char::is_whitespace) else {
+        return (args, None);
+    };
+
+    let labels: HashSet<String> = labels
+        .split(',')
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(String::from)
+        .collect();
+
+    (input, (!labels.is_empty()).then_some(labels))
+}
+
+// Extract the login from `name`, `@name` or the URL of a GitHub profile.
+fn parse_owner(input: &str) -> Option<&str> {
+    let login = input.strip_prefix('@').unwrap_or(input);
+    let login =
+        login.strip_prefix("https://").or_else(|| login.strip_prefix("http://")).unwrap_or(login);
+    let login = login.strip_prefix("www.").unwrap_or(login);
+    let login = login.strip_prefix("github.com/").unwrap_or(login).trim_end_matches('/');
+
+    let is_valid = !login.is_empty()
+        && login.len() <= MAX_LOGIN_LEN
+        && login.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    is_valid.then_some(login)
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            Command,
+            test_helpers::{CHAT_ID, TestHarness, str_hashset},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+        storage::OwnerSubscription,
+    };
+
+    #[test]
+    fn test_parse_owner() {
+        assert_eq!(parse_owner("rust-lang"), Some("rust-lang"));
+        assert_eq!(parse_owner("@rust-lang"), Some("rust-lang"));
+        assert_eq!(parse_owner("https://github.com/rust-lang/"), Some("rust-lang"));
+        assert_eq!(parse_owner("www.github.com/rust-lang"), Some("rust-lang"));
+        assert_eq!(parse_owner("rust-lang/rust"), None);
+        assert_eq!(parse_owner("https://gitlab.com/rust-lang"), None);
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split_args("rust-lang"), ("rust-lang", None));
+        assert_eq!(
+            split_args("rust-lang E-easy, help wanted,"),
+            ("rust-lang", Some(str_hashset(&["E-easy", "help wanted"])))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_org_tracks_owner_with_labels() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let owner = OwnerSubscription {
+            owner: "rust-lang".to_string(),
+            tracked_labels: str_hashset(&["E-easy"]),
+        };
+
+        mock_repository
+            .expect_resolve_owner()
+            .with(eq("Rust-Lang"))
+            .times(1)
+            .returning(|_| Ok(Some("rust-lang".to_string())));
+        mock_repository
+            .expect_add_owner()
+            .with(eq(CHAT_ID), eq("rust-lang"))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        mock_repository
+            .expect_set_owner_labels()
+            .with(eq(CHAT_ID), eq("rust-lang"), eq(str_hashset(&["E-easy"])))
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        let owners = vec![owner.clone()];
+        mock_repository.expect_get_user_owners().times(1).returning(move |_| Ok(owners.clone()));
+        mock_messaging
+            .expect_send_owner_tracked_msg()
+            .with(eq(CHAT_ID), eq(owner), eq(true))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Org("https://github.com/Rust-Lang E-easy".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_org_not_found() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository.expect_resolve_owner().times(1).returning(|_| Ok(None));
+        mock_repository.expect_add_owner().never();
+        mock_messaging
+            .expect_send_error_msg()
+            .withf(|&chat_id, error| {
+                chat_id == CHAT_ID && matches!(error, BotHandlerError::InvalidInput(_))
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Org("missing".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_org_without_args_lists_owners() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository.expect_get_user_owners().times(1).returning(|_| Ok(Vec::new()));
+        mock_messaging
+            .expect_send_owner_list_msg()
+            .with(eq(CHAT_ID), eq(Vec::new()))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(Command::Org(String::new()), harness.dialogue.clone())
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unorg() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_remove_owner()
+            .with(eq(CHAT_ID), eq("rust-lang"))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        mock_messaging
+            .expect_send_owner_removed_msg()
+            .with(eq(CHAT_ID), eq("rust-lang"), eq(true))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Unorg("@rust-lang".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
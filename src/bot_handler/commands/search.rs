@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, CommandState, commands::Context},
+    repository::RepositoryServiceError,
+    storage::SearchFilter,
+};
+
+/// The reply that keeps the default of a search criterion.
+const SKIP_INPUT: &str = "-";
+
+/// The criteria asked for, in order, when building a search.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SearchStep {
+    /// The label of the issues.
+    Label,
+    /// The language of the repositories.
+    Language,
+    /// The minimum number of stars of the repositories.
+    MinStars,
+    /// A topic of the repositories.
+    Topic,
+}
+
+impl SearchStep {
+    // The step following this one, if any.
+    fn next(self) -> Option<Self> {
+        match self {
+            Self::Label => Some(Self::Language),
+            Self::Language => Some(Self::MinStars),
+            Self::MinStars => Some(Self::Topic),
+            Self::Topic => None,
+        }
+    }
+}
+
+/// Handle `/search`, which asks for the search criteria one at a time.
+pub async fn handle(ctx: Context<'_>) -> BotHandlerResult<()> {
+    let step = SearchStep::Label;
+    ctx.handler.messaging_service.prompt_for_search_input(ctx.message.chat.id, step).await?;
+    ctx.dialogue
+        .update(CommandState::BuildingSearch { step, filter: SearchFilter::default() })
+        .await
+        .map_err(BotHandlerError::DialogueError)?;
+    Ok(())
+}
+
+/// Handle the reply to a search criterion prompt. Invalid input is reported
+/// and asked for again; after the last criterion the search is saved.
+pub async fn handle_reply(
+    ctx: Context<'_>,
+    step: SearchStep,
+    mut filter: SearchFilter,
+    text: &str,
+) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let input = Some(text.trim()).filter(|&input| input != SKIP_INPUT);
+
+    let result = match step {
+        SearchStep::Label => filter.set_label(input),
+        SearchStep::Language => filter.set_language(input),
+        SearchStep::MinStars => filter.set_min_stars(input),
+        SearchStep::Topic => filter.set_topic(input),
+    };
+
+    if let Err(e) = result {
+        ctx.handler
+            .messaging_service
+            .send_error_msg(chat_id, BotHandlerError::InvalidInput(e.to_string()))
+            .await?;
+        ctx.handler.messaging_service.prompt_for_search_input(chat_id, step).await?;
+        return Ok(());
+    }
+
+    if let Some(next) = step.next() {
+        ctx.handler.messaging_service.prompt_for_search_input(chat_id, next).await?;
+        ctx.dialogue
+            .update(CommandState::BuildingSearch { step: next, filter })
+            .await
+            .map_err(BotHandlerError::DialogueError)?;
+        return Ok(());
+    }
+
+    ctx.dialogue.exit().await.map_err(BotHandlerError::DialogueError)?;
+
+    match ctx.handler.repository_service.add_search(chat_id, &filter).await {
+        Ok(added) => {
+            ctx.handler.messaging_service.send_search_added_msg(chat_id, &filter, added).await?;
+        }
+        Err(e @ RepositoryServiceError::LimitExceeded(_)) => {
+            ctx.handler.messaging_service.send_error_msg(chat_id, e.into()).await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(())
+}
+
+/// Handle `/searches`, which lists the user's searches.
+pub async fn handle_list(ctx: Context<'_>) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let searches = ctx.handler.repository_service.get_user_searches(chat_id).await?;
+    ctx.handler.messaging_service.send_search_list_msg(chat_id, searches).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::{Sequence, predicate::eq};
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            Command,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+    };
+
+    #[tokio::test]
+    async fn test_search_wizard_adds_search() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let mut seq = Sequence::new();
+        let expected = SearchFilter {
+            label: "help wanted".to_string(),
+            language: Some("rust".to_string()),
+            topic: None,
+            min_stars: Some(100),
+        };
+
+        for step in [SearchStep::Label, SearchStep::Language, SearchStep::MinStars] {
+            mock_messaging
+                .expect_prompt_for_search_input()
+                .with(eq(CHAT_ID), eq(step))
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_, _| Ok(()));
+        }
+        // The first answer is invalid, so the stars are asked for again.
+        mock_messaging
+            .expect_send_error_msg()
+            .withf(|&chat_id, error| {
+                chat_id == CHAT_ID && matches!(error, BotHandlerError::InvalidInput(_))
+            })
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        for step in [SearchStep::MinStars, SearchStep::Topic] {
+            mock_messaging
+                .expect_prompt_for_search_input()
+                .with(eq(CHAT_ID), eq(step))
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_, _| Ok(()));
+        }
+        mock_repository
+            .expect_add_search()
+            .with(eq(CHAT_ID), eq(expected.clone()))
+            .times(1)
+            .returning(|_, _| Ok(true));
+        mock_messaging
+            .expect_send_search_added_msg()
+            .with(eq(CHAT_ID), eq(expected), eq(true))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        harness
+            .handle_command_with_dialogue(Command::Search, harness.dialogue.clone())
+            .await
+            .unwrap();
+        for reply in ["help wanted", "Rust", "many", "100", "-"] {
+            harness.handle_reply_with_dialogue(reply, &harness.new_dialogue()).await.unwrap();
+        }
+
+        // Assert
+        assert_eq!(harness.dialogue.get().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_searches_lists_searches() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_get_user_searches()
+            .with(eq(CHAT_ID))
+            .times(1)
+            .returning(|_| Ok(Vec::new()));
+        mock_messaging
+            .expect_send_search_list_msg()
+            .with(eq(CHAT_ID), eq(Vec::new()))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result =
+            harness.handle_command_with_dialogue(Command::Searches, harness.dialogue.clone()).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
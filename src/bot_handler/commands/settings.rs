@@ -0,0 +1,373 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot_handler::{BotHandlerError, BotHandlerResult, commands::Context},
+    storage::{DeliveryMode, QuietHours, parse_timezone},
+};
+
+/// The settings that are changed by typing their new value.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SettingField {
+    /// How notifications are delivered.
+    #[serde(rename = "d")]
+    Delivery,
+    /// The timezone of the digest schedule and quiet hours.
+    #[serde(rename = "tz")]
+    Timezone,
+    /// When the user does not want to be notified.
+    #[serde(rename = "q")]
+    QuietHours,
+}
+
+impl SettingField {
+    // The setting named in `/settings <name> <value>`, if any.
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "delivery" => Some(Self::Delivery),
+            "timezone" => Some(Self::Timezone),
+            "quiet" => Some(Self::QuietHours),
+            _ => None,
+        }
+    }
+}
+
+/// Handle `/settings [timezone <name> | quiet <HH:MM-HH:MM|off>]`. Without
+/// arguments the user's settings are shown with buttons to change them;
+/// otherwise the named one is changed.
+pub async fn handle(ctx: Context<'_>, args: &str) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+    let args = args.trim();
+    let repository_service = &ctx.handler.repository_service;
+
+    if args.is_empty() {
+        let settings = repository_service.get_user_settings(chat_id).await?;
+        ctx.handler.messaging_service.send_settings_msg(chat_id, settings, false).await?;
+        return Ok(());
+    }
+
+    let (setting, value) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let invalid_input = match SettingField::from_name(setting) {
+        Some(field) => update(&ctx, field, value).await?,
+        None => Some(format!("Unknown setting: {setting}. Use delivery, timezone or quiet.")),
+    };
+
+    if let Some(message) = invalid_input {
+        ctx.handler
+            .messaging_service
+            .send_error_msg(chat_id, BotHandlerError::InvalidInput(message))
+            .await?;
+        return Ok(());
+    }
+
+    let settings = repository_service.get_user_settings(chat_id).await?;
+    ctx.handler.messaging_service.send_settings_msg(chat_id, settings, true).await?;
+
+    Ok(())
+}
+
+/// Handle the reply with the new value of a setting. Invalid input is
+/// reported and asked for again; otherwise the settings are shown.
+pub async fn handle_reply(
+    ctx: Context<'_>,
+    field: SettingField,
+    text: &str,
+) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+
+    if let Some(message) = update(&ctx, field, text).await? {
+        ctx.handler
+            .messaging_service
+            .send_error_msg(chat_id, BotHandlerError::InvalidInput(message))
+            .await?;
+        ctx.handler.messaging_service.prompt_for_setting_input(chat_id, field).await?;
+        return Ok(());
+    }
+
+    ctx.dialogue.exit().await.map_err(BotHandlerError::DialogueError)?;
+
+    let settings = ctx.handler.repository_service.get_user_settings(chat_id).await?;
+    ctx.handler.messaging_service.send_settings_msg(chat_id, settings, true).await?;
+
+    Ok(())
+}
+
+// Helper to change a setting to the value the user typed. Returns why the
+// value is invalid, if it is.
+async fn update(
+    ctx: &Context<'_>,
+    field: SettingField,
+    value: &str,
+) -> BotHandlerResult<Option<String>> {
+    let chat_id = ctx.message.chat.id;
+    let value = value.trim();
+    let repository_service = &ctx.handler.repository_service;
+
+    match field {
+        SettingField::Delivery => match value.parse::<DeliveryMode>() {
+            Ok(delivery_mode) =>
+                repository_service.set_delivery_mode(chat_id, delivery_mode).await?,
+            Err(e) => return Ok(Some(e.to_string())),
+        },
+        SettingField::Timezone => match parse_timezone(value) {
+            Ok(timezone) => repository_service.set_timezone(chat_id, timezone).await?,
+            Err(e) => return Ok(Some(e.to_string())),
+        },
+        SettingField::QuietHours if value.eq_ignore_ascii_case("off") =>
+            repository_service.set_quiet_hours(chat_id, None).await?,
+        SettingField::QuietHours => match value.parse::<QuietHours>() {
+            Ok(quiet_hours) =>
+                repository_service.set_quiet_hours(chat_id, Some(quiet_hours)).await?,
+            Err(e) => return Ok(Some(e.to_string())),
+        },
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+    use mockall::predicate::eq;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            Command, CommandState,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        repository::MockRepositoryService,
+        storage::UserSettings,
+    };
+
+    #[tokio::test]
+    async fn test_settings_sets_timezone() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let timezone = parse_timezone("Europe/Berlin").unwrap();
+
+        mock_repository
+            .expect_set_timezone()
+            .with(eq(CHAT_ID), eq(timezone))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_repository
+            .expect_get_user_settings()
+            .times(1)
+            .returning(move |_| Ok(UserSettings { timezone, ..Default::default() }));
+        mock_messaging
+            .expect_send_settings_msg()
+            .withf(move |&chat_id, settings, &changed| {
+                chat_id == CHAT_ID && settings.timezone == timezone && changed
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Settings("timezone europe/berlin".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_settings_sets_quiet_hours() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let quiet_hours = QuietHours {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+        };
+
+        mock_repository
+            .expect_set_quiet_hours()
+            .with(eq(CHAT_ID), eq(Some(quiet_hours)))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_repository
+            .expect_get_user_settings()
+            .times(1)
+            .returning(|_| Ok(UserSettings::default()));
+        mock_messaging.expect_send_settings_msg().times(1).returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Settings("quiet 22:00-07:00".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_settings_turns_off_quiet_hours() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_set_quiet_hours()
+            .with(eq(CHAT_ID), eq(None))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_repository
+            .expect_get_user_settings()
+            .times(1)
+            .returning(|_| Ok(UserSettings::default()));
+        mock_messaging.expect_send_settings_msg().times(1).returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Settings("quiet OFF".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_settings_invalid_timezone() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository.expect_set_timezone().never();
+        mock_messaging
+            .expect_send_error_msg()
+            .withf(|&chat_id, error| {
+                chat_id == CHAT_ID && matches!(error, BotHandlerError::InvalidInput(_))
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Settings("timezone Mars/Olympus".to_string()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_settings_without_args_shows_settings() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository
+            .expect_get_user_settings()
+            .with(eq(CHAT_ID))
+            .times(1)
+            .returning(|_| Ok(UserSettings::default()));
+        mock_messaging
+            .expect_send_settings_msg()
+            .with(eq(CHAT_ID), eq(UserSettings::default()), eq(false))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result = harness
+            .handle_command_with_dialogue(
+                Command::Settings(String::new()),
+                harness.dialogue.clone(),
+            )
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_settings_reply_sets_delivery_mode() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+        let delivery_mode = DeliveryMode::Daily(NaiveTime::from_hms_opt(18, 30, 0).unwrap());
+
+        mock_repository
+            .expect_set_delivery_mode()
+            .with(eq(CHAT_ID), eq(delivery_mode))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_repository
+            .expect_get_user_settings()
+            .times(1)
+            .returning(|_| Ok(UserSettings::default()));
+        mock_messaging
+            .expect_send_settings_msg()
+            .withf(|&chat_id, _, &changed| chat_id == CHAT_ID && changed)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        harness
+            .dialogue
+            .update(CommandState::EditingSetting { field: SettingField::Delivery })
+            .await
+            .unwrap();
+
+        // Act
+        let result = harness.handle_reply_with_dialogue("daily 18:30", &harness.dialogue).await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(harness.dialogue.get().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_settings_reply_asks_again_for_invalid_value() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mut mock_repository = MockRepositoryService::new();
+
+        mock_repository.expect_set_quiet_hours().never();
+        mock_messaging
+            .expect_send_error_msg()
+            .withf(|_, error| matches!(error, BotHandlerError::InvalidInput(_)))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_messaging
+            .expect_prompt_for_setting_input()
+            .with(eq(CHAT_ID), eq(SettingField::QuietHours))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+        let state = CommandState::EditingSetting { field: SettingField::QuietHours };
+        harness.dialogue.update(state.clone()).await.unwrap();
+
+        // Act
+        let result = harness.handle_reply_with_dialogue("tonight", &harness.dialogue).await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(harness.dialogue.get().await.unwrap(), Some(state));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::bot_handler::{BotHandlerError, BotHandlerResult, commands::Context};
+
+/// Handle `/status`, which shows the state of the poller. Only the admin chat
+/// may use it.
+pub async fn handle(ctx: Context<'_>) -> BotHandlerResult<()> {
+    let chat_id = ctx.message.chat.id;
+
+    match &ctx.handler.admin {
+        Some((admin_chat_id, poller_status)) if *admin_chat_id == chat_id => {
+            let status = poller_status.borrow().clone();
+            ctx.handler.messaging_service.send_poller_status_msg(chat_id, &status).await?;
+        }
+        _ => {
+            ctx.handler
+                .messaging_service
+                .send_error_msg(
+                    chat_id,
+                    BotHandlerError::InvalidInput(
+                        "/status is only available in the admin chat".to_string(),
+                    ),
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::watch;
+
+    use super::*;
+    use crate::{
+        bot_handler::{
+            Command,
+            test_helpers::{CHAT_ID, TestHarness},
+        },
+        messaging::MockMessagingService,
+        poller::PollerStatus,
+        repository::MockRepositoryService,
+    };
+
+    #[tokio::test]
+    async fn test_status_in_admin_chat() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mock_repository = MockRepositoryService::new();
+        let degraded =
+            PollerStatus::Degraded { restarts: 2, last_error: "network down".to_string() };
+        let (status_tx, status_rx) = watch::channel(PollerStatus::Running);
+
+        let expected = degraded.clone();
+        mock_messaging
+            .expect_send_poller_status_msg()
+            .withf(move |&chat_id, status| chat_id == CHAT_ID && *status == expected)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await.with_admin(status_rx);
+        // The latest status is shown
+        status_tx.send_replace(degraded);
+
+        // Act
+        let result =
+            harness.handle_command_with_dialogue(Command::Status, harness.dialogue.clone()).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_status_outside_admin_chat() {
+        // Arrange
+        let mut mock_messaging = MockMessagingService::new();
+        let mock_repository = MockRepositoryService::new();
+
+        mock_messaging.expect_send_poller_status_msg().never();
+        mock_messaging
+            .expect_send_error_msg()
+            .withf(|&chat_id, error| {
+                chat_id == CHAT_ID && matches!(error, BotHandlerError::InvalidInput(_))
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let harness = TestHarness::new(mock_messaging, mock_repository).await;
+
+        // Act
+        let result =
+            harness.handle_command_with_dialogue(Command::Status, harness.dialogue.clone()).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+}
@@ -16,15 +16,18 @@ use serde::{Deserialize, Serialize};
 use teloxide::{
     dispatching::dialogue::{Dialogue, SqliteStorage, SqliteStorageError, serializer::Json},
     prelude::*,
-    types::Message,
+    types::{ChatMemberUpdated, Message},
     utils::command::BotCommands,
 };
 use thiserror::Error;
+use tokio::sync::watch;
 
 use crate::{
-    bot_handler::commands::CommandHandler,
+    bot_handler::commands::{CommandHandler, search::SearchStep, settings::SettingField},
     messaging::{MessagingError, MessagingService},
+    poller::PollerStatus,
     repository::{RepositoryService, RepositoryServiceError},
+    storage::SearchFilter,
 };
 
 type DialogueStorage = SqliteStorage<Json>;
@@ -96,6 +99,35 @@ pub enum Command {
     /// Show an overview of all tracked repositories and their labels.
     #[command(description = "Show an overview of tracked repositories.")]
     Overview,
+    /// Track all repositories of an organization or user, or list the tracked
+    /// ones.
+    #[command(description = "Track all repositories of an organization or user: /org <name> \
+                             [labels separated by commas]. Without a name, list the tracked ones.")]
+    Org(String),
+    /// Stop tracking the repositories of an organization or user.
+    #[command(description = "Stop tracking an organization or user: /unorg <name>.")]
+    Unorg(String),
+    /// Subscribe to new issues across GitHub.
+    #[command(
+        description = "Subscribe to new issues across GitHub by label, language, stars and topic."
+    )]
+    Search,
+    /// List the search subscriptions.
+    #[command(description = "List and remove your searches.")]
+    Searches,
+    /// Choose between instant notifications and a digest, or show the
+    /// current choice.
+    #[command(description = "Choose how you are notified: /delivery instant, hourly, daily \
+                             [HH:MM] or weekly [HH:MM]. Times are in your timezone.")]
+    Delivery(String),
+    /// Show and change the user's preferences, or change one of them right
+    /// away.
+    #[command(description = "Show and change your settings. Some can be changed right away: \
+                             /settings timezone <name> or /settings quiet <HH:MM-HH:MM|off>.")]
+    Settings(String),
+    /// Show the state of the poller, in the admin chat only.
+    #[command(hide)]
+    Status,
 }
 
 impl fmt::Display for Command {
@@ -106,6 +138,13 @@ impl fmt::Display for Command {
             Command::Add => write!(f, "add"),
             Command::List => write!(f, "list"),
             Command::Overview => write!(f, "overview"),
+            Command::Org(args) => write!(f, "org {args}"),
+            Command::Unorg(args) => write!(f, "unorg {args}"),
+            Command::Search => write!(f, "search"),
+            Command::Searches => write!(f, "searches"),
+            Command::Delivery(args) => write!(f, "delivery {args}"),
+            Command::Settings(args) => write!(f, "settings {args}"),
+            Command::Status => write!(f, "status"),
         }
     }
 }
@@ -115,6 +154,8 @@ pub struct BotHandler {
     messaging_service: Arc<dyn MessagingService>,
     repository_service: Arc<dyn RepositoryService>,
     max_concurrency: usize,
+    /// The admin chat, and the status of the poller shown to it.
+    admin: Option<(ChatId, watch::Receiver<PollerStatus>)>,
 }
 
 /// The state of the command.
@@ -132,6 +173,19 @@ pub enum CommandState {
         /// The page number of the repository list the user came from.
         from_page: usize,
     },
+    /// The user is answering the prompts of `/search`.
+    BuildingSearch {
+        /// The criterion the bot is waiting for.
+        step: SearchStep,
+        /// The criteria answered so far.
+        filter: SearchFilter,
+    },
+    /// The bot is waiting for the user to reply with the new value of a
+    /// setting.
+    EditingSetting {
+        /// The setting being changed.
+        field: SettingField,
+    },
 }
 
 impl BotHandler {
@@ -141,7 +195,17 @@ impl BotHandler {
         repository_service: Arc<dyn RepositoryService>,
         max_concurrency: usize,
     ) -> Self {
-        Self { messaging_service, repository_service, max_concurrency }
+        Self { messaging_service, repository_service, max_concurrency, admin: None }
+    }
+
+    /// Let `admin_chat_id` check the status of the poller with `/status`.
+    pub fn with_admin(
+        mut self,
+        admin_chat_id: ChatId,
+        poller_status: watch::Receiver<PollerStatus>,
+    ) -> Self {
+        self.admin = Some((admin_chat_id, poller_status));
+        self
     }
 
     /// Dispatches the incoming command to the appropriate handler.
@@ -155,7 +219,8 @@ impl BotHandler {
         cmd.handle(ctx).await
     }
 
-    /// Handle a reply message when we're waiting for repository input.
+    /// Handle a reply message when we're waiting for repository input, search
+    /// criteria or a setting.
     pub async fn handle_reply(
         &self,
         msg: &Message,
@@ -163,15 +228,23 @@ impl BotHandler {
     ) -> BotHandlerResult<()> {
         let text = msg.text();
         let dialogue_state = dialogue.get().await.map_err(BotHandlerError::DialogueError)?;
-        // Check if we're waiting for repository input.
+        let ctx = Context { handler: self, message: msg, dialogue, query: None };
+        // Check which input we're waiting for.
         match (dialogue_state, text) {
             (Some(CommandState::AwaitingAddRepo), Some(text)) => {
-                let ctx = Context { handler: self, message: msg, dialogue, query: None };
                 commands::add::handle_reply(ctx, text).await?;
             }
+            // The search dialogue spans several replies and ends itself.
+            (Some(CommandState::BuildingSearch { step, filter }), Some(text)) => {
+                return commands::search::handle_reply(ctx, step, filter, text).await;
+            }
+            // Invalid settings are asked for again, so the dialogue ends itself.
+            (Some(CommandState::EditingSetting { field }), Some(text)) => {
+                return commands::settings::handle_reply(ctx, field, text).await;
+            }
             _ => {
                 // Should not happen, because force reply does not accept empty input and there
-                // are only two possible states, but just in case
+                // are only these states awaiting a reply, but just in case
                 self.messaging_service
                     .send_error_msg(
                         msg.chat.id,
@@ -184,6 +257,23 @@ impl BotHandler {
         Ok(())
     }
 
+    /// Handles a change of the bot's membership in a chat. Polling for the
+    /// chat stops when the user blocks the bot or removes it from a group, and
+    /// resumes when it is unblocked or added back.
+    pub async fn handle_my_chat_member(&self, update: &ChatMemberUpdated) -> BotHandlerResult<()> {
+        let chat_id = update.chat.id;
+        let active = update.new_chat_member.is_present();
+
+        if self.repository_service.set_chat_active(chat_id, active).await? {
+            tracing::info!(
+                "Chat {chat_id} is {}",
+                if active { "active again" } else { "no longer reachable" }
+            );
+        }
+
+        Ok(())
+    }
+
     /// Handles an incoming callback query.
     pub async fn handle_callback_query(
         &self,
@@ -208,29 +298,66 @@ impl BotHandler {
                 query: Some(query),
             };
 
+            let chat_id = ctx.message.chat.id;
             match action {
-                CallbackAction::ViewRepoDetails(repo_id, from_page) => {
-                    callbacks::view_repo::handle(ctx, repo_id, from_page, &query_id).await?;
+                CallbackAction::ViewRepoDetails(repo_ref, from_page) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::view_repo::handle(ctx, &repo_id, from_page, &query_id).await?;
                 }
-                CallbackAction::BackToRepoDetails(repo_id, from_page) => {
-                    callbacks::view_repo::handle(ctx, repo_id, from_page, &query_id).await?;
+                CallbackAction::BackToRepoDetails(repo_ref, from_page) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::view_repo::handle(ctx, &repo_id, from_page, &query_id).await?;
                 }
-                CallbackAction::ViewRepoLabels(repo_id, page, from_page) => {
-                    callbacks::view_labels::handle(ctx, repo_id, page, from_page, &query_id)
+                CallbackAction::ViewRepoLabels(repo_ref, page, from_page) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::view_labels::handle(ctx, &repo_id, page, from_page, &query_id)
                         .await?;
                 }
-                CallbackAction::RemoveRepoPrompt(repo_id) => {
-                    callbacks::remove::handle(ctx, repo_id, 1).await?;
+                CallbackAction::RemoveRepoPrompt(repo_ref) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::remove::handle(ctx, &repo_id, 1).await?;
                 }
                 CallbackAction::ToggleLabel(label, label_page, _) => {
                     callbacks::toggle_label::handle(ctx, label, label_page).await?;
                 }
+                CallbackAction::ToggleIssueFilter(repo_ref, filter, from_page) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::toggle_issue_filter::handle(ctx, &repo_id, filter, from_page)
+                        .await?;
+                }
                 CallbackAction::BackToRepoList(page) => {
                     callbacks::list::handle(ctx, page).await?;
                 }
                 CallbackAction::ListReposPage(page) => {
                     callbacks::list::handle(ctx, page).await?;
                 }
+                CallbackAction::RemoveSearch(id) => {
+                    callbacks::remove_search::handle(ctx, id).await?;
+                }
+                CallbackAction::MuteLabel(repo_ref, label_ref) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::mute_label::handle(ctx, &repo_id, label_ref).await?;
+                }
+                CallbackAction::MuteRepo(repo_ref) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::mute_repo::handle(ctx, &repo_id).await?;
+                }
+                CallbackAction::UntrackRepo(repo_ref) => {
+                    let repo_id = self.resolve_repo_ref(chat_id, repo_ref).await?;
+                    callbacks::untrack_repo::handle(ctx, &repo_id).await?;
+                }
+                CallbackAction::EditSetting(field) => {
+                    callbacks::settings::handle_edit(ctx, field).await?;
+                }
+                CallbackAction::ToggleNotificationOption(option) => {
+                    callbacks::settings::handle_toggle_option(ctx, option).await?;
+                }
+                CallbackAction::SetLanguage(language) => {
+                    callbacks::settings::handle_set_language(ctx, language).await?;
+                }
+                CallbackAction::SetPageSize(page_size) => {
+                    callbacks::settings::handle_set_page_size(ctx, page_size).await?;
+                }
                 CallbackAction::CmdHelp => commands::help::handle(ctx).await?,
                 CallbackAction::CmdList => commands::list::handle(ctx, 1).await?,
                 CallbackAction::CmdAdd => commands::add::handle(ctx).await?,
@@ -249,4 +376,24 @@ impl BotHandler {
         }
         Ok(())
     }
+
+    /// Resolve the repository a callback button refers to. Long names are sent
+    /// as references, which are matched against the repositories the chat
+    /// tracks.
+    async fn resolve_repo_ref(&self, chat_id: ChatId, repo_ref: &str) -> BotHandlerResult<String> {
+        if !callback_actions::is_callback_ref(repo_ref) {
+            return Ok(repo_ref.to_string());
+        }
+
+        self.repository_service
+            .get_user_repos(chat_id, 1)
+            .await?
+            .items
+            .into_iter()
+            .map(|repo| repo.name_with_owner)
+            .find(|name| callback_actions::refers_to(repo_ref, name))
+            .ok_or_else(|| {
+                BotHandlerError::InvalidInput("Repository is no longer tracked".to_string())
+            })
+    }
 }
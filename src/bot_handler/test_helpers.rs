@@ -1,4 +1,4 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, str::FromStr, sync::Arc};
 
 use chrono::Utc;
 use mockall::predicate::*;
@@ -14,7 +14,9 @@ use super::*;
 use crate::{
     bot_handler::{BotHandler, Command, CommandState},
     messaging::MockMessagingService,
-    repository::MockRepositoryService,
+    poller::PollerStatus,
+    repository::{MockRepositoryService, RepositoryServiceError},
+    storage::RepoEntity,
 };
 
 pub const CHAT_ID: ChatId = ChatId(123);
@@ -41,6 +43,12 @@ impl TestHarness {
         Self { bot_handler, dialogue, storage }
     }
 
+    // Makes the test chat the admin chat, shown the given poller status.
+    pub fn with_admin(mut self, poller_status: watch::Receiver<PollerStatus>) -> Self {
+        self.bot_handler = self.bot_handler.with_admin(CHAT_ID, poller_status);
+        self
+    }
+
     // Creates a new dialogue for the same chat ID to test state persistence.
     pub fn new_dialogue(&self) -> Dialogue<CommandState, DialogueStorage> {
         Dialogue::new(self.storage.clone(), CHAT_ID)
@@ -52,7 +60,7 @@ impl TestHarness {
         command: Command,
         dialogue: Dialogue<CommandState, DialogueStorage>,
     ) -> Result<(), BotHandlerError> {
-        let msg = mock_message(CHAT_ID, &format!("/{}", command.to_string()));
+        let msg = mock_message(CHAT_ID, &format!("/{command}"));
         self.bot_handler.handle_commands(&msg, command, dialogue).await
     }
 
@@ -187,6 +195,15 @@ pub fn mock_callback_query<'a>(
     (msg, query)
 }
 
+// Helper to resolve a repository to itself, as if its name was already
+// canonical
+pub fn resolve_as_is(
+    owner: &str,
+    name: &str,
+) -> Result<Option<RepoEntity>, RepositoryServiceError> {
+    Ok(Some(RepoEntity::from_str(&format!("{owner}/{name}")).unwrap()))
+}
+
 pub fn setup_add_repo_mocks(mock_messaging: &mut MockMessagingService) {
     let status_msg = mock_message(CHAT_ID, "Processing... ⏳");
     mock_messaging
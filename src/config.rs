@@ -7,6 +7,13 @@ const DEFAULT_REPOS_PER_USER: usize = 20;
 const DEFAULT_LABELS_PER_REPO: usize = 10;
 const DEFAULT_MAX_CONCURRENCY: usize = 10;
 const DEFAULT_RATE_LIMIT_THRESHOLD: u64 = 10;
+const DEFAULT_MAX_ISSUE_PAGES: usize = 5;
+const DEFAULT_WEBHOOK_LISTEN_ADDR: &str = "0.0.0.0:8080";
+const DEFAULT_TELEGRAM_WEBHOOK_LISTEN_ADDR: &str = "0.0.0.0:8443";
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+const DEFAULT_POLLER_MAX_RESTARTS: u32 = 5;
+const DEFAULT_REPO_NAME_SYNC_INTERVAL: u64 = 24 * 60 * 60;
+const DEFAULT_INACTIVE_CHAT_GRACE_PERIOD: u64 = 30 * 24 * 60 * 60;
 
 /// Represents the application configuration.
 #[derive(Debug)]
@@ -29,6 +36,35 @@ pub struct Config {
     pub max_concurrency: usize,
     /// The threshold before the bot should pause operations.
     pub rate_limit_threshold: u64,
+    /// The maximum number of issue pages to fetch per repository and poll.
+    pub max_issue_pages: usize,
+    /// The secret GitHub webhook deliveries are signed with. The webhook
+    /// server only runs if it is set.
+    pub github_webhook_secret: Option<String>,
+    /// The address the webhook server listens on.
+    pub webhook_listen_addr: String,
+    /// The public URL Telegram delivers updates to. The bot receives updates
+    /// through a webhook if it is set, and through long polling otherwise.
+    pub telegram_webhook_url: Option<String>,
+    /// The address the Telegram webhook listener binds to.
+    pub telegram_webhook_listen_addr: String,
+    /// The secret Telegram sends in the `X-Telegram-Bot-Api-Secret-Token`
+    /// header of every webhook request.
+    pub telegram_webhook_secret: Option<String>,
+    /// How long in seconds to wait for in-flight polls and other background
+    /// work to finish on shutdown.
+    pub shutdown_timeout: u64,
+    /// The chat that is alerted when the poller stops for good.
+    pub admin_chat_id: Option<i64>,
+    /// How many times in a row a failed poller is restarted before it is
+    /// stopped.
+    pub poller_max_restarts: u32,
+    /// The interval in seconds to check tracked repositories for renames and
+    /// transfers.
+    pub repo_name_sync_interval: u64,
+    /// How long in seconds the subscriptions of a chat that blocked the bot
+    /// are kept before they are deleted.
+    pub inactive_chat_grace_period: u64,
 }
 
 impl Config {
@@ -61,6 +97,36 @@ impl Config {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(DEFAULT_RATE_LIMIT_THRESHOLD),
+            max_issue_pages: env::var("MAX_ISSUE_PAGES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_ISSUE_PAGES),
+            github_webhook_secret: env::var("GITHUB_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty()),
+            webhook_listen_addr: env::var("WEBHOOK_LISTEN_ADDR")
+                .unwrap_or_else(|_| DEFAULT_WEBHOOK_LISTEN_ADDR.to_string()),
+            telegram_webhook_url: env::var("TELEGRAM_WEBHOOK_URL").ok().filter(|s| !s.is_empty()),
+            telegram_webhook_listen_addr: env::var("TELEGRAM_WEBHOOK_LISTEN_ADDR")
+                .unwrap_or_else(|_| DEFAULT_TELEGRAM_WEBHOOK_LISTEN_ADDR.to_string()),
+            telegram_webhook_secret: env::var("TELEGRAM_WEBHOOK_SECRET")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            shutdown_timeout: env::var("SHUTDOWN_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT),
+            admin_chat_id: env::var("ADMIN_CHAT_ID").ok().and_then(|v| v.parse().ok()),
+            poller_max_restarts: env::var("POLLER_MAX_RESTARTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_POLLER_MAX_RESTARTS),
+            repo_name_sync_interval: env::var("REPO_NAME_SYNC_INTERVAL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_REPO_NAME_SYNC_INTERVAL),
+            inactive_chat_grace_period: env::var("INACTIVE_CHAT_GRACE_PERIOD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_INACTIVE_CHAT_GRACE_PERIOD),
         })
     }
 }
@@ -82,6 +148,17 @@ mod tests {
                 ("DATABASE_URL", Some("sqlite:test/test.db")),
                 ("MAX_REPOS_PER_USER", Some("50")),
                 ("MAX_LABELS_PER_REPO", Some("20")),
+                ("MAX_ISSUE_PAGES", Some("3")),
+                ("GITHUB_WEBHOOK_SECRET", Some("webhook secret")),
+                ("WEBHOOK_LISTEN_ADDR", Some("127.0.0.1:9000")),
+                ("TELEGRAM_WEBHOOK_URL", Some("https://bot.example.com/telegram")),
+                ("TELEGRAM_WEBHOOK_LISTEN_ADDR", Some("127.0.0.1:9443")),
+                ("TELEGRAM_WEBHOOK_SECRET", Some("telegram_secret")),
+                ("SHUTDOWN_TIMEOUT", Some("5")),
+                ("ADMIN_CHAT_ID", Some("-1001234567890")),
+                ("POLLER_MAX_RESTARTS", Some("3")),
+                ("REPO_NAME_SYNC_INTERVAL", Some("3600")),
+                ("INACTIVE_CHAT_GRACE_PERIOD", Some("86400")),
             ],
             || {
                 let config = Config::from_env().unwrap();
@@ -92,6 +169,20 @@ mod tests {
                 assert_eq!(config.database_url, "sqlite:test/test.db");
                 assert_eq!(config.max_repos_per_user, 50);
                 assert_eq!(config.max_labels_per_repo, 20);
+                assert_eq!(config.max_issue_pages, 3);
+                assert_eq!(config.github_webhook_secret.as_deref(), Some("webhook secret"));
+                assert_eq!(config.webhook_listen_addr, "127.0.0.1:9000");
+                assert_eq!(
+                    config.telegram_webhook_url.as_deref(),
+                    Some("https://bot.example.com/telegram")
+                );
+                assert_eq!(config.telegram_webhook_listen_addr, "127.0.0.1:9443");
+                assert_eq!(config.telegram_webhook_secret.as_deref(), Some("telegram_secret"));
+                assert_eq!(config.shutdown_timeout, 5);
+                assert_eq!(config.admin_chat_id, Some(-1001234567890));
+                assert_eq!(config.poller_max_restarts, 3);
+                assert_eq!(config.repo_name_sync_interval, 3600);
+                assert_eq!(config.inactive_chat_grace_period, 86400);
             },
         );
     }
@@ -194,12 +285,48 @@ mod tests {
                 ("MAX_REPOS_PER_USER", None),
                 ("MAX_LABELS_PER_REPO", None),
                 ("MAX_CONCURRENCY", None),
+                ("MAX_ISSUE_PAGES", None),
+                ("SHUTDOWN_TIMEOUT", None),
+                ("ADMIN_CHAT_ID", None),
+                ("POLLER_MAX_RESTARTS", None),
+                ("REPO_NAME_SYNC_INTERVAL", None),
             ],
             || {
                 let config = Config::from_env().unwrap();
                 assert_eq!(config.max_repos_per_user, DEFAULT_REPOS_PER_USER);
                 assert_eq!(config.max_labels_per_repo, DEFAULT_LABELS_PER_REPO);
                 assert_eq!(config.max_concurrency, DEFAULT_MAX_CONCURRENCY);
+                assert_eq!(config.max_issue_pages, DEFAULT_MAX_ISSUE_PAGES);
+                assert_eq!(config.shutdown_timeout, DEFAULT_SHUTDOWN_TIMEOUT);
+                assert!(config.admin_chat_id.is_none());
+                assert_eq!(config.poller_max_restarts, DEFAULT_POLLER_MAX_RESTARTS);
+                assert_eq!(config.repo_name_sync_interval, DEFAULT_REPO_NAME_SYNC_INTERVAL);
+            },
+        );
+    }
+
+    #[test]
+    fn test_missing_webhooks_default() {
+        with_vars(
+            [
+                ("GITHUB_TOKEN", Some("test github token")),
+                ("TELOXIDE_TOKEN", Some("test telegram bot token")),
+                ("GITHUB_WEBHOOK_SECRET", None),
+                ("WEBHOOK_LISTEN_ADDR", None),
+                ("TELEGRAM_WEBHOOK_URL", None),
+                ("TELEGRAM_WEBHOOK_LISTEN_ADDR", None),
+                ("TELEGRAM_WEBHOOK_SECRET", None),
+            ],
+            || {
+                let config = Config::from_env().unwrap();
+                assert!(config.github_webhook_secret.is_none());
+                assert_eq!(config.webhook_listen_addr, DEFAULT_WEBHOOK_LISTEN_ADDR);
+                assert!(config.telegram_webhook_url.is_none());
+                assert_eq!(
+                    config.telegram_webhook_listen_addr,
+                    DEFAULT_TELEGRAM_WEBHOOK_LISTEN_ADDR
+                );
+                assert!(config.telegram_webhook_secret.is_none());
             },
         );
     }
@@ -1,20 +1,66 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use teloxide::{
+    RequestError,
     dispatching::{
         DefaultKey, DpHandlerDescription,
         dialogue::{Dialogue, SqliteStorage, serializer::Json},
     },
     dptree::{deps, filter_map},
     prelude::*,
-    types::Update,
+    types::{ChatMemberUpdated, Update},
+    update_listeners::webhooks,
 };
+use thiserror::Error;
 
-use crate::bot_handler::{BotHandler, BotHandlerError, BotHandlerResult, Command, CommandState};
+use crate::{
+    bot_handler::{BotHandler, BotHandlerError, BotHandlerResult, Command, CommandState},
+    config::Config,
+};
 
 type DispatchHandler = Handler<'static, DependencyMap, BotHandlerResult<()>, DpHandlerDescription>;
 type DialogueStorage = SqliteStorage<Json>;
 
+/// Represents errors that can occur while setting up how updates are received.
+#[derive(Debug, Error)]
+pub enum UpdateModeError {
+    /// The webhook listen address is not a valid socket address.
+    #[error("Invalid Telegram webhook listen address: {0}")]
+    InvalidListenAddr(#[from] std::net::AddrParseError),
+    /// The public webhook URL is not a valid URL.
+    #[error("Invalid Telegram webhook URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    /// Telegram refused to set up the webhook.
+    #[error("Failed to set up the Telegram webhook: {0}")]
+    Request(#[from] RequestError),
+}
+
+/// How the bot receives updates from Telegram.
+pub enum UpdateMode {
+    /// Ask Telegram for updates with long polling.
+    LongPolling,
+    /// Let Telegram deliver updates to a webhook.
+    Webhook(Box<webhooks::Options>),
+}
+
+impl UpdateMode {
+    /// Select the update mode from the configuration: a webhook if a public
+    /// URL is configured, long polling otherwise.
+    pub fn from_config(config: &Config) -> Result<Self, UpdateModeError> {
+        let Some(url) = &config.telegram_webhook_url else {
+            return Ok(Self::LongPolling);
+        };
+
+        let address: SocketAddr = config.telegram_webhook_listen_addr.parse()?;
+        let mut options = webhooks::Options::new(address, url.parse()?);
+        if let Some(secret) = &config.telegram_webhook_secret {
+            options = options.secret_token(secret.clone());
+        }
+
+        Ok(Self::Webhook(Box::new(options)))
+    }
+}
+
 /// Encapsulates the dispatcher logic for the bot.
 pub struct BotDispatcher {
     handler: Arc<BotHandler>,
@@ -35,13 +81,53 @@ impl BotDispatcher {
             dptree::entry()
                 .branch(self.build_commands_branch())
                 .branch(self.build_callback_queries_branch())
-                .branch(self.build_force_reply_branch()),
+                .branch(self.build_force_reply_branch())
+                .branch(self.build_my_chat_member_branch()),
         )
         .dependencies(deps![self.dialogue_storage.clone(), self.handler.clone()])
-        .enable_ctrlc_handler()
         .build()
     }
 
+    /// Builds the dispatcher and receives updates in the given mode until
+    /// `stop` completes. Both modes share the same handler tree.
+    pub async fn run(
+        &self,
+        bot: Bot,
+        mode: UpdateMode,
+        stop: impl Future<Output = ()> + Send + 'static,
+    ) -> Result<(), UpdateModeError> {
+        let mut dispatcher = self.build(bot.clone());
+        tracing::debug!("Dispatcher built successfully.");
+
+        let shutdown_token = dispatcher.shutdown_token();
+        tokio::spawn(async move {
+            stop.await;
+            match shutdown_token.shutdown() {
+                Ok(stopped) => {
+                    stopped.await;
+                    tracing::info!("Dispatcher stopped");
+                }
+                Err(e) => tracing::warn!("Could not stop the dispatcher: {e}"),
+            }
+        });
+
+        match mode {
+            UpdateMode::LongPolling => dispatcher.dispatch().await,
+            UpdateMode::Webhook(options) => {
+                tracing::info!("Receiving Telegram updates through a webhook at {}", options.url);
+                let listener = webhooks::axum(bot, *options).await?;
+                dispatcher
+                    .dispatch_with_listener(
+                        listener,
+                        LoggingErrorHandler::with_custom_text("An error from the update listener"),
+                    )
+                    .await
+            }
+        }
+
+        Ok(())
+    }
+
     /// Builds the branch for handling text commands.
     fn build_commands_branch(&self) -> DispatchHandler {
         Update::filter_message()
@@ -83,6 +169,16 @@ impl BotDispatcher {
                 },
             )
     }
+
+    /// Builds the branch for handling changes of the bot's membership in a
+    /// chat, e.g. when the user blocks or unblocks the bot.
+    fn build_my_chat_member_branch(&self) -> DispatchHandler {
+        Update::filter_my_chat_member().endpoint(
+            |update: ChatMemberUpdated, handler: Arc<BotHandler>| async move {
+                handler.handle_my_chat_member(&update).await
+            },
+        )
+    }
 }
 
 /// Extracts a dialogue from an update using the provided dialogue storage.
@@ -92,3 +188,61 @@ fn extract_dialogue(
 ) -> Option<Dialogue<CommandState, DialogueStorage>> {
     update.chat().map(|chat| Dialogue::new(storage.clone(), chat.id))
 }
+
+#[cfg(test)]
+mod tests {
+    use temp_env::with_vars;
+
+    use super::*;
+
+    fn update_mode_with(vars: [(&str, Option<&str>); 3]) -> Result<UpdateMode, UpdateModeError> {
+        with_vars(
+            [
+                [("GITHUB_TOKEN", Some("test github token"))].as_slice(),
+                [("TELOXIDE_TOKEN", Some("test telegram bot token"))].as_slice(),
+                vars.as_slice(),
+            ]
+            .concat(),
+            || UpdateMode::from_config(&Config::from_env().unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_update_mode_defaults_to_long_polling() {
+        let mode = update_mode_with([
+            ("TELEGRAM_WEBHOOK_URL", None),
+            ("TELEGRAM_WEBHOOK_LISTEN_ADDR", None),
+            ("TELEGRAM_WEBHOOK_SECRET", None),
+        ]);
+
+        assert!(matches!(mode, Ok(UpdateMode::LongPolling)));
+    }
+
+    #[test]
+    fn test_update_mode_webhook() {
+        let mode = update_mode_with([
+            ("TELEGRAM_WEBHOOK_URL", Some("https://bot.example.com/telegram")),
+            ("TELEGRAM_WEBHOOK_LISTEN_ADDR", Some("127.0.0.1:9443")),
+            ("TELEGRAM_WEBHOOK_SECRET", Some("telegram_secret")),
+        ]);
+
+        let Ok(UpdateMode::Webhook(options)) = mode else {
+            panic!("Expected the webhook update mode");
+        };
+        assert_eq!(options.address, "127.0.0.1:9443".parse().unwrap());
+        assert_eq!(options.url.as_str(), "https://bot.example.com/telegram");
+        assert_eq!(options.path, "/telegram");
+        assert_eq!(options.secret_token.as_deref(), Some("telegram_secret"));
+    }
+
+    #[test]
+    fn test_update_mode_invalid_listen_addr() {
+        let mode = update_mode_with([
+            ("TELEGRAM_WEBHOOK_URL", Some("https://bot.example.com/telegram")),
+            ("TELEGRAM_WEBHOOK_LISTEN_ADDR", Some("not an address")),
+            ("TELEGRAM_WEBHOOK_SECRET", None),
+        ]);
+
+        assert!(matches!(mode, Err(UpdateModeError::InvalidListenAddr(_))));
+    }
+}
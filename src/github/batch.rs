@@ -0,0 +1,187 @@
+//! Packs issue queries for several repositories into a single GraphQL
+//! document, using one aliased `repository` field per repository.
+
+use std::{collections::HashMap, fmt::Write, sync::LazyLock};
+
+use graphql_client::{Error as GraphQLError, PathFragment};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use super::{ISSUES_PAGE_SIZE, RepoIssuesRequest, issues};
+
+/// The selection set of the issue connection, cut out of the `Issues` query
+/// so that each aliased repository deserializes into the generated `issues`
+/// types.
+pub(super) static ISSUES_SELECTION: LazyLock<&'static str> =
+    LazyLock::new(|| issues_selection(issues::QUERY));
+
+/// Cut the selection set of the `issues` field out of the `Issues` query in a
+/// GraphQL document.
+pub(super) fn issues_selection(document: &str) -> &str {
+    let operation = &document[document.find("query Issues(").expect("Issues query is missing")..];
+    let field = &operation[operation.find("issues(").expect("issues field is missing")..];
+    // The arguments do not nest parentheses
+    let arguments_end = field.find(')').expect("issues arguments are not closed");
+    let selection = &field[arguments_end..];
+    let start = selection.find('{').expect("issues selection is missing");
+
+    let mut depth = 0;
+    for (i, c) in selection[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &selection[start..=start + i];
+                }
+            }
+            _ => {}
+        }
+    }
+    panic!("issues selection is not closed")
+}
+
+/// The operation name of the batched issues query.
+pub(super) const OPERATION_NAME: &str = "IssuesBatch";
+
+/// The operation name of the batched repository name lookup.
+pub(super) const REPO_NAMES_OPERATION_NAME: &str = "RepoNamesBatch";
+
+/// GitHub rejects queries that may return more than this many nodes.
+const MAX_NODES_PER_QUERY: i64 = 500_000;
+
+/// Upper bound on the repositories packed into one request, to keep response
+/// sizes and server-side timeouts reasonable.
+pub(super) const MAX_REPOS_PER_QUERY: usize = 20;
+
+/// The worst-case number of nodes a single aliased repository can return: a
+/// page of issues, each with its labels, labeled events, first assignee and
+/// linked pull requests.
+const NODES_PER_REPO: i64 = ISSUES_PAGE_SIZE * (1 + 20 + 10 + 1 + 10);
+
+/// The number of repositories to pack into a single request.
+pub(super) const fn batch_size() -> usize {
+    let by_nodes = (MAX_NODES_PER_QUERY / NODES_PER_REPO) as usize;
+    if by_nodes < MAX_REPOS_PER_QUERY { by_nodes } else { MAX_REPOS_PER_QUERY }
+}
+
+/// The alias of the `index`-th repository of a batch.
+pub(super) fn alias(index: usize) -> String {
+    format!("r{index}")
+}
+
+/// A repository page to fetch as part of a batch.
+#[derive(Debug, Clone)]
+pub(super) struct PendingPage<'a> {
+    /// The position of the request in the caller's list.
+    pub index: usize,
+    pub request: &'a RepoIssuesRequest,
+    pub after: Option<String>,
+}
+
+/// Build the request body fetching one page of issues for every pending
+/// repository.
+pub(super) fn build_issues_batch(pages: &[PendingPage<'_>]) -> Value {
+    let mut declarations = String::from("$first: Int");
+    let mut fields = String::new();
+    let mut variables = Map::new();
+    variables.insert("first".to_string(), json!(ISSUES_PAGE_SIZE));
+
+    for (i, page) in pages.iter().enumerate() {
+        let request = page.request;
+        let mut labels: Vec<&String> = request.labels.iter().collect();
+        labels.sort();
+
+        let _ = write!(
+            declarations,
+            ", $owner{i}: String!, $name{i}: String!, $labels{i}: [String!], $since{i}: DateTime, \
+             $after{i}: String"
+        );
+        let _ = write!(
+            fields,
+            "  {alias}: repository(owner: $owner{i}, name: $name{i}) {{\n    issues(first: \
+             $first, after: $after{i}, states: OPEN, filterBy: {{labels: $labels{i}, since: \
+             $since{i}}}, orderBy: {{field: CREATED_AT, direction: DESC}}) {selection}\n  }}\n",
+            alias = alias(i),
+            selection = *ISSUES_SELECTION
+        );
+
+        variables.insert(format!("owner{i}"), json!(request.owner));
+        variables.insert(format!("name{i}"), json!(request.name));
+        variables.insert(format!("labels{i}"), json!(labels));
+        variables.insert(format!("since{i}"), json!(request.since.map(|dt| dt.to_rfc3339())));
+        variables.insert(format!("after{i}"), json!(page.after));
+    }
+
+    request_body(OPERATION_NAME, &declarations, &fields, variables)
+}
+
+/// The canonical name of a repository, as returned by the batched name
+/// lookup.
+#[derive(Debug, Deserialize)]
+pub(super) struct RepoName {
+    #[serde(rename = "nameWithOwner")]
+    pub name_with_owner: String,
+}
+
+/// Build the request body looking up the canonical `owner/name` of every
+/// given repository.
+pub(super) fn build_repo_names_batch(repos: &[(String, String)]) -> Value {
+    let mut declarations = String::new();
+    let mut fields = String::new();
+    let mut variables = Map::new();
+
+    for (i, (owner, name)) in repos.iter().enumerate() {
+        if i > 0 {
+            declarations.push_str(", ");
+        }
+        let _ = write!(declarations, "$owner{i}: String!, $name{i}: String!");
+        let _ = writeln!(
+            fields,
+            "  {alias}: repository(owner: $owner{i}, name: $name{i}) {{\n    nameWithOwner\n  }}",
+            alias = alias(i)
+        );
+
+        variables.insert(format!("owner{i}"), json!(owner));
+        variables.insert(format!("name{i}"), json!(name));
+    }
+
+    request_body(REPO_NAMES_OPERATION_NAME, &declarations, &fields, variables)
+}
+
+/// Wrap the aliased `fields` into a request body, asking for the rate limit
+/// as well so the cost of the batch is recorded.
+fn request_body(
+    operation_name: &str,
+    declarations: &str,
+    fields: &str,
+    variables: Map<String, Value>,
+) -> Value {
+    json!({
+        "query": format!(
+            "query {operation_name}({declarations}) {{\n{fields}  rateLimit {{\n    cost\n    \
+             remaining\n    resetAt\n    nodeCount\n  }}\n}}"
+        ),
+        "variables": variables,
+        "operationName": operation_name,
+    })
+}
+
+/// Split GraphQL errors by the alias they were reported for. Errors that do not
+/// point into an aliased repository are returned separately.
+pub(super) fn split_errors_by_alias(
+    errors: Vec<GraphQLError>,
+) -> (HashMap<String, Vec<GraphQLError>>, Vec<GraphQLError>) {
+    let mut by_alias: HashMap<String, Vec<GraphQLError>> = HashMap::new();
+    let mut unattributed = Vec::new();
+
+    for error in errors {
+        match error.path.as_deref() {
+            Some([PathFragment::Key(key), ..]) =>
+                by_alias.entry(key.clone()).or_default().push(error),
+            _ => unattributed.push(error),
+        }
+    }
+
+    (by_alias, unattributed)
+}
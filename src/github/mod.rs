@@ -1,15 +1,20 @@
 #![allow(missing_docs)]
+mod batch;
 #[cfg(test)]
 mod tests;
 
 use std::{
-    collections::HashSet,
-    sync::Arc,
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
     time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
 use backoff::{Error as BackoffError, ExponentialBackoff, future::retry};
+use chrono::Utc;
 use graphql_client::{GraphQLQuery, Response};
 use mockall::automock;
 use rand::{Rng, rng};
@@ -17,6 +22,7 @@ use reqwest::{
     Client,
     header::{AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT},
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::Mutex;
 
@@ -26,6 +32,31 @@ struct RateLimitState {
     reset_at: Instant,
 }
 
+/// The `rateLimit` field selected alongside every query.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitInfo {
+    cost: u32,
+    remaining: u32,
+    reset_at: DateTime,
+    node_count: u32,
+}
+
+/// The points consumed by a type of query, as reported by the `rateLimit`
+/// field.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct QueryCost {
+    /// The number of requests sent.
+    pub requests: u64,
+    /// The total points consumed.
+    pub points: u64,
+    /// The points consumed by the last request, per unit of work (e.g. per
+    /// repository of a batch).
+    pub last_cost_per_unit: u32,
+    /// The number of nodes the last request could return.
+    pub last_node_count: u32,
+}
+
 /// Represents errors that can occur when interacting with the GitHub API.
 #[derive(Debug, Error)]
 pub enum GithubError {
@@ -59,6 +90,10 @@ pub enum GithubError {
     /// An error indicating that a required header could not be parsed.
     #[error("Failed to parse header: {0}")]
     HeaderError(String),
+    /// An error indicating that a repository does not exist or is not
+    /// accessible anymore.
+    #[error("Repository not found: {0}")]
+    RepositoryNotFound(String),
 }
 
 // Helper function to check if a GraphQL error is retryable
@@ -72,27 +107,160 @@ fn is_retryable_graphql_error(error: &graphql_client::Error) -> bool {
         .unwrap_or(false)
 }
 
+/// The number of issues requested per page.
+const ISSUES_PAGE_SIZE: i64 = 50;
+
+/// Returns the cursor of the next issues page, or `None` on the last page.
+/// Issues are ordered by creation but filtered by update time, so a page of
+/// old issues can still be followed by an old issue that was relabeled since
+/// the last poll; every page is worth fetching.
+fn next_issues_cursor(page_info: &issues::IssuesRepositoryIssuesPageInfo) -> Option<String> {
+    page_info.end_cursor.clone().filter(|_| page_info.has_next_page)
+}
+
+/// Returns the canonical `owner/name` of a repository from a `Repository`
+/// query response, or `None` if GitHub could not resolve the repository.
+fn canonical_name_with_owner(
+    body: Response<repository::ResponseData>,
+) -> Result<Option<String>, GithubError> {
+    resolved_field(body, "repository", |data| data.repository.map(|repo| repo.name_with_owner))
+}
+
+/// Extract the top-level `field` of a query response, or `None` if GitHub
+/// could not resolve it. A missing or inaccessible repository or owner is
+/// reported as an error on the field itself; any other error fails the query.
+fn resolved_field<D, T>(
+    body: Response<D>,
+    field: &str,
+    extract: impl FnOnce(D) -> Option<T>,
+) -> Result<Option<T>, GithubError> {
+    let has_data = body.data.is_some();
+    if let Some(value) = body.data.and_then(extract) {
+        return Ok(Some(value));
+    }
+
+    match body.errors {
+        Some(errors) => {
+            let (by_alias, unattributed) = batch::split_errors_by_alias(errors);
+            if unattributed.is_empty() && by_alias.keys().all(|key| key == field) {
+                Ok(None)
+            } else {
+                let msg = format!("GraphQL API reported errors: {by_alias:?} {unattributed:?}");
+                tracing::error!("Permanent GraphQL API error: {msg}");
+                Err(GithubError::GraphQLApiError(msg))
+            }
+        }
+        None if has_data => Ok(None),
+        None => Err(GithubError::GraphQLApiError(
+            "GraphQL response had no data field and no errors reported".to_string(),
+        )),
+    }
+}
+
+/// The upper bound on pages of repositories fetched for a single owner.
+const OWNER_REPOS_MAX_PAGES: usize = 10;
+
+/// The repositories of a GitHub organization or user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerRepos {
+    /// The canonical login of the owner.
+    pub login: String,
+    /// The `owner/name` of every repository owned by the owner that is
+    /// neither archived nor a fork.
+    pub repos: Vec<String>,
+}
+
+/// A request for the open issues of a single repository, see
+/// [`GithubClient::repos_issues_by_label_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoIssuesRequest {
+    /// The owner of the repository.
+    pub owner: String,
+    /// The name of the repository.
+    pub name: String,
+    /// Only issues carrying any of these labels are returned.
+    pub labels: HashSet<String>,
+    /// Only issues updated at or after this time are returned.
+    pub since: Option<chrono::DateTime<Utc>>,
+}
+
+/// An open issue found by a GitHub-wide search, together with the repository
+/// details a search cannot filter by.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIssue {
+    /// The `owner/name` of the repository the issue belongs to.
+    pub repo_name_with_owner: String,
+    /// The number of stars of the repository.
+    pub repo_stars: i64,
+    /// The topics of the repository.
+    pub repo_topics: Vec<String>,
+    /// The issue, in the shape returned by the issues query.
+    pub issue: issues::IssuesRepositoryIssuesNodes,
+}
+
+/// The outcome of a single repository's request within a batch.
+pub type RepoIssuesResult = Result<Vec<issues::IssuesRepositoryIssuesNodes>, GithubError>;
+
 /// A trait for interacting with the GitHub API.
 #[automock]
 #[async_trait]
 pub trait GithubClient: Send + Sync {
-    /// Check if a repository exists.
-    async fn repo_exists(&self, owner: &str, name: &str) -> Result<bool, GithubError>;
+    /// Get the canonical `owner/name` of a repository, following renames and
+    /// transfers and fixing the case. Returns `None` if the repository does
+    /// not exist.
+    async fn repo_name_with_owner(
+        &self,
+        owner: &str,
+        name: &str,
+    ) -> Result<Option<String>, GithubError>;
 
-    /// Get issues by label.
+    /// Get the canonical `owner/name` of many repositories at once, packing
+    /// the lookups into aliased GraphQL requests. The names are returned in
+    /// the order of `repos`, `None` for a repository that does not exist.
+    async fn repos_name_with_owner_batch(
+        &self,
+        repos: Vec<(String, String)>,
+    ) -> Result<Vec<Option<String>>, GithubError>;
+
+    /// Get open issues by label, optionally limited to issues updated at or
+    /// after `since`.
     async fn repo_issues_by_label(
         &self,
         owner: &str,
         name: &str,
         labels: HashSet<String>,
+        since: Option<chrono::DateTime<Utc>>,
     ) -> Result<Vec<issues::IssuesRepositoryIssuesNodes>, GithubError>;
 
+    /// Get open issues by label for many repositories at once, packing them
+    /// into as few GraphQL requests as possible. The results are returned in
+    /// the order of `requests`; an error reported for a single repository only
+    /// fails its own entry, and a repository that no longer exists fails with
+    /// `RepositoryNotFound`. An error on a later page fails the whole entry, so
+    /// the repository is retried from its first page.
+    async fn repos_issues_by_label_batch(
+        &self,
+        requests: Vec<RepoIssuesRequest>,
+    ) -> Result<Vec<RepoIssuesResult>, GithubError>;
+
     /// Get repo labels
     async fn repo_labels(
         &self,
         owner: &str,
         name: &str,
     ) -> Result<Vec<labels::LabelsRepositoryLabelsNodes>, GithubError>;
+
+    /// Get the repositories of an organization or user, skipping archived
+    /// repositories and forks. Returns `None` if the owner does not exist.
+    async fn owner_repositories(&self, login: &str) -> Result<Option<OwnerRepos>, GithubError>;
+
+    /// Search open issues across GitHub, newest first, optionally limited to
+    /// issues updated at or after `since`.
+    async fn search_issues(
+        &self,
+        query: &str,
+        since: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Vec<SearchIssue>, GithubError>;
 }
 
 // GraphQL DateTime scalar type.
@@ -109,15 +277,104 @@ type DateTime = String;
 pub struct Repository;
 
 /// GraphQL query for fetching issues.
+// `Default` cannot be derived for the timeline union, see the manual impl
+// below.
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/github/schema.graphql",
     query_path = "src/github/github.graphql",
-    response_derives = "Debug, Default, serde::Serialize, Clone",
+    response_derives = "Debug, serde::Serialize, Clone",
     variables_derives = "Debug, Clone"
 )]
 pub struct Issues;
 
+impl Default for issues::IssuesRepositoryIssuesNodes {
+    fn default() -> Self {
+        Self {
+            id: String::default(),
+            title: String::default(),
+            url: String::default(),
+            created_at: DateTime::default(),
+            updated_at: DateTime::default(),
+            author: None,
+            body_text: String::default(),
+            comments: issues::IssuesRepositoryIssuesNodesComments { total_count: 0 },
+            reactions: issues::IssuesRepositoryIssuesNodesReactions { total_count: 0 },
+            labels: None,
+            timeline_items: issues::IssuesRepositoryIssuesNodesTimelineItems { nodes: None },
+            assignees: issues::IssuesRepositoryIssuesNodesAssignees { total_count: 0 },
+            linked_items: issues::IssuesRepositoryIssuesNodesLinkedItems { nodes: None },
+        }
+    }
+}
+
+impl issues::IssuesRepositoryIssuesNodes {
+    /// Returns `true` if the issue currently carries any of the given labels.
+    pub fn has_any_label(&self, labels: &HashSet<String>) -> bool {
+        self.labels
+            .iter()
+            .filter_map(|connection| connection.nodes.as_ref())
+            .flatten()
+            .any(|label| labels.contains(&label.name))
+    }
+
+    /// Returns the most recent time one of the given labels was applied to the
+    /// issue, according to its timeline.
+    pub fn last_labeled_at(&self, labels: &HashSet<String>) -> Option<chrono::DateTime<Utc>> {
+        self.timeline_items
+            .nodes
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|item| match item {
+                issues::IssuesRepositoryIssuesNodesTimelineItemsNodes::LabeledEvent(event)
+                    if labels.contains(&event.label.name) =>
+                    chrono::DateTime::parse_from_rfc3339(&event.created_at).ok(),
+                _ => None,
+            })
+            .map(|dt| dt.with_timezone(&Utc))
+            .max()
+    }
+
+    /// Drop the labels of the issue that are not among the given ones, e.g. to
+    /// only show the labels a chat tracks.
+    pub fn retain_labels(&mut self, labels: &HashSet<String>) {
+        if let Some(nodes) = self.labels.as_mut().and_then(|connection| connection.nodes.as_mut()) {
+            nodes.retain(|label| labels.contains(&label.name));
+        }
+    }
+
+    /// Returns `true` if anyone is assigned to the issue.
+    pub fn is_assigned(&self) -> bool {
+        self.assignees.total_count > 0
+    }
+
+    /// Returns `true` if an open pull request is linked to the issue, either
+    /// through the development sidebar or by a closing keyword such as
+    /// "fixes #123". Only the most recent links are looked at.
+    pub fn has_open_linked_pr(&self) -> bool {
+        use issues::{
+            IssuesRepositoryIssuesNodesLinkedItemsNodes as Item,
+            IssuesRepositoryIssuesNodesLinkedItemsNodesOnConnectedEventSubject as Subject,
+            IssuesRepositoryIssuesNodesLinkedItemsNodesOnCrossReferencedEventSource as Source,
+        };
+
+        self.linked_items.nodes.iter().flatten().flatten().any(|item| match item {
+            Item::ConnectedEvent(event) => matches!(
+                &event.subject,
+                Subject::PullRequest(pr) if !pr.closed
+            ),
+            Item::CrossReferencedEvent(event) =>
+                event.will_close_target
+                    && matches!(
+                        &event.source,
+                        Source::PullRequest(pr) if !pr.closed
+                    ),
+            _ => false,
+        })
+    }
+}
+
 /// GraphQL query for fetching labels.
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -128,6 +385,76 @@ pub struct Issues;
 )]
 pub struct Labels;
 
+/// GraphQL query for listing the repositories of an organization or user.
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/github.graphql",
+    response_derives = "Debug, Default, serde::Serialize, Clone",
+    variables_derives = "Debug, Clone"
+)]
+pub struct OwnerRepositories;
+
+/// GraphQL query for searching issues across GitHub.
+// `Default` cannot be derived for the search result union.
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/github/schema.graphql",
+    query_path = "src/github/github.graphql",
+    response_derives = "Debug, serde::Serialize, Clone",
+    variables_derives = "Debug, Clone"
+)]
+pub struct SearchIssues;
+
+impl From<search_issues::SearchIssuesSearchNodesOnIssue> for SearchIssue {
+    fn from(node: search_issues::SearchIssuesSearchNodesOnIssue) -> Self {
+        let labels = node.labels.map(|connection| issues::IssuesRepositoryIssuesNodesLabels {
+            nodes: connection.nodes.map(|nodes| {
+                nodes
+                    .into_iter()
+                    .map(|label| issues::IssuesRepositoryIssuesNodesLabelsNodes {
+                        name: label.name,
+                        color: label.color,
+                    })
+                    .collect()
+            }),
+        });
+
+        Self {
+            repo_name_with_owner: node.repository.name_with_owner,
+            repo_stars: node.repository.stargazer_count,
+            repo_topics: node
+                .repository
+                .repository_topics
+                .nodes
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|node| node.topic.name)
+                .collect(),
+            issue: issues::IssuesRepositoryIssuesNodes {
+                id: node.id,
+                title: node.title,
+                url: node.url,
+                created_at: node.created_at,
+                updated_at: node.updated_at,
+                author: node.author.map(|author| issues::IssuesRepositoryIssuesNodesAuthor {
+                    login: author.login,
+                }),
+                body_text: node.body_text,
+                comments: issues::IssuesRepositoryIssuesNodesComments {
+                    total_count: node.comments.total_count,
+                },
+                reactions: issues::IssuesRepositoryIssuesNodesReactions {
+                    total_count: node.reactions.total_count,
+                },
+                labels,
+                ..Default::default()
+            },
+        }
+    }
+}
+
 /// The default implementation of the `GithubClient` trait.
 #[derive(Clone)]
 pub struct DefaultGithubClient {
@@ -135,6 +462,8 @@ pub struct DefaultGithubClient {
     graphql_url: String,
     rate_limit: Arc<Mutex<RateLimitState>>,
     rate_limit_threshold: u64,
+    query_costs: Arc<Mutex<HashMap<String, QueryCost>>>,
+    max_issue_pages: usize,
 }
 
 impl DefaultGithubClient {
@@ -143,6 +472,7 @@ impl DefaultGithubClient {
         github_token: &str,
         graphql_url: &str,
         rate_limit_threshold: u64,
+        max_issue_pages: usize,
     ) -> Result<Self, GithubError> {
         // Build the HTTP client with the GitHub token.
         let mut headers = HeaderMap::new();
@@ -159,6 +489,8 @@ impl DefaultGithubClient {
             graphql_url: graphql_url.to_string(),
             rate_limit: Arc::new(Mutex::new(initial_state)),
             rate_limit_threshold,
+            query_costs: Arc::new(Mutex::new(HashMap::new())),
+            max_issue_pages: max_issue_pages.max(1),
         })
     }
 
@@ -183,17 +515,61 @@ impl DefaultGithubClient {
         Q::Variables: Clone,
         Q::ResponseData: serde::de::DeserializeOwned,
     {
+        let request_body = Q::build_query(variables);
+        let body: Response<Q::ResponseData> =
+            self.send_graphql(request_body.operation_name, 1, &request_body).await?;
+
+        // Any remaining GraphQL error fails the whole query
+        if let Some(errors) = &body.errors {
+            let msg = format!("GraphQL API reported errors: {errors:?}");
+            tracing::error!("Permanent GraphQL API error: {msg}");
+            return Err(GithubError::GraphQLApiError(msg));
+        }
+
+        // Unwrap the data or permanent-fail
+        body.data.ok_or_else(|| {
+            tracing::error!("GraphQL response had no data field; permanent failure");
+            GithubError::GraphQLApiError(
+                "GraphQL response had no data field and no errors reported".to_string(),
+            )
+        })
+    }
+
+    /// Send a GraphQL request body, retrying on transient failures and rate
+    /// limits. Other GraphQL errors are returned in the response, so callers
+    /// can decide whether a partial response is usable.
+    ///
+    /// Every query selects the `rateLimit` field; it is consumed here to track
+    /// the cost of `operation_name` and removed from the data before it is
+    /// deserialized. `units` is the amount of work in the request, e.g. the
+    /// number of repositories in a batch, and scales the cost estimate.
+    async fn send_graphql<B, T>(
+        &self,
+        operation_name: &str,
+        units: u32,
+        request_body: &B,
+    ) -> Result<Response<T>, GithubError>
+    where
+        B: serde::Serialize + ?Sized,
+        T: serde::de::DeserializeOwned,
+    {
+        // Reserve the expected cost once, however many attempts the query takes
+        let estimated_cost = self.estimated_cost(operation_name, units).await;
+        self.rate_limit_guard(estimated_cost).await;
+        let attempts = AtomicU32::new(0);
+        // Whether GitHub reported the remaining points, replacing the reservation
+        let settled = AtomicBool::new(false);
+
         // closure that Backoff expects
         let operation = || async {
-            // 0. Rate limit guard
-            self.rate_limit_guard().await;
-
-            // 1. Build the request
-            let request_body = Q::build_query(variables.clone());
+            // 0. Retries only wait for the budget, their cost is reserved already
+            if attempts.fetch_add(1, Ordering::Relaxed) > 0 {
+                self.rate_limit_guard(0).await;
+            }
 
-            // 2. Send HTTP
+            // 1. Send HTTP
             let resp =
-                self.client.post(&self.graphql_url).json(&request_body).send().await.map_err(
+                self.client.post(&self.graphql_url).json(request_body).send().await.map_err(
                     |e| {
                         tracing::warn!("Network error sending GraphQL request: {e}. Retrying...");
                         BackoffError::transient(GithubError::RequestError { source: e })
@@ -201,9 +577,10 @@ impl DefaultGithubClient {
                 )?;
 
             //3 Update rate limit state from headers
-            if let Err(e) = self.update_rate_limit_from_headers(resp.headers()).await {
+            match self.update_rate_limit_from_headers(resp.headers()).await {
+                Ok(()) => settled.store(true, Ordering::Relaxed),
                 // Option A: warn and continue
-                tracing::warn!("Could not update rate-limit info: {}", e);
+                Err(e) => tracing::warn!("Could not update rate-limit info: {}", e),
             }
 
             // 4. HTTP-status check
@@ -251,54 +628,129 @@ impl DefaultGithubClient {
             }
 
             // 5. Parse JSON
-            let body: Response<Q::ResponseData> = resp.json().await.map_err(|e| {
+            let mut body: Response<serde_json::Value> = resp.json().await.map_err(|e| {
                 tracing::warn!("Failed to parse JSON: {e}. Retrying...");
                 BackoffError::transient(GithubError::GraphQLApiError(format!(
                     "JSON parse error: {e}"
                 )))
             })?;
 
-            // 6. GraphQL errors?
+            // 6. Record the cost of the query
+            let rate_limit = body
+                .data
+                .as_mut()
+                .and_then(|data| data.as_object_mut())
+                .and_then(|data| data.remove("rateLimit"))
+                .filter(|value| !value.is_null());
+            if let Some(rate_limit) = rate_limit {
+                match serde_json::from_value::<RateLimitInfo>(rate_limit) {
+                    Ok(info) => {
+                        self.record_query_cost(operation_name, units, &info).await;
+                        settled.store(true, Ordering::Relaxed);
+                    }
+                    Err(e) => tracing::warn!("Could not parse rateLimit for {operation_name}: {e}"),
+                }
+            }
+
+            // 7. Rate limit errors are retried, everything else is up to the caller
             if let Some(errors) = &body.errors {
                 let is_rate_limit_error = errors.iter().any(|e| {
                     e.message.to_lowercase().contains("rate limit") || is_retryable_graphql_error(e)
                 });
 
-                let msg = format!("GraphQL API reported errors: {errors:?}");
-
                 if is_rate_limit_error {
-                    tracing::warn!("Retryable GraphQL API error: {msg}. Retrying...");
+                    tracing::warn!("Retryable GraphQL API error: {errors:?}. Retrying...");
                     return Err(BackoffError::transient(GithubError::RateLimited));
-                } else {
-                    tracing::error!("Permanent GraphQL API error: {msg}");
-                    return Err(BackoffError::permanent(GithubError::GraphQLApiError(msg)));
                 }
             }
 
-            // 7. Unwrap the data or permanent-fail
-            body.data.ok_or_else(|| {
-                tracing::error!("GraphQL response had no data field; permanent failure");
-                BackoffError::permanent(GithubError::GraphQLApiError(
-                    "GraphQL response had no data field and no errors reported".to_string(),
-                ))
-            })
+            // 8. Deserialize the remaining data into the expected type
+            let data = body
+                .data
+                .map(serde_json::from_value::<T>)
+                .transpose()
+                .map_err(|e| BackoffError::permanent(GithubError::from(e)))?;
+
+            Ok(Response { data, errors: body.errors, extensions: body.extensions })
         };
 
         // kick off the retry loop
-        retry(Self::backoff_config(), operation).await
+        let result = retry(Self::backoff_config(), operation).await;
+
+        if !settled.load(Ordering::Relaxed) {
+            self.release_reservation(estimated_cost).await;
+        }
+        result
+    }
+
+    /// The expected cost of `units` of work of the given query type, based on
+    /// the last observed cost. Unknown queries are assumed to cost a point per
+    /// unit, the minimum GitHub charges.
+    async fn estimated_cost(&self, operation: &str, units: u32) -> u32 {
+        let per_unit = self
+            .query_costs
+            .lock()
+            .await
+            .get(operation)
+            .map(|cost| cost.last_cost_per_unit)
+            .unwrap_or(1);
+
+        per_unit.max(1).saturating_mul(units.max(1))
+    }
+
+    /// Record the cost reported for a query and update the rate limit state
+    /// with the authoritative remaining points.
+    async fn record_query_cost(&self, operation: &str, units: u32, info: &RateLimitInfo) {
+        {
+            let mut costs = self.query_costs.lock().await;
+            let cost = costs.entry(operation.to_string()).or_default();
+            cost.requests += 1;
+            cost.points += u64::from(info.cost);
+            cost.last_cost_per_unit = info.cost.div_ceil(units.max(1));
+            cost.last_node_count = info.node_count;
+        }
+
+        let mut state = self.rate_limit.lock().await;
+        state.remaining = info.remaining;
+        match chrono::DateTime::parse_from_rfc3339(&info.reset_at) {
+            Ok(reset_at) => {
+                let reset_in = (reset_at.with_timezone(&Utc) - Utc::now()).to_std();
+                state.reset_at = Instant::now() + reset_in.unwrap_or_default();
+            }
+            Err(e) => tracing::warn!("Invalid rateLimit resetAt {}: {e}", info.reset_at),
+        }
+
+        tracing::debug!(
+            "Query {} cost {} points ({} nodes), {} remaining",
+            operation,
+            info.cost,
+            info.node_count,
+            info.remaining
+        );
+    }
+
+    /// The points consumed so far, per type of query.
+    pub async fn query_costs(&self) -> HashMap<String, QueryCost> {
+        self.query_costs.lock().await.clone()
     }
 
-    /// Rate limit guard that sleeps until the rate limit resets if we're close
-    /// to the threshold.
-    async fn rate_limit_guard(&self) {
+    /// Rate limit guard that sleeps until the rate limit resets if sending a
+    /// query of `estimated_cost` points would take us below the threshold.
+    /// Otherwise the cost is reserved, so concurrent queries don't overspend
+    /// the budget before GitHub reports the actual remaining points. The
+    /// reservation is replaced by the remaining points GitHub reports, or
+    /// released if it never does.
+    async fn rate_limit_guard(&self, estimated_cost: u32) {
         let (remaining, reset_at) = {
-            let state = self.rate_limit.lock().await;
-            (state.remaining, state.reset_at)
+            let mut state = self.rate_limit.lock().await;
+            let snapshot = (state.remaining, state.reset_at);
+            state.remaining = state.remaining.saturating_sub(estimated_cost);
+            snapshot
         };
 
         // define a safety threshold
         let threshold = self.rate_limit_threshold as u32;
-        if remaining <= threshold {
+        if remaining < threshold.saturating_add(estimated_cost) {
             let now = Instant::now();
             if now < reset_at {
                 let wait = reset_at - now;
@@ -317,6 +769,13 @@ impl DefaultGithubClient {
         }
     }
 
+    /// Give back points reserved by `rate_limit_guard` for a query whose
+    /// actual cost GitHub never reported, e.g. because it never got a response.
+    async fn release_reservation(&self, reserved_cost: u32) {
+        let mut state = self.rate_limit.lock().await;
+        state.remaining = state.remaining.saturating_add(reserved_cost);
+    }
+
     /// Update the rate limit state from the response headers.
     async fn update_rate_limit_from_headers(&self, headers: &HeaderMap) -> Result<(), GithubError> {
         // Names are case-insensitive in HeaderMap
@@ -366,35 +825,182 @@ impl DefaultGithubClient {
 #[async_trait]
 impl GithubClient for DefaultGithubClient {
     /// Check if a repository exists.
-    async fn repo_exists(&self, owner: &str, name: &str) -> Result<bool, GithubError> {
-        tracing::debug!("Checking if repository {}/{} exists", owner, name);
-        let data = self
-            .execute_graphql::<Repository>(repository::Variables {
-                owner: owner.to_string(),
-                name: name.to_string(),
-            })
-            .await?;
+    async fn repo_name_with_owner(
+        &self,
+        owner: &str,
+        name: &str,
+    ) -> Result<Option<String>, GithubError> {
+        tracing::debug!("Resolving repository {}/{}", owner, name);
+        let request_body = Repository::build_query(repository::Variables {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        });
+        let body = self.send_graphql(request_body.operation_name, 1, &request_body).await?;
 
-        Ok(data.repository.is_some())
+        canonical_name_with_owner(body)
     }
 
-    /// Get issues by label.
+    /// Look the repositories up in chunks of `MAX_REPOS_PER_QUERY`. As for a
+    /// single lookup, a missing or inaccessible repository is reported as an
+    /// error on its own alias and yields `None`; any other error fails the
+    /// lookup.
+    async fn repos_name_with_owner_batch(
+        &self,
+        repos: Vec<(String, String)>,
+    ) -> Result<Vec<Option<String>>, GithubError> {
+        let mut names = Vec::with_capacity(repos.len());
+
+        for chunk in repos.chunks(batch::MAX_REPOS_PER_QUERY) {
+            tracing::debug!("Resolving a batch of {} repositories", chunk.len());
+            let body: Response<HashMap<String, Option<batch::RepoName>>> = self
+                .send_graphql(
+                    batch::REPO_NAMES_OPERATION_NAME,
+                    chunk.len() as u32,
+                    &batch::build_repo_names_batch(chunk),
+                )
+                .await?;
+
+            let (errors_by_alias, unattributed) =
+                batch::split_errors_by_alias(body.errors.unwrap_or_default());
+            // Without an alias to blame, an error says nothing about which
+            // repositories exist, so the whole batch fails
+            let (Some(mut data), true) = (body.data, unattributed.is_empty()) else {
+                let msg = format!("GraphQL API reported errors: {unattributed:?}");
+                tracing::error!("Permanent GraphQL API error: {msg}");
+                return Err(GithubError::GraphQLApiError(msg));
+            };
+
+            for (i, (owner, name)) in chunk.iter().enumerate() {
+                let alias = batch::alias(i);
+                if let Some(errors) = errors_by_alias.get(&alias) {
+                    tracing::debug!("Could not resolve repository {owner}/{name}: {errors:?}");
+                }
+                names.push(data.remove(&alias).flatten().map(|repo| repo.name_with_owner));
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Get open issues by label, newest first, optionally limited to issues
+    /// updated at or after `since`.
     async fn repo_issues_by_label(
         &self,
         owner: &str,
         name: &str,
         labels: HashSet<String>,
+        since: Option<chrono::DateTime<Utc>>,
     ) -> Result<Vec<issues::IssuesRepositoryIssuesNodes>, GithubError> {
-        let data = self
-            .execute_graphql::<Issues>(issues::Variables {
-                owner: owner.to_string(),
-                name: name.to_string(),
-                labels: Some(labels.into_iter().collect()),
-                first: Some(10),
-            })
-            .await?;
+        let request =
+            RepoIssuesRequest { owner: owner.to_string(), name: name.to_string(), labels, since };
 
-        Ok(data.repository.and_then(|r| r.issues).and_then(|i| i.nodes).unwrap_or_default())
+        self.repos_issues_by_label_batch(vec![request]).await?.pop().unwrap_or_else(|| {
+            Err(GithubError::GraphQLApiError("Batch returned no result".to_string()))
+        })
+    }
+
+    /// Get open issues by label for many repositories, newest first. Each
+    /// round fetches one page for every repository that still has pages worth
+    /// fetching, packed into as few aliased queries as the node limits allow.
+    /// A repository's pagination stops on its last page or at the configured
+    /// page cap.
+    async fn repos_issues_by_label_batch(
+        &self,
+        requests: Vec<RepoIssuesRequest>,
+    ) -> Result<Vec<RepoIssuesResult>, GithubError> {
+        let mut results: Vec<RepoIssuesResult> = requests.iter().map(|_| Ok(Vec::new())).collect();
+        let mut pending: Vec<batch::PendingPage> = requests
+            .iter()
+            .enumerate()
+            .map(|(index, request)| batch::PendingPage { index, request, after: None })
+            .collect();
+
+        for page in 1..=self.max_issue_pages {
+            let mut next_pending = Vec::new();
+
+            for chunk in pending.chunks(batch::batch_size()) {
+                let body: Response<HashMap<String, Option<issues::IssuesRepository>>> = self
+                    .send_graphql(
+                        batch::OPERATION_NAME,
+                        chunk.len() as u32,
+                        &batch::build_issues_batch(chunk),
+                    )
+                    .await?;
+
+                let (mut errors_by_alias, unattributed) =
+                    batch::split_errors_by_alias(body.errors.unwrap_or_default());
+                let Some(mut data) = body.data else {
+                    let msg = format!("GraphQL API reported errors: {unattributed:?}");
+                    tracing::error!("Permanent GraphQL API error: {msg}");
+                    return Err(GithubError::GraphQLApiError(msg));
+                };
+                if !unattributed.is_empty() {
+                    tracing::warn!("GraphQL API reported errors for the batch: {unattributed:?}");
+                }
+
+                for (i, pending_page) in chunk.iter().enumerate() {
+                    let alias = batch::alias(i);
+                    let request = pending_page.request;
+
+                    // Errors pointing into this repository only fail this repository. The
+                    // pages fetched so far are dropped as well: the caller moves its polling
+                    // window on success, which would skip the issues of the missing pages.
+                    if let Some(errors) = errors_by_alias.remove(&alias) {
+                        let msg = format!("GraphQL API reported errors: {errors:?}");
+                        tracing::warn!(
+                            "Failed to fetch issues for {}/{}: {msg}",
+                            request.owner,
+                            request.name
+                        );
+                        results[pending_page.index] = Err(GithubError::GraphQLApiError(msg));
+                        continue;
+                    }
+
+                    let Some(repository) = data.remove(&alias).flatten() else {
+                        tracing::warn!(
+                            "Repository {}/{} was not found",
+                            request.owner,
+                            request.name
+                        );
+                        results[pending_page.index] = Err(GithubError::RepositoryNotFound(
+                            format!("{}/{}", request.owner, request.name),
+                        ));
+                        continue;
+                    };
+                    let Some(connection) = repository.issues else {
+                        continue;
+                    };
+                    let nodes = connection.nodes.unwrap_or_default();
+                    let next_cursor = next_issues_cursor(&connection.page_info);
+                    if let Ok(issues) = &mut results[pending_page.index] {
+                        issues.extend(nodes);
+                    }
+
+                    match next_cursor {
+                        Some(cursor) if page < self.max_issue_pages =>
+                            next_pending.push(batch::PendingPage {
+                                after: Some(cursor),
+                                ..pending_page.clone()
+                            }),
+                        Some(_) => tracing::warn!(
+                            "Reached the limit of {} issue pages for {}/{}; older issues were \
+                             skipped",
+                            self.max_issue_pages,
+                            request.owner,
+                            request.name
+                        ),
+                        None => {}
+                    }
+                }
+            }
+
+            if next_pending.is_empty() {
+                break;
+            }
+            pending = next_pending;
+        }
+
+        Ok(results)
     }
 
     /// Get repo labels
@@ -412,4 +1018,109 @@ impl GithubClient for DefaultGithubClient {
 
         Ok(data.repository.and_then(|r| r.labels).and_then(|l| l.nodes).unwrap_or_default())
     }
+
+    /// Get the repositories of an organization or user, page by page up to
+    /// `OWNER_REPOS_MAX_PAGES`.
+    async fn owner_repositories(&self, login: &str) -> Result<Option<OwnerRepos>, GithubError> {
+        tracing::debug!("Listing repositories of {}", login);
+        let mut owner_repos: Option<OwnerRepos> = None;
+        let mut after = None;
+
+        for page in 1..=OWNER_REPOS_MAX_PAGES {
+            let request_body = OwnerRepositories::build_query(owner_repositories::Variables {
+                login: login.to_string(),
+                after: after.take(),
+            });
+            let body = self.send_graphql(request_body.operation_name, 1, &request_body).await?;
+
+            let Some(owner) = resolved_field(
+                body,
+                "repositoryOwner",
+                |data: owner_repositories::ResponseData| data.repository_owner,
+            )?
+            else {
+                return Ok(None);
+            };
+
+            let connection = owner.repositories;
+            let result = owner_repos
+                .get_or_insert_with(|| OwnerRepos { login: owner.login, repos: Vec::new() });
+            result.repos.extend(
+                connection
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|repo| !repo.is_archived && !repo.is_fork)
+                    .map(|repo| repo.name_with_owner),
+            );
+
+            match connection.page_info.end_cursor {
+                Some(cursor) if connection.page_info.has_next_page => {
+                    if page == OWNER_REPOS_MAX_PAGES {
+                        tracing::warn!(
+                            "Reached the limit of {OWNER_REPOS_MAX_PAGES} repository pages for \
+                             {login}; remaining repositories were skipped"
+                        );
+                    }
+                    after = Some(cursor);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(owner_repos)
+    }
+
+    /// Search open issues across GitHub, newest first, page by page up to the
+    /// configured page cap.
+    async fn search_issues(
+        &self,
+        query: &str,
+        since: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Vec<SearchIssue>, GithubError> {
+        let mut query = format!("{query} sort:created-desc");
+        if let Some(since) = since {
+            query.push_str(&format!(" updated:>={}", since.format("%Y-%m-%dT%H:%M:%SZ")));
+        }
+        tracing::debug!("Searching issues: {}", query);
+
+        let mut results = Vec::new();
+        let mut after = None;
+
+        for page in 1..=self.max_issue_pages {
+            let data = self
+                .execute_graphql::<SearchIssues>(search_issues::Variables {
+                    query: query.clone(),
+                    first: ISSUES_PAGE_SIZE,
+                    after: after.take(),
+                })
+                .await?;
+
+            let connection = data.search;
+            results.extend(connection.nodes.into_iter().flatten().flatten().filter_map(|node| {
+                match node {
+                    search_issues::SearchIssuesSearchNodes::Issue(issue) =>
+                        Some(SearchIssue::from(issue)),
+                    search_issues::SearchIssuesSearchNodes::PullRequest => None,
+                }
+            }));
+
+            match connection.page_info.end_cursor {
+                Some(cursor) if connection.page_info.has_next_page => {
+                    if page == self.max_issue_pages {
+                        tracing::warn!(
+                            "Reached the limit of {} search pages for {query}; older issues were \
+                             skipped",
+                            self.max_issue_pages
+                        );
+                    }
+                    after = Some(cursor);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(results)
+    }
 }
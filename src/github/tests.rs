@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use axum::response::IntoResponse;
+
 use super::*;
 
 /// Helper: set the shared rate-limit state so the guard will sleep `wait_ms`
@@ -13,13 +15,13 @@ async fn prime_state(client: &DefaultGithubClient, remaining: u32, wait_ms: u64)
 /// Helper: run the guard once and return how long it actually waited.
 async fn measure_sleep(client: &DefaultGithubClient) -> Duration {
     let start = Instant::now();
-    client.rate_limit_guard().await;
+    client.rate_limit_guard(1).await;
     start.elapsed()
 }
 
 #[test]
 fn test_new_github_client() {
-    let client = DefaultGithubClient::new("test_token", "https://api.github.com/graphql", 10);
+    let client = DefaultGithubClient::new("test_token", "https://api.github.com/graphql", 10, 5);
     assert!(client.is_ok());
 }
 
@@ -55,8 +57,8 @@ fn test_is_not_retryable_graphql_error() {
 
 #[tokio::test]
 async fn test_update_rate_limit_from_headers() {
-    let client =
-        DefaultGithubClient::new("fake", "https://api.github.com/graphql", 5).expect("client init");
+    let client = DefaultGithubClient::new("fake", "https://api.github.com/graphql", 5, 5)
+        .expect("client init");
 
     // Build fake headers with remaining=3, reset in 60s
     let mut headers = HeaderMap::new();
@@ -80,7 +82,7 @@ async fn rate_limit_guard_sleeps_when_below_threshold_and_before_reset() {
     // -------- Arrange --------
     let threshold = 5;
     let client =
-        DefaultGithubClient::new("fake_token", "https://example.com/graphql", threshold as u64)
+        DefaultGithubClient::new("fake_token", "https://example.com/graphql", threshold as u64, 5)
             .expect("client");
 
     const WAIT_MS: u64 = 40;
@@ -96,7 +98,7 @@ async fn rate_limit_guard_sleeps_when_below_threshold_and_before_reset() {
 
     // -------- Act --------
     let start = Instant::now();
-    client.rate_limit_guard().await;
+    client.rate_limit_guard(1).await;
     let elapsed = start.elapsed();
 
     // -------- Assert --------
@@ -118,7 +120,7 @@ async fn jitter_is_within_bounds() {
     const FUDGE_MS: u64 = 8;
 
     let client =
-        DefaultGithubClient::new("fake", "https://example/graphql", THRESHOLD).expect("client");
+        DefaultGithubClient::new("fake", "https://example/graphql", THRESHOLD, 5).expect("client");
 
     // Force a sleep path
     prime_state(&client, THRESHOLD as u32, WAIT_MS).await;
@@ -148,7 +150,7 @@ async fn jitter_varies_across_runs() {
     const FUDGE_MS: u64 = 8;
 
     let client =
-        DefaultGithubClient::new("fake", "https://example/graphql", THRESHOLD).expect("client");
+        DefaultGithubClient::new("fake", "https://example/graphql", THRESHOLD, 5).expect("client");
 
     let mut samples = Vec::with_capacity(RUNS);
 
@@ -189,7 +191,7 @@ async fn jitter_varies_across_runs() {
 async fn no_jitter_when_wait_is_zero() {
     const THRESHOLD: u64 = 1;
     let client =
-        DefaultGithubClient::new("fake", "https://example/graphql", THRESHOLD).expect("client");
+        DefaultGithubClient::new("fake", "https://example/graphql", THRESHOLD, 5).expect("client");
 
     // Force path where remaining <= threshold but reset_at == now
     let mut s = client.rate_limit.lock().await;
@@ -201,3 +203,531 @@ async fn no_jitter_when_wait_is_zero() {
     let elapsed = measure_sleep(&client).await;
     assert!(elapsed < Duration::from_millis(2), "Guard unexpectedly slept: {:?}", elapsed);
 }
+
+fn page_info(has_next_page: bool) -> issues::IssuesRepositoryIssuesPageInfo {
+    issues::IssuesRepositoryIssuesPageInfo { has_next_page, end_cursor: Some("cursor".to_string()) }
+}
+
+#[test]
+fn test_next_issues_cursor_follows_cursor() {
+    assert_eq!(next_issues_cursor(&page_info(true)), Some("cursor".to_string()));
+}
+
+#[test]
+fn test_next_issues_cursor_stops_on_last_page() {
+    assert_eq!(next_issues_cursor(&page_info(false)), None);
+}
+
+// Helper to build an issue node of a GraphQL response
+fn issue_json(id: &str, created_at: chrono::DateTime<Utc>) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "title": id,
+        "url": format!("https://github.com/owner/repo/issues/{id}"),
+        "createdAt": created_at.to_rfc3339(),
+        "updatedAt": Utc::now().to_rfc3339(),
+        "author": { "login": "octocat" },
+        "bodyText": "",
+        "comments": { "totalCount": 0 },
+        "reactions": { "totalCount": 0 },
+        "labels": { "nodes": [] },
+        "timelineItems": { "nodes": [] },
+        "assignees": { "totalCount": 0 },
+        "linkedItems": { "nodes": [] }
+    })
+}
+
+#[tokio::test]
+async fn test_repos_issues_by_label_batch_fetches_pages_after_old_issues() {
+    let since = Utc::now() - chrono::Duration::hours(1);
+    // Issues come newest first, so an old issue relabeled since the last poll
+    // can come after a page of other old issues
+    let handler = move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+        let (nodes, has_next_page) = if body["variables"]["after0"].is_null() {
+            let old = since - chrono::Duration::days(30);
+            (vec![issue_json("new", Utc::now()), issue_json("old", old)], true)
+        } else {
+            (vec![issue_json("relabeled", since - chrono::Duration::days(365))], false)
+        };
+        axum::Json(serde_json::json!({
+            "data": {
+                "r0": {
+                    "issues": {
+                        "pageInfo": { "hasNextPage": has_next_page, "endCursor": "cursor" },
+                        "nodes": nodes
+                    }
+                }
+            }
+        }))
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+    let router = axum::Router::new().route("/graphql", axum::routing::post(handler));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = DefaultGithubClient::new("fake", &url, 10, 5).unwrap();
+    let request = RepoIssuesRequest { since: Some(since), ..issues_request("owner", "repo") };
+
+    let mut results = client.repos_issues_by_label_batch(vec![request]).await.unwrap();
+
+    let ids: Vec<String> =
+        results.pop().unwrap().unwrap().into_iter().map(|issue| issue.id).collect();
+    assert_eq!(ids, vec!["new", "old", "relabeled"]);
+}
+
+#[tokio::test]
+async fn test_repos_issues_by_label_batch_reports_missing_repos_and_failed_pages() {
+    let handler = |axum::Json(body): axum::Json<serde_json::Value>| async move {
+        let response = if body["variables"]["after0"].is_null() {
+            // The first repository is gone, the second has another page
+            serde_json::json!({
+                "data": {
+                    "r0": null,
+                    "r1": {
+                        "issues": {
+                            "pageInfo": { "hasNextPage": true, "endCursor": "cursor" },
+                            "nodes": [issue_json("first-page", Utc::now())]
+                        }
+                    }
+                }
+            })
+        } else {
+            serde_json::json!({
+                "data": { "r0": null },
+                "errors": [{ "message": "Something went wrong", "path": ["r0", "issues"] }]
+            })
+        };
+        axum::Json(response)
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+    let router = axum::Router::new().route("/graphql", axum::routing::post(handler));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = DefaultGithubClient::new("fake", &url, 10, 5).unwrap();
+
+    let results = client
+        .repos_issues_by_label_batch(vec![
+            issues_request("owner", "gone"),
+            issues_request("owner", "repo"),
+        ])
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(&results[0], Err(GithubError::RepositoryNotFound(name)) if name == "owner/gone")
+    );
+    // The whole repository fails, so it is retried from its first page
+    assert!(matches!(&results[1], Err(GithubError::GraphQLApiError(_))));
+}
+
+#[tokio::test]
+async fn test_repos_name_with_owner_batch() {
+    let handler = |axum::Json(body): axum::Json<serde_json::Value>| async move {
+        assert_eq!(body["operationName"], "RepoNamesBatch");
+        assert_eq!(body["variables"]["owner0"], "old-owner");
+        assert_eq!(body["variables"]["name2"], "private");
+        axum::Json(serde_json::json!({
+            "data": {
+                "r0": { "nameWithOwner": "new-owner/new-name" },
+                "r1": null,
+                "r2": null,
+                "rateLimit": { "cost": 1, "remaining": 4999, "resetAt": "2030-01-01T00:00:00Z", "nodeCount": 3 }
+            },
+            "errors": [
+                { "message": "Could not resolve to a Repository", "path": ["r1"] },
+                { "message": "Resource not accessible", "path": ["r2"] }
+            ]
+        }))
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+    let router = axum::Router::new().route("/graphql", axum::routing::post(handler));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = DefaultGithubClient::new("fake", &url, 10, 5).unwrap();
+
+    let names = client
+        .repos_name_with_owner_batch(vec![
+            ("old-owner".to_string(), "old-name".to_string()),
+            ("owner".to_string(), "deleted".to_string()),
+            ("owner".to_string(), "private".to_string()),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(names, vec![Some("new-owner/new-name".to_string()), None, None]);
+}
+
+#[tokio::test]
+async fn test_repos_name_with_owner_batch_fails_on_unattributed_errors() {
+    let handler = || async {
+        axum::Json(serde_json::json!({
+            "data": { "r0": null },
+            "errors": [{ "message": "Something went wrong" }]
+        }))
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+    let router = axum::Router::new().route("/graphql", axum::routing::post(handler));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let client = DefaultGithubClient::new("fake", &url, 10, 5).unwrap();
+
+    let result =
+        client.repos_name_with_owner_batch(vec![("owner".to_string(), "repo".to_string())]).await;
+
+    assert!(matches!(result, Err(GithubError::GraphQLApiError(_))));
+}
+
+#[test]
+fn test_build_repo_names_batch_aliases_each_repo() {
+    let repos = vec![
+        ("owner".to_string(), "first".to_string()),
+        ("owner".to_string(), "second".to_string()),
+    ];
+
+    let body = batch::build_repo_names_batch(&repos);
+
+    let query = normalize_whitespace(body["query"].as_str().unwrap());
+    assert!(query.starts_with(
+        "query RepoNamesBatch($owner0: String!, $name0: String!, $owner1: String!, $name1: \
+         String!) {"
+    ));
+    assert!(query.contains("r0: repository(owner: $owner0, name: $name0) { nameWithOwner }"));
+    assert!(query.contains("r1: repository(owner: $owner1, name: $name1) { nameWithOwner }"));
+    assert!(query.contains("rateLimit {"));
+    assert_eq!(body["variables"]["name1"], "second");
+    assert_eq!(body["operationName"], "RepoNamesBatch");
+}
+
+fn issues_request(owner: &str, name: &str) -> RepoIssuesRequest {
+    RepoIssuesRequest {
+        owner: owner.to_string(),
+        name: name.to_string(),
+        labels: HashSet::from(["bug".to_string()]),
+        since: None,
+    }
+}
+
+/// Collapse all whitespace, so selections can be compared regardless of
+/// indentation.
+fn normalize_whitespace(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[test]
+fn test_batch_selection_is_taken_from_issues_query() {
+    let selection = normalize_whitespace(*batch::ISSUES_SELECTION);
+
+    assert!(selection.starts_with("{ pageInfo { hasNextPage endCursor } nodes { id title url"));
+    // Ends with the issue connection, before the fields following it
+    assert!(!selection.contains("rateLimit"));
+    assert!(normalize_whitespace(issues::QUERY).contains(&selection));
+}
+
+#[test]
+fn test_issues_selection_follows_the_query() {
+    let document = "query Other { issues(first: 1) { a } }\nquery Issues($first: Int) {\n  \
+                    repository(owner: \"o\", name: \"n\") {\n    issues(first: $first, filterBy: \
+                    {since: null}) {\n      nodes { id labels { name } }\n    }\n  }\n  rateLimit \
+                    { cost }\n}";
+
+    assert_eq!(batch::issues_selection(document), "{\n      nodes { id labels { name } }\n    }");
+}
+
+#[test]
+fn test_build_issues_batch_aliases_each_repo() {
+    let first = issues_request("owner", "first");
+    let second = issues_request("owner", "second");
+    let pages = vec![
+        batch::PendingPage { index: 0, request: &first, after: None },
+        batch::PendingPage { index: 1, request: &second, after: Some("cursor".to_string()) },
+    ];
+
+    let body = batch::build_issues_batch(&pages);
+
+    let query = body["query"].as_str().unwrap();
+    assert!(query.contains("r0: repository(owner: $owner0, name: $name0)"));
+    assert!(query.contains("r1: repository(owner: $owner1, name: $name1)"));
+    assert!(query.contains("rateLimit"));
+    assert_eq!(body["variables"]["name0"], "first");
+    assert_eq!(body["variables"]["name1"], "second");
+    assert_eq!(body["variables"]["after0"], serde_json::Value::Null);
+    assert_eq!(body["variables"]["after1"], "cursor");
+    assert_eq!(body["variables"]["labels0"], serde_json::json!(["bug"]));
+}
+
+#[test]
+fn test_batch_size_is_bounded() {
+    assert!(batch::batch_size() >= 1);
+    assert!(batch::batch_size() <= 20);
+}
+
+#[test]
+fn test_split_errors_by_alias() {
+    let error = |path: Option<Vec<graphql_client::PathFragment>>| graphql_client::Error {
+        message: "error".to_string(),
+        locations: None,
+        path,
+        extensions: None,
+    };
+    let errors =
+        vec![error(Some(vec![graphql_client::PathFragment::Key("r1".to_string())])), error(None)];
+
+    let (by_alias, unattributed) = batch::split_errors_by_alias(errors);
+
+    assert_eq!(by_alias.len(), 1);
+    assert_eq!(by_alias["r1"].len(), 1);
+    assert_eq!(unattributed.len(), 1);
+}
+
+fn rate_limit_info(cost: u32, remaining: u32) -> RateLimitInfo {
+    RateLimitInfo {
+        cost,
+        remaining,
+        reset_at: (Utc::now() + chrono::Duration::seconds(60)).to_rfc3339(),
+        node_count: 100,
+    }
+}
+
+#[tokio::test]
+async fn test_record_query_cost() {
+    let client = DefaultGithubClient::new("fake", "https://example/graphql", 5, 5).expect("client");
+
+    client.record_query_cost("IssuesBatch", 4, &rate_limit_info(10, 4000)).await;
+    client.record_query_cost("IssuesBatch", 4, &rate_limit_info(6, 3994)).await;
+
+    let costs = client.query_costs().await;
+    let cost = costs["IssuesBatch"];
+    assert_eq!(cost.requests, 2);
+    assert_eq!(cost.points, 16);
+    assert_eq!(cost.last_cost_per_unit, 2);
+    assert_eq!(cost.last_node_count, 100);
+
+    let state = client.rate_limit.lock().await;
+    assert_eq!(state.remaining, 3994);
+    let diff = state.reset_at.checked_duration_since(Instant::now()).unwrap();
+    assert!(diff >= Duration::from_secs(58) && diff <= Duration::from_secs(61));
+}
+
+#[tokio::test]
+async fn test_estimated_cost_scales_with_units() {
+    let client = DefaultGithubClient::new("fake", "https://example/graphql", 5, 5).expect("client");
+
+    // Unknown queries cost a point per unit
+    assert_eq!(client.estimated_cost("IssuesBatch", 3).await, 3);
+
+    client.record_query_cost("IssuesBatch", 2, &rate_limit_info(10, 4000)).await;
+    assert_eq!(client.estimated_cost("IssuesBatch", 3).await, 15);
+    assert_eq!(client.estimated_cost("Labels", 1).await, 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_guard_reserves_budget() {
+    let client = DefaultGithubClient::new("fake", "https://example/graphql", 5, 5).expect("client");
+    prime_state(&client, 100, 0).await;
+
+    client.rate_limit_guard(20).await;
+
+    assert_eq!(client.rate_limit.lock().await.remaining, 80);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rate_limit_guard_sleeps_when_cost_exceeds_budget() {
+    const WAIT_MS: u64 = 40;
+    let client = DefaultGithubClient::new("fake", "https://example/graphql", 5, 5).expect("client");
+
+    // Plenty of points for a cheap query, but not for an expensive batch
+    prime_state(&client, 20, WAIT_MS).await;
+
+    let elapsed = {
+        let start = Instant::now();
+        client.rate_limit_guard(30).await;
+        start.elapsed()
+    };
+
+    assert!(elapsed >= Duration::from_millis(WAIT_MS), "Guard returned too fast: {:?}", elapsed);
+}
+
+/// Serve a GraphQL endpoint that fails the first request with a transient
+/// error and answers the retry with `response`.
+async fn flaky_graphql_server(response: serde_json::Value) -> String {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let handler = move || {
+        let attempts = attempts.clone();
+        let response = response.clone();
+        async move {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                return axum::http::StatusCode::BAD_GATEWAY.into_response();
+            }
+            axum::Json(response).into_response()
+        }
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+    let router = axum::Router::new().route("/graphql", axum::routing::post(handler));
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    url
+}
+
+#[tokio::test]
+async fn test_retried_query_releases_its_unsettled_reservation() {
+    // Arrange
+    let url = flaky_graphql_server(serde_json::json!({
+        "data": { "r0": { "nameWithOwner": "owner/repo" } }
+    }))
+    .await;
+    let client = DefaultGithubClient::new("fake", &url, 5, 5).unwrap();
+    prime_state(&client, 100, 0).await;
+
+    // Act
+    let names = client
+        .repos_name_with_owner_batch(vec![("owner".to_string(), "repo".to_string())])
+        .await
+        .unwrap();
+
+    // Assert: reserved once despite the retry, and given back as GitHub never
+    // reported the remaining points
+    assert_eq!(names, vec![Some("owner/repo".to_string())]);
+    assert_eq!(client.rate_limit.lock().await.remaining, 100);
+}
+
+#[tokio::test]
+async fn test_retried_query_settles_reservation_with_reported_points() {
+    // Arrange
+    let url = flaky_graphql_server(serde_json::json!({
+        "data": {
+            "r0": { "nameWithOwner": "owner/repo" },
+            "rateLimit": {
+                "cost": 1,
+                "remaining": 4000,
+                "resetAt": (Utc::now() + chrono::Duration::seconds(60)).to_rfc3339(),
+                "nodeCount": 1
+            }
+        }
+    }))
+    .await;
+    let client = DefaultGithubClient::new("fake", &url, 5, 5).unwrap();
+    prime_state(&client, 100, 0).await;
+
+    // Act
+    client
+        .repos_name_with_owner_batch(vec![("owner".to_string(), "repo".to_string())])
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(client.rate_limit.lock().await.remaining, 4000);
+    assert_eq!(client.query_costs().await["RepoNamesBatch"].requests, 1);
+}
+
+fn repository_response(
+    repository: Option<&str>,
+    errors: Option<Vec<graphql_client::Error>>,
+) -> Response<repository::ResponseData> {
+    Response {
+        data: Some(repository::ResponseData {
+            repository: repository.map(|name_with_owner| repository::RepositoryRepository {
+                id: "R_1".to_string(),
+                name_with_owner: name_with_owner.to_string(),
+                url: format!("https://github.com/{name_with_owner}"),
+            }),
+            rate_limit: None,
+        }),
+        errors,
+        extensions: None,
+    }
+}
+
+fn graphql_error(path: Option<&str>) -> graphql_client::Error {
+    graphql_client::Error {
+        message: "Could not resolve to a Repository".to_string(),
+        locations: None,
+        path: path.map(|p| vec![graphql_client::PathFragment::Key(p.to_string())]),
+        extensions: None,
+    }
+}
+
+#[test]
+fn test_canonical_name_with_owner_found() {
+    let body = repository_response(Some("rust-lang/rust"), None);
+    assert_eq!(canonical_name_with_owner(body).unwrap().as_deref(), Some("rust-lang/rust"));
+}
+
+#[test]
+fn test_canonical_name_with_owner_not_found() {
+    let body = repository_response(None, Some(vec![graphql_error(Some("repository"))]));
+    assert_eq!(canonical_name_with_owner(body).unwrap(), None);
+}
+
+#[test]
+fn test_canonical_name_with_owner_other_error() {
+    let body = repository_response(None, Some(vec![graphql_error(None)]));
+    assert!(matches!(canonical_name_with_owner(body), Err(GithubError::GraphQLApiError(_))));
+}
+
+#[test]
+fn test_resolved_field_owner_not_found() {
+    // GitHub returns `null` without an error for an unknown owner
+    let body: Response<owner_repositories::ResponseData> = Response {
+        data: Some(owner_repositories::ResponseData { repository_owner: None, rate_limit: None }),
+        errors: None,
+        extensions: None,
+    };
+    let owner = resolved_field(body, "repositoryOwner", |data| data.repository_owner);
+    assert!(owner.unwrap().is_none());
+}
+
+// Helper to deserialize an issue with the given assignees and linked items
+fn issue_with_links(
+    assignees: i64,
+    linked_items: serde_json::Value,
+) -> issues::IssuesRepositoryIssuesNodes {
+    serde_json::from_value(serde_json::json!({
+        "id": "I_1",
+        "title": "Fix typo",
+        "url": "https://github.com/owner/repo/issues/1",
+        "createdAt": "2024-05-16T00:00:00Z",
+        "updatedAt": "2024-05-16T00:00:00Z",
+        "author": { "login": "octocat" },
+        "bodyText": "",
+        "comments": { "totalCount": 0 },
+        "reactions": { "totalCount": 0 },
+        "labels": { "nodes": [] },
+        "timelineItems": { "nodes": [] },
+        "assignees": { "totalCount": assignees },
+        "linkedItems": { "nodes": linked_items }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_issue_assignees_and_linked_prs() {
+    let unlinked = issue_with_links(
+        1,
+        serde_json::json!([
+            // A mention without a closing keyword does not count
+            {
+                "__typename": "CrossReferencedEvent",
+                "willCloseTarget": false,
+                "source": { "__typename": "PullRequest", "closed": false }
+            },
+            {
+                "__typename": "ConnectedEvent",
+                "subject": { "__typename": "PullRequest", "closed": true }
+            }
+        ]),
+    );
+    assert!(unlinked.is_assigned());
+    assert!(!unlinked.has_open_linked_pr());
+
+    let linked = issue_with_links(
+        0,
+        serde_json::json!([
+            {
+                "__typename": "CrossReferencedEvent",
+                "willCloseTarget": true,
+                "source": { "__typename": "PullRequest", "closed": false }
+            }
+        ]),
+    );
+    assert!(!linked.is_assigned());
+    assert!(linked.has_open_linked_pr());
+}
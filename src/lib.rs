@@ -23,17 +23,23 @@ pub mod poller;
 pub mod repository;
 /// The storage layer for persisting data.
 pub mod storage;
+/// The receiver for GitHub webhook events.
+pub mod webhook;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use teloxide::{
     dispatching::dialogue::{SqliteStorage, serializer},
     prelude::*,
 };
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
 use crate::{
-    bot_handler::BotHandler, config::Config, messaging::TelegramMessagingService,
-    poller::GithubPoller, repository::DefaultRepositoryService,
+    bot_handler::BotHandler,
+    config::Config,
+    messaging::TelegramMessagingService,
+    poller::{DigestScheduler, GithubPoller, OutboxWorker, PollerSupervisor},
+    repository::DefaultRepositoryService,
     storage::sqlite::SqliteStorage as ApplicationStorage,
 };
 
@@ -46,10 +52,22 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         &config.github_token,
         &config.github_graphql_url,
         config.rate_limit_threshold,
+        config.max_issue_pages,
     )?);
 
     let messaging_service = Arc::new(TelegramMessagingService::new(bot.clone()));
 
+    // Cancelled on SIGINT/SIGTERM, stops new work everywhere.
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown_signal().await;
+            tracing::info!("Shutdown requested");
+            shutdown.cancel();
+        }
+    });
+
     // Spawn a polling task for issues.
     let github_poller = GithubPoller::new(
         github_client.clone(),
@@ -59,11 +77,50 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         config.max_concurrency,
     );
 
-    tokio::spawn(async move {
-        if let Err(e) = github_poller.run().await {
-            tracing::error!("Error in poller: {e}");
-        }
-    });
+    // The background tasks that use the database, awaited on shutdown before
+    // it is closed. Webhook deliveries are handled on tasks of their own.
+    let mut tasks = Vec::new();
+    let webhook_tasks = TaskTracker::new();
+
+    // Serve GitHub webhooks next to polling, if a secret is configured.
+    if let Some(secret) = config.github_webhook_secret.clone() {
+        let listener = tokio::net::TcpListener::bind(&config.webhook_listen_addr).await?;
+        let state =
+            webhook::WebhookState::new(secret, github_poller.clone(), webhook_tasks.clone());
+        let shutdown = shutdown.clone();
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = webhook::serve(listener, state, shutdown).await {
+                tracing::error!("Error in webhook server: {e}");
+            }
+        }));
+    }
+
+    // Retry the notifications that could not be sent right away.
+    let outbox_worker = OutboxWorker::new(storage.clone(), messaging_service.clone());
+    tasks.push(tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { outbox_worker.run(shutdown).await }
+    }));
+
+    // Send the digests of the chats that collect their notifications.
+    let digest_scheduler = DigestScheduler::new(storage.clone(), messaging_service.clone());
+    tasks.push(tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { digest_scheduler.run(shutdown).await }
+    }));
+
+    // Restart the poller when it fails, and alert the admin chat if it gives up.
+    let poller_supervisor = PollerSupervisor::new(
+        github_poller,
+        messaging_service.clone(),
+        config.admin_chat_id.map(ChatId),
+        config.poller_max_restarts,
+    );
+    let poller_status = poller_supervisor.status();
+    tasks.push(tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { poller_supervisor.run(shutdown).await }
+    }));
 
     let dialogue_storage = SqliteStorage::open(&config.database_url, serializer::Json).await?;
     let repo_manager_service = Arc::new(DefaultRepositoryService::new(
@@ -72,12 +129,188 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         config.max_repos_per_user,
         config.max_labels_per_repo,
     ));
-    let handler =
-        Arc::new(BotHandler::new(messaging_service, repo_manager_service, config.max_concurrency));
-    let mut dispatcher = dispatcher::BotDispatcher::new(handler, dialogue_storage).build(bot);
-    tracing::debug!("Dispatcher built successfully.");
 
-    dispatcher.dispatch().await;
+    // Follow repositories that were renamed or transferred on GitHub.
+    tasks.push(tokio::spawn(repository::run_repo_name_sync(
+        repo_manager_service.clone(),
+        Duration::from_secs(config.repo_name_sync_interval),
+        shutdown.clone(),
+    )));
+
+    // Delete the subscriptions of chats that blocked the bot once they had time
+    // to come back.
+    tasks.push(tokio::spawn(repository::run_inactive_chat_purge(
+        repo_manager_service.clone(),
+        Duration::from_secs(config.inactive_chat_grace_period),
+        shutdown.clone(),
+    )));
+
+    // Keep the ledger of notified issues from growing forever.
+    tasks.push(tokio::spawn(repository::run_notified_issue_prune(
+        repo_manager_service.clone(),
+        shutdown.clone(),
+    )));
+
+    // On shutdown, let the poller and the other background tasks finish what
+    // they are sending, flush the database and only then stop the dispatcher.
+    let drain_timeout = Duration::from_secs(config.shutdown_timeout);
+    let stop_dispatcher = {
+        let shutdown = shutdown.clone();
+        let storage = storage.clone();
+        async move {
+            shutdown.cancelled().await;
+            drain_background_tasks(tasks, webhook_tasks, drain_timeout).await;
+            storage.close().await;
+        }
+    };
+    let mut handler =
+        BotHandler::new(messaging_service, repo_manager_service, config.max_concurrency);
+    // The admin chat can check on the poller with `/status`.
+    if let Some(admin_chat_id) = config.admin_chat_id {
+        handler = handler.with_admin(ChatId(admin_chat_id), poller_status);
+    }
+    let handler = Arc::new(handler);
+    let update_mode = dispatcher::UpdateMode::from_config(&config)?;
+    dispatcher::BotDispatcher::new(handler, dialogue_storage)
+        .run(bot, update_mode, stop_dispatcher)
+        .await?;
 
     Ok(())
 }
+
+/// Wait up to `timeout` for the background tasks to stop, then for the
+/// webhook deliveries still being handled. Returns `false` if they did not
+/// finish in time, in which case their in-flight work is abandoned.
+async fn drain_background_tasks(
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+    webhook_tasks: TaskTracker,
+    timeout: Duration,
+) -> bool {
+    let drain = async {
+        futures::future::join_all(tasks).await;
+        // The webhook server stopped taking deliveries, wait for the ones it is
+        // still handling
+        webhook_tasks.close();
+        webhook_tasks.wait().await;
+    };
+
+    if tokio::time::timeout(timeout, drain).await.is_err() {
+        tracing::warn!(
+            "Background tasks did not finish within {}s, abandoning in-flight work",
+            timeout.as_secs()
+        );
+        return false;
+    }
+    true
+}
+
+/// Completes when the process receives SIGINT (Ctrl-C) or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, mpsc};
+
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::*;
+    use crate::{
+        github::MockGithubClient, messaging::MockMessagingService, storage::MockRepoStorage,
+    };
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const OPENED_PAYLOAD: &str = include_str!("webhook/fixtures/issues_opened.json");
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_shutdown_waits_for_webhook_notifications() {
+        // Arrange
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let started_tx = Mutex::new(Some(started_tx));
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let mut storage = MockRepoStorage::new();
+        // The notification is still running when shutdown is requested, until the
+        // test releases it
+        storage.expect_get_repo_subscribers().times(1).returning(move |_| {
+            started_tx.lock().unwrap().take().unwrap().send(()).unwrap();
+            release_rx.lock().unwrap().recv().unwrap();
+            Ok(Vec::new())
+        });
+        storage.expect_get_owner_subscribers().returning(|_| Ok(Vec::new()));
+        let poller = GithubPoller::new(
+            Arc::new(MockGithubClient::new()),
+            Arc::new(storage),
+            Arc::new(MockMessagingService::new()),
+            10,
+            10,
+        );
+
+        let shutdown = CancellationToken::new();
+        let webhook_tasks = TaskTracker::new();
+        let state = webhook::WebhookState::new(SECRET.to_string(), poller, webhook_tasks.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), webhook::WEBHOOK_PATH);
+        let server = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { webhook::serve(listener, state, shutdown).await.unwrap() }
+        });
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(OPENED_PAYLOAD.as_bytes());
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        let response = reqwest::Client::new()
+            .post(url)
+            .header("X-GitHub-Event", "issues")
+            .header("X-Hub-Signature-256", signature)
+            .body(OPENED_PAYLOAD)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+        drop(response);
+        started_rx.await.unwrap();
+
+        // Act
+        shutdown.cancel();
+        let drain = tokio::spawn(drain_background_tasks(
+            vec![server],
+            webhook_tasks,
+            Duration::from_secs(10),
+        ));
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        // Assert
+        assert!(!drain.is_finished(), "shutdown must wait for the webhook notification");
+        release_tx.send(()).unwrap();
+        assert!(drain.await.unwrap());
+    }
+}
@@ -1,10 +1,19 @@
+use std::collections::BTreeSet;
+
 use lazy_static::lazy_static;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use url::Url;
 
-use super::utils;
+use super::{translations, utils};
 use crate::{
-    bot_handler::CallbackAction, pagination::Paginated, repository::LabelNormalized,
-    storage::RepoEntity,
+    bot_handler::{CallbackAction, commands::settings::SettingField},
+    github::issues::IssuesRepositoryIssuesNodes,
+    pagination::Paginated,
+    repository::LabelNormalized,
+    storage::{
+        IssueFilter, IssueFilters, Language, NotificationOption, PAGE_SIZES, RepoEntity,
+        SearchSubscription, UserSettings,
+    },
 };
 
 pub fn build_repo_list_keyboard(paginated_repos: &Paginated<RepoEntity>) -> InlineKeyboardMarkup {
@@ -44,12 +53,24 @@ pub fn build_repo_list_keyboard(paginated_repos: &Paginated<RepoEntity>) -> Inli
     InlineKeyboardMarkup::new(buttons)
 }
 
-pub fn build_repo_item_keyboard(repo: &RepoEntity, from_page: usize) -> InlineKeyboardMarkup {
+pub fn build_repo_item_keyboard(
+    repo: &RepoEntity,
+    filters: &IssueFilters,
+    from_page: usize,
+) -> InlineKeyboardMarkup {
     let id = &repo.name_with_owner;
     // actions
     let back_to_list = utils::serialize_action(&CallbackAction::BackToRepoList(from_page));
     let repo_labels = utils::serialize_action(&CallbackAction::ViewRepoLabels(id, 1, from_page));
     let remove_repo = utils::serialize_action(&CallbackAction::RemoveRepoPrompt(id));
+    let filter_button = |filter: IssueFilter, enabled: bool, text: &str| {
+        let toggle_filter =
+            utils::serialize_action(&CallbackAction::ToggleIssueFilter(id, filter, from_page));
+        InlineKeyboardButton::callback(
+            format!("{} {text}", if enabled { "✅" } else { "⬜️" }),
+            toggle_filter,
+        )
+    };
 
     // buttons
     let buttons = vec![
@@ -57,6 +78,13 @@ pub fn build_repo_item_keyboard(repo: &RepoEntity, from_page: usize) -> InlineKe
         vec![InlineKeyboardButton::callback("🔙 Repository list".to_string(), back_to_list)],
         // Manage repo labels button
         vec![InlineKeyboardButton::callback("⚙️ Labels".to_string(), repo_labels)],
+        // Issue filter toggles
+        vec![filter_button(IssueFilter::Assigned, filters.exclude_assigned, "Skip assigned")],
+        vec![filter_button(
+            IssueFilter::LinkedPr,
+            filters.exclude_linked_prs,
+            "Skip issues with an open PR",
+        )],
         // Remove repo action
         vec![InlineKeyboardButton::callback("❌ Remove".to_string(), remove_repo)],
     ];
@@ -132,6 +160,127 @@ pub fn build_repo_labels_keyboard(
     InlineKeyboardMarkup::new(buttons)
 }
 
+/// The number of issues and labels that get a button on a notification, to
+/// keep the keyboard from growing longer than the message.
+const MAX_NOTIFICATION_BUTTONS: usize = 5;
+
+/// The number of characters of an issue title shown on its "Open" button.
+const OPEN_BUTTON_TITLE_LEN: usize = 30;
+
+pub fn build_new_issues_keyboard(
+    id: &str, // repo name with owner
+    issues: &[&IssuesRepositoryIssuesNodes],
+    language: Language,
+) -> InlineKeyboardMarkup {
+    // Open buttons, named after the issue if there is more than one
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = issues
+        .iter()
+        .take(MAX_NOTIFICATION_BUTTONS)
+        .filter_map(|issue| {
+            let url = Url::parse(&issue.url).ok()?;
+            let text = if issues.len() == 1 {
+                translations::open_button(language).to_string()
+            } else {
+                format!("🔗 {}", utils::excerpt(&issue.title, OPEN_BUTTON_TITLE_LEN))
+            };
+            Some(vec![InlineKeyboardButton::url(text, url)])
+        })
+        .collect();
+
+    // Mute buttons for the labels the issues were notified for
+    let labels: BTreeSet<&str> = issues
+        .iter()
+        .filter_map(|issue| issue.labels.as_ref()?.nodes.as_ref())
+        .flatten()
+        .map(|label| label.name.as_str())
+        .collect();
+    buttons.extend(labels.into_iter().take(MAX_NOTIFICATION_BUTTONS).map(|label| {
+        let mute_label = utils::serialize_action(&CallbackAction::MuteLabel(id, label));
+        vec![InlineKeyboardButton::callback(
+            translations::mute_label_button(language, label),
+            mute_label,
+        )]
+    }));
+
+    let mute_repo = utils::serialize_action(&CallbackAction::MuteRepo(id));
+    let untrack_repo = utils::serialize_action(&CallbackAction::UntrackRepo(id));
+    buttons.push(vec![
+        InlineKeyboardButton::callback(translations::mute_repo_button(language), mute_repo),
+        InlineKeyboardButton::callback(translations::untrack_repo_button(language), untrack_repo),
+    ]);
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
+pub fn build_search_list_keyboard(searches: &[SearchSubscription]) -> InlineKeyboardMarkup {
+    let buttons = searches
+        .iter()
+        .map(|search| {
+            let remove_search = utils::serialize_action(&CallbackAction::RemoveSearch(search.id));
+            vec![InlineKeyboardButton::callback(format!("❌ {}", search.filter), remove_search)]
+        })
+        .collect::<Vec<_>>();
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
+pub fn build_settings_keyboard(settings: &UserSettings) -> InlineKeyboardMarkup {
+    let edit_button = |field: SettingField, text: &str| {
+        let edit_setting = utils::serialize_action(&CallbackAction::EditSetting(field));
+        vec![InlineKeyboardButton::callback(format!("✏️ {text}"), edit_setting)]
+    };
+    let option_button = |option: NotificationOption, enabled: bool, text: &str| {
+        let toggle_option =
+            utils::serialize_action(&CallbackAction::ToggleNotificationOption(option));
+        vec![InlineKeyboardButton::callback(
+            format!("{} {text}", if enabled { "✅" } else { "⬜️" }),
+            toggle_option,
+        )]
+    };
+    // Choices in one row, the current one checked
+    let choice_text =
+        |selected: bool, text: String| if selected { format!("✅ {text}") } else { text };
+
+    let mut buttons = vec![
+        edit_button(SettingField::Delivery, "Delivery"),
+        edit_button(SettingField::Timezone, "Timezone"),
+        edit_button(SettingField::QuietHours, "Quiet hours"),
+        option_button(
+            NotificationOption::Silent,
+            settings.silent_notifications,
+            "Silent notifications",
+        ),
+        option_button(NotificationOption::LinkPreviews, settings.link_previews, "Link previews"),
+    ];
+    buttons.push(
+        Language::ALL
+            .into_iter()
+            .map(|language| {
+                let set_language = utils::serialize_action(&CallbackAction::SetLanguage(language));
+                InlineKeyboardButton::callback(
+                    choice_text(language == settings.language, language.name().to_string()),
+                    set_language,
+                )
+            })
+            .collect(),
+    );
+    buttons.push(
+        PAGE_SIZES
+            .into_iter()
+            .map(|page_size| {
+                let set_page_size =
+                    utils::serialize_action(&CallbackAction::SetPageSize(page_size));
+                InlineKeyboardButton::callback(
+                    choice_text(page_size == settings.page_size, format!("📄 {page_size}")),
+                    set_page_size,
+                )
+            })
+            .collect(),
+    );
+
+    InlineKeyboardMarkup::new(buttons)
+}
+
 lazy_static! {
     pub static ref COMMAND_KEYBOARD: InlineKeyboardMarkup = InlineKeyboardMarkup::new(vec![
         vec![InlineKeyboardButton::callback(
@@ -158,7 +307,13 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
-    use crate::{pagination::Paginated, storage::RepoEntity};
+    use crate::{
+        github::issues::{
+            IssuesRepositoryIssuesNodesLabels, IssuesRepositoryIssuesNodesLabelsNodes,
+        },
+        pagination::Paginated,
+        storage::RepoEntity,
+    };
 
     #[test]
     fn test_build_repo_list_keyboard() {
@@ -180,12 +335,94 @@ mod tests {
     #[test]
     fn test_build_repo_item_keyboard() {
         let repo = RepoEntity::from_str("owner/repo").unwrap();
-        let keyboard = build_repo_item_keyboard(&repo, 1);
+        let filters = IssueFilters { exclude_assigned: true, exclude_linked_prs: false };
+        let keyboard = build_repo_item_keyboard(&repo, &filters, 1);
 
-        assert_eq!(keyboard.inline_keyboard.len(), 3);
+        assert_eq!(keyboard.inline_keyboard.len(), 5);
         assert_eq!(keyboard.inline_keyboard[0][0].text, "🔙 Repository list");
         assert_eq!(keyboard.inline_keyboard[1][0].text, "⚙️ Labels");
-        assert_eq!(keyboard.inline_keyboard[2][0].text, "❌ Remove");
+        assert_eq!(keyboard.inline_keyboard[2][0].text, "✅ Skip assigned");
+        assert_eq!(keyboard.inline_keyboard[3][0].text, "⬜️ Skip issues with an open PR");
+        assert_eq!(keyboard.inline_keyboard[4][0].text, "❌ Remove");
+    }
+
+    #[test]
+    fn test_build_new_issues_keyboard() {
+        let issue = |title: &str, labels: &[&str]| IssuesRepositoryIssuesNodes {
+            title: title.to_string(),
+            url: "https://github.com/owner/repo/issues/1".to_string(),
+            labels: Some(IssuesRepositoryIssuesNodesLabels {
+                nodes: Some(
+                    labels
+                        .iter()
+                        .map(|name| IssuesRepositoryIssuesNodesLabelsNodes {
+                            name: name.to_string(),
+                            color: "ffffff".to_string(),
+                        })
+                        .collect(),
+                ),
+            }),
+            ..Default::default()
+        };
+        let first = issue("First", &["bug", "good first issue"]);
+        let second = issue("Second", &["bug"]);
+
+        let keyboard = build_new_issues_keyboard("owner/repo", &[&first], Language::English);
+        let texts: Vec<Vec<&str>> = keyboard
+            .inline_keyboard
+            .iter()
+            .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                vec!["🔗 Open"],
+                vec!["🔇 Mute bug"],
+                vec!["🔇 Mute good first issue"],
+                vec!["🔕 Mute repo for 24h", "❌ Stop tracking"],
+            ]
+        );
+
+        // Every issue gets its own button, labels are only offered once
+        let keyboard =
+            build_new_issues_keyboard("owner/repo", &[&first, &second], Language::English);
+        assert_eq!(keyboard.inline_keyboard.len(), 5);
+        assert_eq!(keyboard.inline_keyboard[0][0].text, "🔗 First");
+        assert_eq!(keyboard.inline_keyboard[1][0].text, "🔗 Second");
+
+        // The buttons are in the chat's language
+        let keyboard = build_new_issues_keyboard("owner/repo", &[&second], Language::Spanish);
+        assert_eq!(keyboard.inline_keyboard[0][0].text, "🔗 Abrir");
+        assert_eq!(keyboard.inline_keyboard[1][0].text, "🔇 Silenciar bug");
+    }
+
+    #[test]
+    fn test_build_settings_keyboard() {
+        let settings = UserSettings {
+            link_previews: true,
+            language: Language::Spanish,
+            page_size: 20,
+            ..Default::default()
+        };
+
+        let keyboard = build_settings_keyboard(&settings);
+        let texts: Vec<Vec<&str>> = keyboard
+            .inline_keyboard
+            .iter()
+            .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                vec!["✏️ Delivery"],
+                vec!["✏️ Timezone"],
+                vec!["✏️ Quiet hours"],
+                vec!["⬜️ Silent notifications"],
+                vec!["✅ Link previews"],
+                vec!["English", "✅ Español"],
+                vec!["📄 5", "📄 10", "✅ 📄 20"],
+            ]
+        );
     }
 
     #[test]
@@ -1,30 +1,43 @@
 mod keyboards;
+mod rate_limiter;
 #[cfg(test)]
 mod tests;
+mod translations;
 mod utils;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use async_trait::async_trait;
 use keyboards::{
-    COMMAND_KEYBOARD, build_repo_item_keyboard, build_repo_labels_keyboard,
-    build_repo_list_keyboard,
+    COMMAND_KEYBOARD, build_new_issues_keyboard, build_repo_item_keyboard,
+    build_repo_labels_keyboard, build_repo_list_keyboard, build_search_list_keyboard,
+    build_settings_keyboard,
 };
 use mockall::automock;
 use teloxide::{
+    ApiError, RequestError,
     prelude::*,
+    requests::Output,
     sugar::request::RequestLinkPreviewExt,
-    types::{ChatId, ForceReply, InlineKeyboardMarkup, MessageId, ParseMode},
+    types::{ChatId, ForceReply, InlineKeyboardMarkup, MessageId, ParseMode, ReplyMarkup},
     utils::{command::BotCommands, html},
 };
 use thiserror::Error;
 
+use self::rate_limiter::SendRateLimiter;
 use crate::{
-    bot_handler::{BotHandlerError, Command, commands::add::AddSummary},
-    github::issues::IssuesRepositoryIssuesNodes,
+    bot_handler::{
+        BotHandlerError, Command,
+        commands::{add::AddSummary, search::SearchStep, settings::SettingField},
+    },
+    github::{SearchIssue, issues::IssuesRepositoryIssuesNodes},
     pagination::Paginated,
+    poller::PollerStatus,
     repository::LabelNormalized,
-    storage::RepoEntity,
+    storage::{
+        DeliveryMode, DigestEntry, IssueFilters, Language, OwnerSubscription, RepoEntity,
+        SearchFilter, SearchSubscription, UserSettings,
+    },
 };
 
 /// Represents errors that can occur when sending messages.
@@ -33,10 +46,63 @@ pub enum MessagingError {
     /// An error from the underlying `teloxide` library.
     #[error("Teloxide API request failed: {0}")]
     TeloxideRequest(#[from] teloxide::RequestError),
+    /// A message too long for one was split into several parts, and only the
+    /// first ones were delivered.
+    #[error("Teloxide API request failed after {sent_parts} parts were sent: {source}")]
+    PartiallySent {
+        /// The number of parts that were delivered.
+        sent_parts: usize,
+        /// The error sending the next part.
+        source: teloxide::RequestError,
+    },
+}
+
+impl MessagingError {
+    /// Whether messages can never be delivered to the chat again, e.g. because
+    /// the user blocked the bot or the chat was deleted.
+    pub fn is_permanent_delivery_failure(&self) -> bool {
+        let (Self::TeloxideRequest(error) | Self::PartiallySent { source: error, .. }) = self;
+        matches!(
+            error,
+            RequestError::Api(
+                ApiError::BotBlocked
+                    | ApiError::BotKicked
+                    | ApiError::BotKickedFromSupergroup
+                    | ApiError::BotKickedFromChannel
+                    | ApiError::ChatNotFound
+                    | ApiError::UserDeactivated
+                    | ApiError::GroupDeactivated
+                    | ApiError::CantInitiateConversation
+                    | ApiError::CantTalkWithBots
+            )
+        )
+    }
+
+    /// The number of parts of a split message that were delivered before the
+    /// error, so a retry can skip them.
+    pub fn sent_parts(&self) -> usize {
+        match self {
+            Self::TeloxideRequest(_) => 0,
+            Self::PartiallySent { sent_parts, .. } => *sent_parts,
+        }
+    }
+
+    // Record that the first `sent_parts` parts of a split message were
+    // delivered before the error.
+    fn after_sent_parts(self, sent_parts: usize) -> Self {
+        match self {
+            Self::TeloxideRequest(source) if sent_parts > 0 =>
+                Self::PartiallySent { sent_parts, source },
+            error => error,
+        }
+    }
 }
 
 type Result<T> = std::result::Result<T, MessagingError>;
 
+/// The number of characters of an issue's description shown in notifications.
+const ISSUE_EXCERPT_LEN: usize = 200;
+
 /// Trait for sending messages to the user.
 #[automock]
 #[async_trait]
@@ -81,15 +147,16 @@ pub trait MessagingService: Send + Sync {
     async fn answer_remove_callback_query(&self, query_id: &str, removed: bool) -> Result<()>;
 
     /// Sends a callback query with repository details.
-    /// This includes a link to the repository, button for managing labels and
-    /// remove button. The callback query is sent to the user when they
-    /// click on a repository in the list.
+    /// This includes a link to the repository, button for managing labels,
+    /// toggles for the issue filters and remove button. The callback query is
+    /// sent to the user when they click on a repository in the list.
     async fn answer_details_callback_query(
         &self,
         chat_id: ChatId,
         message_id: MessageId,
         repo: &RepoEntity,
         labels: &[LabelNormalized],
+        filters: &IssueFilters,
         from_page: usize,
     ) -> Result<()>;
 
@@ -132,17 +199,26 @@ pub trait MessagingService: Send + Sync {
         from_page: usize,
     ) -> Result<()>;
 
-    /// Sends a message to the user that there are new issues.
+    /// Sends a message to the user that there are new issues. Newly opened
+    /// issues and existing issues that just gained a tracked label are listed
+    /// in separate sections, with buttons to open the issues and to mute their
+    /// labels or the repository. Like all notifications, it is sent in the
+    /// user's language, silently and with a link preview if the user chose so.
+    /// The first `sent_parts` parts of a message too long for one, which an
+    /// earlier attempt delivered, are not sent again.
     async fn send_new_issues_msg(
         &self,
         chat_id: ChatId,
         repo_name_with_owner: &str,
-        issues: Vec<IssuesRepositoryIssuesNodes>,
+        new_issues: Vec<IssuesRepositoryIssuesNodes>,
+        labeled_issues: Vec<IssuesRepositoryIssuesNodes>,
+        settings: &UserSettings,
+        sent_parts: usize,
     ) -> Result<()>;
 
     /// Sends a summary message after adding repositories.
     /// This message includes the number of successfully added, already
-    /// tracked, not found, invalid URLs, and errors.
+    /// tracked, not found, invalid URLs, normalized inputs, and errors.
     async fn send_add_summary_msg(&self, chat_id: ChatId, summary: &AddSummary) -> Result<()>;
 
     /// Sends an overview message with tracked repositories and their labels.
@@ -164,17 +240,213 @@ pub trait MessagingService: Send + Sync {
         message_id: MessageId,
         summary: &AddSummary,
     ) -> Result<()>;
+
+    /// Sends a message to the admin chat that the poller stopped and will not
+    /// be restarted.
+    async fn send_poller_stopped_msg(&self, chat_id: ChatId, last_error: &str) -> Result<()>;
+
+    /// Sends the status of the poller to the admin chat.
+    async fn send_poller_status_msg(&self, chat_id: ChatId, status: &PollerStatus) -> Result<()>;
+
+    /// Sends the organizations and users tracked by the user, with their
+    /// labels.
+    async fn send_owner_list_msg(
+        &self,
+        chat_id: ChatId,
+        owners: Vec<OwnerSubscription>,
+    ) -> Result<()>;
+
+    /// Sends a confirmation that all repositories of an owner are tracked.
+    /// `added` is `false` if the owner was already tracked.
+    async fn send_owner_tracked_msg(
+        &self,
+        chat_id: ChatId,
+        owner: &OwnerSubscription,
+        added: bool,
+    ) -> Result<()>;
+
+    /// Sends a confirmation that an owner is no longer tracked. `removed` is
+    /// `false` if the owner was not tracked.
+    async fn send_owner_removed_msg(
+        &self,
+        chat_id: ChatId,
+        owner: &str,
+        removed: bool,
+    ) -> Result<()>;
+
+    /// Prompts the user for the next criterion of a search.
+    async fn prompt_for_search_input(&self, chat_id: ChatId, step: SearchStep) -> Result<()>;
+
+    /// Sends a confirmation that the user subscribed to a search. `added` is
+    /// `false` if the user already had the same search.
+    async fn send_search_added_msg(
+        &self,
+        chat_id: ChatId,
+        filter: &SearchFilter,
+        added: bool,
+    ) -> Result<()>;
+
+    /// Sends the user's searches, with a button to remove each.
+    async fn send_search_list_msg(
+        &self,
+        chat_id: ChatId,
+        searches: Vec<SearchSubscription>,
+    ) -> Result<()>;
+
+    /// Edits a message listing the user's searches.
+    async fn edit_search_list_msg(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        searches: Vec<SearchSubscription>,
+    ) -> Result<()>;
+
+    /// Sends new issues matching one of the user's searches, skipping the
+    /// first `sent_parts` parts like `send_new_issues_msg`.
+    async fn send_search_issues_msg(
+        &self,
+        chat_id: ChatId,
+        filter: &SearchFilter,
+        issues: Vec<SearchIssue>,
+        settings: &UserSettings,
+        sent_parts: usize,
+    ) -> Result<()>;
+
+    /// Sends how notifications are delivered to the user. `changed` is `true`
+    /// if the user just chose the delivery mode.
+    async fn send_delivery_mode_msg(
+        &self,
+        chat_id: ChatId,
+        settings: UserSettings,
+        changed: bool,
+    ) -> Result<()>;
+
+    /// Sends the user's preferences, with buttons to change them. `changed` is
+    /// `true` if the user just changed one of them.
+    async fn send_settings_msg(
+        &self,
+        chat_id: ChatId,
+        settings: UserSettings,
+        changed: bool,
+    ) -> Result<()>;
+
+    /// Edits a message showing the user's preferences after one of them was
+    /// changed from its buttons.
+    async fn edit_settings_msg(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        settings: UserSettings,
+    ) -> Result<()>;
+
+    /// Prompts the user for the new value of a setting.
+    async fn prompt_for_setting_input(&self, chat_id: ChatId, field: SettingField) -> Result<()>;
+
+    /// Sends a digest of the issues collected since the last one, grouped by
+    /// repository and label.
+    async fn send_digest_msg(
+        &self,
+        chat_id: ChatId,
+        settings: &UserSettings,
+        entries: Vec<DigestEntry>,
+    ) -> Result<()>;
 }
 
 /// The default implementation of the `MessagingService` trait.
 pub struct TelegramMessagingService {
     bot: Bot,
+    rate_limiter: SendRateLimiter,
 }
 
+/// How often a message is sent when Telegram keeps asking to retry later.
+const MAX_SEND_ATTEMPTS: usize = 3;
+
 impl TelegramMessagingService {
     /// Creates a new `TelegramMessagingService`.
     pub fn new(bot: Bot) -> Self {
-        Self { bot }
+        Self { bot, rate_limiter: SendRateLimiter::default() }
+    }
+
+    /// The number of outbound messages waiting for their turn under Telegram's
+    /// rate limits.
+    pub fn queue_depth(&self) -> usize {
+        self.rate_limiter.queue_depth()
+    }
+
+    // Helper to send a message or an edit within Telegram's rate limits. When
+    // Telegram still asks to retry later, every outbound message is paused for
+    // the requested time before the request is sent again.
+    async fn send_limited<R>(&self, chat_id: ChatId, request: R) -> Result<Output<R>>
+    where
+        R: Request<Err = RequestError> + Sync,
+    {
+        let mut attempt = 1;
+        loop {
+            self.rate_limiter.acquire(chat_id).await;
+            match request.send_ref().await {
+                Err(RequestError::RetryAfter(seconds)) if attempt < MAX_SEND_ATTEMPTS => {
+                    tracing::warn!(
+                        "Telegram asked to retry after {} seconds, {} messages queued",
+                        seconds.seconds(),
+                        self.rate_limiter.queue_depth()
+                    );
+                    self.rate_limiter.pause(seconds.duration());
+                    attempt += 1;
+                }
+                result => return result.map_err(MessagingError::TeloxideRequest),
+            }
+        }
+    }
+
+    // Helper to send an HTML message, split into several messages if it is too
+    // long for one. The reply markup goes with the last message, which is
+    // returned.
+    async fn send_html(
+        &self,
+        chat_id: ChatId,
+        text: String,
+        reply_markup: Option<ReplyMarkup>,
+    ) -> Result<Message> {
+        self.send_notification_html(chat_id, text, reply_markup, &UserSettings::default(), 0).await
+    }
+
+    // Helper to send an HTML message like `send_html`, silently and with a
+    // link preview if the user's settings ask for it. The first `sent_parts`
+    // parts were delivered by an earlier attempt and are skipped; when a part
+    // fails, the error tells how many were delivered.
+    async fn send_notification_html(
+        &self,
+        chat_id: ChatId,
+        text: String,
+        reply_markup: Option<ReplyMarkup>,
+        settings: &UserSettings,
+        sent_parts: usize,
+    ) -> Result<Message> {
+        let mut chunks = utils::split_html(&text, utils::MAX_MESSAGE_LEN);
+        let last = chunks.pop().unwrap_or_default();
+        let last_index = chunks.len();
+
+        // The last part is always sent, as it carries the reply markup
+        for (index, chunk) in chunks.into_iter().enumerate().skip(sent_parts) {
+            let request = self
+                .bot
+                .send_message(chat_id, chunk)
+                .parse_mode(ParseMode::Html)
+                .disable_notification(settings.silent_notifications)
+                .disable_link_preview(!settings.link_previews);
+            self.send_limited(chat_id, request).await.map_err(|e| e.after_sent_parts(index))?;
+        }
+
+        let mut request = self
+            .bot
+            .send_message(chat_id, last)
+            .parse_mode(ParseMode::Html)
+            .disable_notification(settings.silent_notifications)
+            .disable_link_preview(!settings.link_previews);
+        if let Some(reply_markup) = reply_markup {
+            request = request.reply_markup(reply_markup);
+        }
+        self.send_limited(chat_id, request).await.map_err(|e| e.after_sent_parts(last_index))
     }
 
     // Helper to format the summary text for adding repositories.
@@ -221,6 +493,16 @@ impl TelegramMessagingService {
             summary_parts.push(invalid_urls);
         }
 
+        if !summary.normalized.is_empty() {
+            let normalized = summary
+                .normalized
+                .iter()
+                .map(|(input, repo)| format!("- {} → {}", html::escape(input), html::escape(repo)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            summary_parts.push(format!("ℹ️ <b>Interpreted as:</b>\n{normalized}"));
+        }
+
         if !summary.errors.is_empty() {
             let error_messages = summary
                 .errors
@@ -240,6 +522,275 @@ impl TelegramMessagingService {
         summary_parts.join("\n\n")
     }
 
+    // Helper to format the tracked organizations and users.
+    fn format_owner_list_text(owners: &[OwnerSubscription]) -> String {
+        if owners.is_empty() {
+            return "No organizations or users tracked. Use /org &lt;name&gt; [labels separated \
+                    by commas] to track all of their repositories."
+                .to_string();
+        }
+
+        let mut message_parts = vec!["🏢 Your tracked organizations and users:".to_string()];
+        for owner in owners {
+            message_parts.push(String::new());
+            message_parts.push(Self::format_owner_text(owner));
+        }
+        message_parts.join("\n")
+    }
+
+    // Helper to format a single tracked organization or user with its labels.
+    fn format_owner_text(owner: &OwnerSubscription) -> String {
+        let owner_link =
+            html::link(&format!("https://github.com/{}", owner.owner), &html::escape(&owner.owner));
+        let mut labels: Vec<_> = owner.tracked_labels.iter().collect();
+        labels.sort();
+
+        let labels = if labels.is_empty() {
+            "⚠️ No labels are being tracked.".to_string()
+        } else {
+            format!(
+                "🏷️ <b>Tracked labels:</b>\n{}",
+                labels
+                    .iter()
+                    .map(|label| format!("- {}", html::escape(label)))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        };
+        format!("🏢 <b>All repositories of:</b> {owner_link}\n{labels}")
+    }
+
+    // Helper to format the user's searches.
+    fn format_search_list_text(searches: &[SearchSubscription]) -> String {
+        if searches.is_empty() {
+            "No searches yet. Use /search to get notified about new issues across GitHub."
+                .to_string()
+        } else {
+            "🔎 Your searches. Tap one to remove it:".to_string()
+        }
+    }
+
+    // Helper to format new issues matching a search.
+    fn format_search_issues_text(
+        filter: &SearchFilter,
+        issues: &[SearchIssue],
+        language: Language,
+    ) -> String {
+        format!(
+            "{}\n\n{}",
+            translations::search_issues_title(language, &html::escape(&filter.to_string())),
+            issues
+                .iter()
+                .map(|result| format!(
+                    "📦 {}\n{}",
+                    html::escape(&result.repo_name_with_owner),
+                    Self::format_issue_card(&result.issue)
+                ))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        )
+    }
+
+    // Helper to describe how notifications are delivered.
+    fn format_delivery_mode(settings: &UserSettings) -> String {
+        let timezone = settings.timezone.name();
+        match settings.delivery_mode {
+            DeliveryMode::Instant =>
+                "you are notified about new issues as soon as they are found".to_string(),
+            DeliveryMode::Hourly =>
+                "new issues are collected into a digest sent every hour".to_string(),
+            DeliveryMode::Daily(time) => format!(
+                "new issues are collected into a digest sent every day at {} ({timezone})",
+                time.format("%H:%M")
+            ),
+            DeliveryMode::Weekly(time) => format!(
+                "new issues are collected into a digest sent every Monday at {} ({timezone})",
+                time.format("%H:%M")
+            ),
+        }
+    }
+
+    // Helper to format how notifications are delivered.
+    fn format_delivery_mode_text(settings: &UserSettings, changed: bool) -> String {
+        let description = Self::format_delivery_mode(settings);
+        if changed {
+            format!("✅ Done, {description}.")
+        } else {
+            format!(
+                "📬 Currently {description}.\n\nChange it with /delivery instant, hourly, daily \
+                 [HH:MM] or weekly [HH:MM]."
+            )
+        }
+    }
+
+    // Helper to format the user's preferences.
+    fn format_settings_text(settings: &UserSettings, changed: bool) -> String {
+        let title = if changed { "✅ Settings updated:" } else { "⚙️ Your settings:" };
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        let quiet_hours = settings
+            .quiet_hours
+            .map_or_else(|| "off".to_string(), |quiet_hours| quiet_hours.to_string());
+
+        format!(
+            "{title}\n\n📬 Delivery: {}\n🌍 Timezone: {}\n🌙 Quiet hours: {quiet_hours}\n🔕 \
+             Silent notifications: {}\n🔗 Link previews: {}\n🗣️ Notification language: {}\n📄 \
+             Page size: {}\n\nTap a button below to change a setting.",
+            Self::format_delivery_mode(settings),
+            settings.timezone.name(),
+            on_off(settings.silent_notifications),
+            on_off(settings.link_previews),
+            settings.language,
+            settings.page_size
+        )
+    }
+
+    // Helper to format a digest. The issues are grouped by repository, and
+    // within a repository by their first label.
+    fn format_digest_text(settings: &UserSettings, entries: &[DigestEntry]) -> String {
+        let mut issues_by_repo: BTreeMap<&str, BTreeMap<_, Vec<&IssuesRepositoryIssuesNodes>>> =
+            BTreeMap::new();
+        for entry in entries {
+            let label = entry
+                .issue
+                .labels
+                .iter()
+                .filter_map(|connection| connection.nodes.as_ref())
+                .flatten()
+                .min_by(|a, b| a.name.cmp(&b.name))
+                .map(|label| (label.name.as_str(), label.color.as_str()));
+            issues_by_repo
+                .entry(&entry.repo_name_with_owner)
+                .or_default()
+                // Issues without a label go last
+                .entry((label.is_none(), label))
+                .or_default()
+                .push(&entry.issue);
+        }
+
+        let language = settings.language;
+        let mut sections =
+            vec![translations::digest_title(language, settings.delivery_mode, entries.len())];
+
+        for (repo, issues_by_label) in issues_by_repo {
+            let repo_link = html::link(&format!("https://github.com/{repo}"), &html::escape(repo));
+            let mut lines = vec![format!("📦 <b>{repo_link}</b>")];
+            for ((_, label), issues) in issues_by_label {
+                lines.push(match label {
+                    Some((name, color)) => format!(
+                        "{} <i>{}</i>",
+                        utils::github_color_to_emoji(color),
+                        html::escape(name)
+                    ),
+                    None => format!("🏷️ <i>{}</i>", translations::unlabeled_group(language)),
+                });
+                lines.extend(issues.into_iter().map(|issue| {
+                    format!(
+                        "• {} · 💬 {}",
+                        html::link(&issue.url, &issue.title),
+                        issue.comments.total_count
+                    )
+                }));
+            }
+            sections.push(lines.join("\n"));
+        }
+
+        sections.join("\n\n")
+    }
+
+    // Helper to format the alert sent when the poller gives up.
+    fn format_poller_stopped_text(last_error: &str) -> String {
+        format!(
+            "🛑 The GitHub poller stopped and will not be restarted. No notifications are sent \
+             until the bot is restarted.\n\nLast error: {}",
+            html::escape(last_error)
+        )
+    }
+
+    // Helper to format the status of the poller.
+    fn format_poller_status_text(status: &PollerStatus) -> String {
+        match status {
+            PollerStatus::Running => "✅ The GitHub poller is running.".to_string(),
+            PollerStatus::Degraded { restarts, last_error } => format!(
+                "⚠️ The GitHub poller failed and is being restarted (restart {restarts}).\n\nLast \
+                 error: {}",
+                html::escape(last_error)
+            ),
+            PollerStatus::Stopped { last_error: None } =>
+                "🛑 The GitHub poller is stopped.".to_string(),
+            PollerStatus::Stopped { last_error: Some(last_error) } =>
+                Self::format_poller_stopped_text(last_error),
+        }
+    }
+
+    // Helper to format a compact card for an issue: the linked title, who
+    // opened it, how much activity it has, its labels and the start of its
+    // description.
+    fn format_issue_card(issue: &IssuesRepositoryIssuesNodes) -> String {
+        // `html::link` escapes the title itself
+        let mut lines = vec![format!("<b>{}</b>", html::link(&issue.url, &issue.title))];
+
+        let author = issue.author.as_ref().map_or("ghost", |author| author.login.as_str());
+        lines.push(format!(
+            "👤 {} · 💬 {} · ❤️ {}",
+            html::escape(author),
+            issue.comments.total_count,
+            issue.reactions.total_count
+        ));
+
+        let labels = issue
+            .labels
+            .iter()
+            .filter_map(|connection| connection.nodes.as_ref())
+            .flatten()
+            .map(|label| {
+                format!(
+                    "{} {}",
+                    utils::github_color_to_emoji(&label.color),
+                    html::escape(&label.name)
+                )
+            })
+            .collect::<Vec<_>>();
+        if !labels.is_empty() {
+            lines.push(labels.join(" · "));
+        }
+
+        let excerpt = utils::excerpt(&issue.body_text, ISSUE_EXCERPT_LEN);
+        if !excerpt.is_empty() {
+            lines.push(format!("<i>{}</i>", html::escape(&excerpt)));
+        }
+
+        lines.join("\n")
+    }
+
+    // Helper to format the new issues notification text.
+    fn format_new_issues_text(
+        repo_name_with_owner: &str,
+        new_issues: &[IssuesRepositoryIssuesNodes],
+        labeled_issues: &[IssuesRepositoryIssuesNodes],
+        language: Language,
+    ) -> String {
+        let format_section = |title: String, issues: &[IssuesRepositoryIssuesNodes]| {
+            if issues.is_empty() {
+                return None;
+            }
+            Some(format!(
+                "{}\n\n{}",
+                title,
+                issues.iter().map(Self::format_issue_card).collect::<Vec<_>>().join("\n\n")
+            ))
+        };
+        let repo = html::escape(repo_name_with_owner);
+
+        [
+            format_section(translations::new_issues_title(language, &repo), new_issues),
+            format_section(translations::labeled_issues_title(language, &repo), labeled_issues),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n\n")
+    }
+
     // Helper to format text for paginated messages
     fn format_paginated_message_text(
         title: &str,
@@ -271,24 +822,15 @@ impl MessagingService for TelegramMessagingService {
         // If no keyboard is provided, use the default command keyboard.
         let keyboard = keyboard.unwrap_or(COMMAND_KEYBOARD.clone());
 
-        self.bot
-            .send_message(chat_id, text)
-            .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
-            .disable_link_preview(true)
-            .await
-            .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
+        self.send_html(chat_id, text, Some(keyboard.into())).await.map(|_| ())
     }
 
     async fn prompt_for_repo_input(&self, chat_id: ChatId) -> Result<()> {
-        let prompt = "Please reply with repository URLs separated by spaces or new lines.";
-        self.bot
-            .send_message(chat_id, prompt)
-            .reply_markup(ForceReply::new())
+        let prompt = "Please reply with repository URLs or owner/repo names separated by spaces \
+                      or new lines.";
+        self.send_html(chat_id, prompt.to_string(), Some(ForceReply::new().into()))
             .await
             .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
     }
 
     async fn send_error_msg(&self, chat_id: ChatId, error: BotHandlerError) -> Result<()> {
@@ -299,7 +841,7 @@ impl MessagingService for TelegramMessagingService {
         let help_text = Command::descriptions();
         self.send_response_with_keyboard(
             chat_id,
-            help_text.to_string(),
+            html::escape(&help_text.to_string()),
             Some(COMMAND_KEYBOARD.clone()),
         )
         .await
@@ -365,10 +907,11 @@ impl MessagingService for TelegramMessagingService {
         message_id: MessageId,
         repo: &RepoEntity,
         labels: &[LabelNormalized],
+        filters: &IssueFilters,
         from_page: usize,
     ) -> Result<()> {
         let repo_link = html::link(&repo.url(), &html::escape(&repo.name_with_owner));
-        let keyboard = build_repo_item_keyboard(repo, from_page);
+        let keyboard = build_repo_item_keyboard(repo, filters, from_page);
 
         let mut message_parts = vec![
             format!("📦 Repository: {}", repo_link),
@@ -392,13 +935,12 @@ impl MessagingService for TelegramMessagingService {
 
         let text = message_parts.join("\n");
 
-        self.bot
+        let request = self
+            .bot
             .edit_message_text(chat_id, message_id, text)
             .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
-            .await
-            .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
+            .reply_markup(keyboard);
+        self.send_limited(chat_id, request).await.map(|_| ())
     }
 
     async fn answer_toggle_label_callback_query(
@@ -434,13 +976,12 @@ impl MessagingService for TelegramMessagingService {
         let title = format!("🏷️ Manage labels for {}:", html::escape(repo_name_with_owner));
         let text_to_send = Self::format_paginated_message_text(&title, paginated_labels, "labels");
 
-        self.bot
+        let request = self
+            .bot
             .edit_message_text(chat_id, message_id, text_to_send)
             .parse_mode(ParseMode::Html)
-            .reply_markup(keyboard)
-            .await
-            .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
+            .reply_markup(keyboard);
+        self.send_limited(chat_id, request).await.map(|_| ())
     }
 
     async fn edit_list_msg(
@@ -456,13 +997,12 @@ impl MessagingService for TelegramMessagingService {
             "repositories",
         );
 
-        self.bot
+        let request = self
+            .bot
             .edit_message_text(chat_id, message_id, text)
             .parse_mode(ParseMode::Html)
-            .reply_markup(new_keyboard)
-            .await
-            .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
+            .reply_markup(new_keyboard);
+        self.send_limited(chat_id, request).await.map(|_| ())
     }
 
     async fn edit_labels_msg(
@@ -481,35 +1021,31 @@ impl MessagingService for TelegramMessagingService {
             "🏷️ Manage repository labels:"
         };
 
-        self.bot
-            .edit_message_text(chat_id, message_id, text)
-            .reply_markup(keyboard)
-            .await
-            .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
+        let request = self.bot.edit_message_text(chat_id, message_id, text).reply_markup(keyboard);
+        self.send_limited(chat_id, request).await.map(|_| ())
     }
 
     async fn send_new_issues_msg(
         &self,
         chat_id: ChatId,
         repo_name_with_owner: &str,
-        issues: Vec<IssuesRepositoryIssuesNodes>,
+        new_issues: Vec<IssuesRepositoryIssuesNodes>,
+        labeled_issues: Vec<IssuesRepositoryIssuesNodes>,
+        settings: &UserSettings,
+        sent_parts: usize,
     ) -> Result<()> {
-        let message = format!(
-            "🚨 New issues in {}:\n\n{}",
+        let message = Self::format_new_issues_text(
             repo_name_with_owner,
-            issues
-                .iter()
-                .map(|issue| format!("- {}: {}", issue.title, issue.url))
-                .collect::<Vec<_>>()
-                .join("\n")
+            &new_issues,
+            &labeled_issues,
+            settings.language,
         );
+        let issues: Vec<_> = new_issues.iter().chain(labeled_issues.iter()).collect();
+        let keyboard = build_new_issues_keyboard(repo_name_with_owner, &issues, settings.language);
 
-        self.bot
-            .send_message(chat_id, message)
+        self.send_notification_html(chat_id, message, Some(keyboard.into()), settings, sent_parts)
             .await
             .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
     }
 
     async fn send_add_summary_msg(&self, chat_id: ChatId, summary: &AddSummary) -> Result<()> {
@@ -557,7 +1093,7 @@ impl MessagingService for TelegramMessagingService {
     }
 
     async fn send_text_message(&self, chat_id: ChatId, text: &str) -> Result<Message> {
-        self.bot.send_message(chat_id, text).await.map_err(MessagingError::TeloxideRequest)
+        self.send_html(chat_id, html::escape(text), None).await
     }
 
     async fn edit_add_summary_msg(
@@ -567,11 +1103,203 @@ impl MessagingService for TelegramMessagingService {
         summary: &AddSummary,
     ) -> Result<()> {
         let text = Self::format_add_summary_text(summary);
-        self.bot
+
+        // A message can only be edited into a single one, the rest of a long
+        // summary follows in new messages
+        let mut chunks = utils::split_html(&text, utils::MAX_MESSAGE_LEN).into_iter();
+        let request = self
+            .bot
+            .edit_message_text(chat_id, message_id, chunks.next().unwrap_or_default())
+            .parse_mode(ParseMode::Html);
+        self.send_limited(chat_id, request).await?;
+        for chunk in chunks {
+            self.send_html(chat_id, chunk, None).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_poller_stopped_msg(&self, chat_id: ChatId, last_error: &str) -> Result<()> {
+        let message = Self::format_poller_stopped_text(last_error);
+        self.send_html(chat_id, message, None).await.map(|_| ())
+    }
+
+    async fn send_poller_status_msg(&self, chat_id: ChatId, status: &PollerStatus) -> Result<()> {
+        let message = Self::format_poller_status_text(status);
+        self.send_html(chat_id, message, None).await.map(|_| ())
+    }
+
+    async fn send_owner_list_msg(
+        &self,
+        chat_id: ChatId,
+        owners: Vec<OwnerSubscription>,
+    ) -> Result<()> {
+        let text = Self::format_owner_list_text(&owners);
+        self.send_response_with_keyboard(chat_id, text, None).await
+    }
+
+    async fn send_owner_tracked_msg(
+        &self,
+        chat_id: ChatId,
+        owner: &OwnerSubscription,
+        added: bool,
+    ) -> Result<()> {
+        let title = if added { "✅ Now tracking" } else { "➡️ Already tracking" };
+        let text = format!(
+            "{title} every repository of {}, except archived ones and forks.\n\n{}",
+            html::escape(&owner.owner),
+            Self::format_owner_text(owner)
+        );
+        self.send_response_with_keyboard(chat_id, text, None).await
+    }
+
+    async fn send_owner_removed_msg(
+        &self,
+        chat_id: ChatId,
+        owner: &str,
+        removed: bool,
+    ) -> Result<()> {
+        let text = if removed {
+            format!("✅ Stopped tracking the repositories of {}.", html::escape(owner))
+        } else {
+            format!("❓ {} is not tracked.", html::escape(owner))
+        };
+        self.send_response_with_keyboard(chat_id, text, None).await
+    }
+
+    async fn prompt_for_search_input(&self, chat_id: ChatId, step: SearchStep) -> Result<()> {
+        let prompt = match step {
+            SearchStep::Label =>
+                "Which label should the issues have? Reply with a label, or - for \"good first \
+                 issue\".",
+            SearchStep::Language =>
+                "Which language should the repositories use? Reply with a language, e.g. rust, or \
+                 - for any language.",
+            SearchStep::MinStars =>
+                "How many stars should the repositories have at least? Reply with a number, or - \
+                 for any number.",
+            SearchStep::Topic =>
+                "Which topic should the repositories have? Reply with a topic, e.g. cli, or - for \
+                 any topic.",
+        };
+        self.send_html(chat_id, prompt.to_string(), Some(ForceReply::new().into()))
+            .await
+            .map(|_| ())
+    }
+
+    async fn send_search_added_msg(
+        &self,
+        chat_id: ChatId,
+        filter: &SearchFilter,
+        added: bool,
+    ) -> Result<()> {
+        let text = if added {
+            format!(
+                "✅ You will be notified about new issues with {}. Use /searches to manage your \
+                 searches.",
+                html::escape(&filter.to_string())
+            )
+        } else {
+            format!("➡️ You already have a search for {}.", html::escape(&filter.to_string()))
+        };
+        self.send_response_with_keyboard(chat_id, text, None).await
+    }
+
+    async fn send_search_list_msg(
+        &self,
+        chat_id: ChatId,
+        searches: Vec<SearchSubscription>,
+    ) -> Result<()> {
+        let text = Self::format_search_list_text(&searches);
+        let keyboard = (!searches.is_empty()).then(|| build_search_list_keyboard(&searches));
+        self.send_response_with_keyboard(chat_id, text, keyboard).await
+    }
+
+    async fn edit_search_list_msg(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        searches: Vec<SearchSubscription>,
+    ) -> Result<()> {
+        let text = Self::format_search_list_text(&searches);
+
+        let request = self
+            .bot
             .edit_message_text(chat_id, message_id, text)
             .parse_mode(ParseMode::Html)
+            .reply_markup(build_search_list_keyboard(&searches));
+        self.send_limited(chat_id, request).await.map(|_| ())
+    }
+
+    async fn send_search_issues_msg(
+        &self,
+        chat_id: ChatId,
+        filter: &SearchFilter,
+        issues: Vec<SearchIssue>,
+        settings: &UserSettings,
+        sent_parts: usize,
+    ) -> Result<()> {
+        let message = Self::format_search_issues_text(filter, &issues, settings.language);
+        self.send_notification_html(chat_id, message, None, settings, sent_parts).await.map(|_| ())
+    }
+
+    async fn send_delivery_mode_msg(
+        &self,
+        chat_id: ChatId,
+        settings: UserSettings,
+        changed: bool,
+    ) -> Result<()> {
+        let text = Self::format_delivery_mode_text(&settings, changed);
+        self.send_response_with_keyboard(chat_id, text, None).await
+    }
+
+    async fn send_settings_msg(
+        &self,
+        chat_id: ChatId,
+        settings: UserSettings,
+        changed: bool,
+    ) -> Result<()> {
+        let text = Self::format_settings_text(&settings, changed);
+        let keyboard = build_settings_keyboard(&settings);
+        self.send_response_with_keyboard(chat_id, text, Some(keyboard)).await
+    }
+
+    async fn edit_settings_msg(
+        &self,
+        chat_id: ChatId,
+        message_id: MessageId,
+        settings: UserSettings,
+    ) -> Result<()> {
+        let request = self
+            .bot
+            .edit_message_text(chat_id, message_id, Self::format_settings_text(&settings, true))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(build_settings_keyboard(&settings));
+        self.send_limited(chat_id, request).await.map(|_| ())
+    }
+
+    async fn prompt_for_setting_input(&self, chat_id: ChatId, field: SettingField) -> Result<()> {
+        let prompt = match field {
+            SettingField::Delivery =>
+                "How do you want to be notified? Reply with instant, hourly, daily [HH:MM] or \
+                 weekly [HH:MM]. Times are in your timezone.",
+            SettingField::Timezone =>
+                "Which timezone are you in? Reply with its name, e.g. Europe/Berlin.",
+            SettingField::QuietHours =>
+                "When do you not want to be notified? Reply with HH:MM-HH:MM, e.g. 22:00-07:00, or \
+                 off.",
+        };
+        self.send_html(chat_id, prompt.to_string(), Some(ForceReply::new().into()))
             .await
             .map(|_| ())
-            .map_err(MessagingError::TeloxideRequest)
+    }
+
+    async fn send_digest_msg(
+        &self,
+        chat_id: ChatId,
+        settings: &UserSettings,
+        entries: Vec<DigestEntry>,
+    ) -> Result<()> {
+        let message = Self::format_digest_text(settings, &entries);
+        self.send_notification_html(chat_id, message, None, settings, 0).await.map(|_| ())
     }
 }
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use teloxide::types::ChatId;
+use tokio::time::{Instant, sleep_until};
+
+/// The time between two messages of the bot, to stay below Telegram's limit of
+/// about 30 messages per second.
+pub const GLOBAL_SEND_INTERVAL: Duration = Duration::from_millis(34);
+
+/// The time between two messages to the same chat, to stay below Telegram's
+/// limit of about one message per second.
+pub const CHAT_SEND_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Spaces out outbound messages, so that neither the bot as a whole nor any
+/// single chat exceeds Telegram's limits. Senders wait for their turn in
+/// `acquire`; when Telegram asks to slow down anyway, `pause` holds back every
+/// sender.
+pub struct SendRateLimiter {
+    global_interval: Duration,
+    chat_interval: Duration,
+    next_global: Mutex<Instant>,
+    next_by_chat: Mutex<HashMap<ChatId, Instant>>,
+    queued: AtomicUsize,
+}
+
+impl SendRateLimiter {
+    /// Creates a new `SendRateLimiter` with the given time between any two
+    /// messages and between two messages to the same chat.
+    pub fn new(global_interval: Duration, chat_interval: Duration) -> Self {
+        Self {
+            global_interval,
+            chat_interval,
+            next_global: Mutex::new(Instant::now()),
+            next_by_chat: Mutex::new(HashMap::new()),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Waits until a message may be sent to the chat.
+    pub async fn acquire(&self, chat_id: ChatId) {
+        // Leaves the queue when done waiting, or when the wait is cancelled
+        let _queued = Queued::enter(&self.queued);
+
+        // Wait for the chat's turn first, so a busy chat does not hold up the
+        // global schedule for everyone else.
+        let chat_slot = self.reserve_chat_slot(chat_id);
+        sleep_until(chat_slot).await;
+
+        let global_slot = {
+            let mut next_global = self.next_global.lock().expect("rate limiter lock poisoned");
+            let slot = (*next_global).max(Instant::now());
+            *next_global = slot + self.global_interval;
+            slot
+        };
+        sleep_until(global_slot).await;
+
+        // Waiting for the global slot may have delayed the message, so keep the
+        // next one to the chat at a distance from when this one actually goes out.
+        {
+            let mut next_by_chat = self.next_by_chat.lock().expect("rate limiter lock poisoned");
+            let next = next_by_chat.entry(chat_id).or_insert(global_slot);
+            *next = (*next).max(global_slot + self.chat_interval);
+        }
+    }
+
+    /// Holds back every sender for `duration`, e.g. after Telegram answered
+    /// with `RetryAfter`.
+    pub fn pause(&self, duration: Duration) {
+        let resume_at = Instant::now() + duration;
+        let mut next_global = self.next_global.lock().expect("rate limiter lock poisoned");
+        *next_global = (*next_global).max(resume_at);
+    }
+
+    /// The number of messages waiting for their turn.
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    // Reserves the chat's next slot and returns it. Chats without pending
+    // messages are forgotten, to keep the map small.
+    fn reserve_chat_slot(&self, chat_id: ChatId) -> Instant {
+        let now = Instant::now();
+        let mut next_by_chat = self.next_by_chat.lock().expect("rate limiter lock poisoned");
+        next_by_chat.retain(|_, next| *next > now);
+
+        let slot = next_by_chat.get(&chat_id).map_or(now, |&next| next.max(now));
+        next_by_chat.insert(chat_id, slot + self.chat_interval);
+        slot
+    }
+}
+
+/// A message waiting for its turn, counted in the queue depth until dropped.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl<'a> Queued<'a> {
+    fn enter(queued: &'a AtomicUsize) -> Self {
+        let depth = queued.fetch_add(1, Ordering::Relaxed) + 1;
+        if depth > 1 {
+            tracing::debug!("{depth} messages queued for sending");
+        }
+        Self(queued)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for SendRateLimiter {
+    fn default() -> Self {
+        Self::new(GLOBAL_SEND_INTERVAL, CHAT_SEND_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    const GLOBAL: Duration = Duration::from_millis(10);
+    const CHAT: Duration = Duration::from_millis(100);
+
+    #[tokio::test]
+    async fn test_acquire_spaces_out_messages_to_a_chat() {
+        let limiter = SendRateLimiter::new(GLOBAL, CHAT);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire(ChatId(1)).await;
+        }
+
+        assert!(start.elapsed() >= CHAT * 2);
+        assert_eq!(limiter.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_does_not_hold_up_other_chats() {
+        let limiter = Arc::new(SendRateLimiter::new(GLOBAL, CHAT));
+        for _ in 0..3 {
+            limiter.acquire(ChatId(1)).await;
+        }
+
+        // Another chat only waits for the global interval
+        let start = Instant::now();
+        limiter.acquire(ChatId(2)).await;
+
+        assert!(start.elapsed() < CHAT);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_spaces_out_messages_globally() {
+        let limiter = Arc::new(SendRateLimiter::new(GLOBAL, CHAT));
+        let start = Instant::now();
+
+        let sends = (0..5).map(|chat| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire(ChatId(chat)).await })
+        });
+        for send in sends.collect::<Vec<_>>() {
+            send.await.unwrap();
+        }
+
+        assert!(start.elapsed() >= GLOBAL * 4);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_acquire_leaves_the_queue() {
+        let limiter = SendRateLimiter::new(GLOBAL, CHAT);
+        limiter.pause(CHAT * 10);
+
+        let acquired = tokio::time::timeout(CHAT, limiter.acquire(ChatId(1))).await;
+
+        assert!(acquired.is_err());
+        assert_eq!(limiter.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pause_holds_back_every_chat() {
+        let limiter = SendRateLimiter::new(GLOBAL, CHAT);
+        let start = Instant::now();
+
+        limiter.pause(CHAT);
+        limiter.acquire(ChatId(1)).await;
+
+        assert!(start.elapsed() >= CHAT);
+    }
+}
@@ -1,5 +1,33 @@
-use super::TelegramMessagingService;
-use crate::pagination::Paginated;
+use std::collections::HashSet;
+
+use chrono::NaiveTime;
+use teloxide::{ApiError, RequestError, types::Seconds};
+
+use super::{MessagingError, TelegramMessagingService};
+use crate::{
+    github::{
+        SearchIssue,
+        issues::{
+            IssuesRepositoryIssuesNodes, IssuesRepositoryIssuesNodesAuthor,
+            IssuesRepositoryIssuesNodesComments, IssuesRepositoryIssuesNodesLabels,
+            IssuesRepositoryIssuesNodesLabelsNodes, IssuesRepositoryIssuesNodesReactions,
+        },
+    },
+    pagination::Paginated,
+    poller::PollerStatus,
+    storage::{
+        DeliveryMode, DigestEntry, Language, OwnerSubscription, SearchFilter, UserSettings,
+        parse_timezone,
+    },
+};
+
+fn issue(title: &str, url: &str) -> IssuesRepositoryIssuesNodes {
+    IssuesRepositoryIssuesNodes {
+        title: title.to_string(),
+        url: url.to_string(),
+        ..Default::default()
+    }
+}
 
 #[test]
 fn test_format_paginated_message_text() {
@@ -23,3 +51,268 @@ fn test_format_paginated_message_text_no_items() {
 
     assert_eq!(text, "Test Title\n\nNo items found.");
 }
+
+#[test]
+fn test_format_new_issues_text() {
+    let new_issues = vec![issue("New issue", "https://github.com/owner/repo/issues/2")];
+    let labeled_issues = vec![issue("Old issue", "https://github.com/owner/repo/issues/1")];
+
+    let text = TelegramMessagingService::format_new_issues_text(
+        "owner/repo",
+        &new_issues,
+        &labeled_issues,
+        Language::English,
+    );
+
+    assert_eq!(
+        text,
+        "🚨 New issues in owner/repo:\n\n<b><a \
+         href=\"https://github.com/owner/repo/issues/2\">New issue</a></b>\n👤 ghost · 💬 0 · ❤️ \
+         0\n\n🏷️ Newly labeled issues in owner/repo:\n\n<b><a \
+         href=\"https://github.com/owner/repo/issues/1\">Old issue</a></b>\n👤 ghost · 💬 0 · ❤️ 0"
+    );
+}
+
+#[test]
+fn test_format_new_issues_text_only_labeled() {
+    let labeled_issues = vec![issue("Old issue", "https://github.com/owner/repo/issues/1")];
+
+    let text = TelegramMessagingService::format_new_issues_text(
+        "owner/repo",
+        &[],
+        &labeled_issues,
+        Language::English,
+    );
+
+    assert!(text.starts_with("🏷️ Newly labeled issues in owner/repo:\n\n<b>"));
+    assert!(!text.contains("🚨"));
+}
+
+#[test]
+fn test_format_new_issues_text_in_spanish() {
+    let new_issues = vec![issue("New issue", "https://github.com/owner/repo/issues/2")];
+
+    let text = TelegramMessagingService::format_new_issues_text(
+        "owner/repo",
+        &new_issues,
+        &[],
+        Language::Spanish,
+    );
+
+    assert!(text.starts_with("🚨 Nuevos issues en owner/repo:\n\n<b>"));
+}
+
+#[test]
+fn test_format_issue_card() {
+    let issue = IssuesRepositoryIssuesNodes {
+        author: Some(IssuesRepositoryIssuesNodesAuthor { login: "octocat".to_string() }),
+        body_text: format!("Steps to <reproduce>:\n\n{}", "a".repeat(300)),
+        comments: IssuesRepositoryIssuesNodesComments { total_count: 3 },
+        reactions: IssuesRepositoryIssuesNodesReactions { total_count: 5 },
+        labels: Some(IssuesRepositoryIssuesNodesLabels {
+            nodes: Some(vec![IssuesRepositoryIssuesNodesLabelsNodes {
+                name: "good first issue".to_string(),
+                color: "0e8a16".to_string(),
+            }]),
+        }),
+        ..issue("Fix <b> tags", "https://github.com/owner/repo/issues/1")
+    };
+
+    let card = TelegramMessagingService::format_issue_card(&issue);
+
+    let lines: Vec<&str> = card.lines().collect();
+    assert_eq!(
+        lines[0],
+        "<b><a href=\"https://github.com/owner/repo/issues/1\">Fix &lt;b&gt; tags</a></b>"
+    );
+    assert_eq!(lines[1], "👤 octocat · 💬 3 · ❤️ 5");
+    assert_eq!(lines[2], "🟢 good first issue");
+    assert!(lines[3].starts_with("<i>Steps to &lt;reproduce&gt;: aaa"));
+    assert!(lines[3].ends_with("…</i>"));
+}
+
+#[test]
+fn test_format_poller_stopped_text() {
+    let text = TelegramMessagingService::format_poller_stopped_text("GitHub authentication failed");
+
+    assert_eq!(
+        text,
+        "🛑 The GitHub poller stopped and will not be restarted. No notifications are sent until \
+         the bot is restarted.\n\nLast error: GitHub authentication failed"
+    );
+}
+
+#[test]
+fn test_format_poller_status_text() {
+    assert_eq!(
+        TelegramMessagingService::format_poller_status_text(&PollerStatus::Running),
+        "✅ The GitHub poller is running."
+    );
+    assert_eq!(
+        TelegramMessagingService::format_poller_status_text(&PollerStatus::Degraded {
+            restarts: 2,
+            last_error: "<network> down".to_string(),
+        }),
+        "⚠️ The GitHub poller failed and is being restarted (restart 2).\n\nLast error: \
+         &lt;network&gt; down"
+    );
+    assert!(
+        TelegramMessagingService::format_poller_status_text(&PollerStatus::Stopped {
+            last_error: Some("GitHub authentication failed".to_string()),
+        })
+        .starts_with("🛑 The GitHub poller stopped and will not be restarted.")
+    );
+}
+
+#[test]
+fn test_format_owner_list_text() {
+    let owners = vec![OwnerSubscription {
+        owner: "rust-lang".to_string(),
+        tracked_labels: HashSet::from(["help wanted".to_string(), "E-easy".to_string()]),
+    }];
+
+    let text = TelegramMessagingService::format_owner_list_text(&owners);
+
+    assert_eq!(
+        text,
+        "🏢 Your tracked organizations and users:\n\n🏢 <b>All repositories of:</b> <a \
+         href=\"https://github.com/rust-lang\">rust-lang</a>\n🏷️ <b>Tracked labels:</b>\n- \
+         E-easy\n- help wanted"
+    );
+}
+
+#[test]
+fn test_format_search_issues_text() {
+    let filter = SearchFilter {
+        language: Some("rust".to_string()),
+        min_stars: Some(100),
+        ..Default::default()
+    };
+    let issues = vec![SearchIssue {
+        repo_name_with_owner: "owner/repo".to_string(),
+        repo_stars: 150,
+        repo_topics: Vec::new(),
+        issue: issue("Fix typo", "https://github.com/owner/repo/issues/1"),
+    }];
+
+    let text =
+        TelegramMessagingService::format_search_issues_text(&filter, &issues, Language::English);
+
+    assert_eq!(
+        text,
+        "🔎 New issues matching label \"good first issue\", language rust, 100+ stars:\n\n📦 owner/repo\n<b><a href=\"https://github.com/owner/repo/issues/1\">Fix \
+         typo</a></b>\n👤 ghost · 💬 0 · ❤️ 0"
+    );
+}
+
+#[test]
+fn test_format_digest_text() {
+    let labeled = |title: &str, url: &str, label: &str| IssuesRepositoryIssuesNodes {
+        labels: Some(IssuesRepositoryIssuesNodesLabels {
+            nodes: Some(vec![IssuesRepositoryIssuesNodesLabelsNodes {
+                name: label.to_string(),
+                color: "7057ff".to_string(),
+            }]),
+        }),
+        ..issue(title, url)
+    };
+    let entry = |repo: &str, issue| DigestEntry { repo_name_with_owner: repo.to_string(), issue };
+    let entries = vec![
+        entry("owner/repo", issue("Unlabeled", "https://github.com/owner/repo/issues/3")),
+        entry(
+            "owner/repo",
+            labeled("Fix typo", "https://github.com/owner/repo/issues/1", "good first issue"),
+        ),
+        entry("other/repo", labeled("Add docs", "https://github.com/other/repo/issues/2", "docs")),
+        entry(
+            "owner/repo",
+            labeled("Add test", "https://github.com/owner/repo/issues/4", "good first issue"),
+        ),
+    ];
+
+    let settings = UserSettings { delivery_mode: DeliveryMode::Hourly, ..Default::default() };
+    let text = TelegramMessagingService::format_digest_text(&settings, &entries);
+
+    assert_eq!(
+        text,
+        "📬 Your hourly digest of 4 issues:\n\n📦 <b><a \
+         href=\"https://github.com/other/repo\">other/repo</a></b>\n🟣 <i>docs</i>\n• <a \
+         href=\"https://github.com/other/repo/issues/2\">Add docs</a> · 💬 0\n\n📦 <b><a \
+         href=\"https://github.com/owner/repo\">owner/repo</a></b>\n🟣 <i>good first \
+         issue</i>\n• <a href=\"https://github.com/owner/repo/issues/1\">Fix typo</a> · 💬 0\n• \
+         <a href=\"https://github.com/owner/repo/issues/4\">Add test</a> · 💬 0\n🏷️ \
+         <i>Other</i>\n• <a href=\"https://github.com/owner/repo/issues/3\">Unlabeled</a> · 💬 0"
+    );
+
+    let settings = UserSettings { language: Language::Spanish, ..settings };
+    let text = TelegramMessagingService::format_digest_text(&settings, &entries);
+
+    assert!(text.starts_with("📬 Tu resumen por hora de 4 issues:\n\n"));
+    assert!(text.contains("🏷️ <i>Otros</i>"));
+}
+
+#[test]
+fn test_format_delivery_mode_text() {
+    let settings = UserSettings {
+        delivery_mode: DeliveryMode::Daily(NaiveTime::from_hms_opt(18, 30, 0).unwrap()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        TelegramMessagingService::format_delivery_mode_text(&settings, true),
+        "✅ Done, new issues are collected into a digest sent every day at 18:30 (UTC)."
+    );
+    assert!(
+        TelegramMessagingService::format_delivery_mode_text(&UserSettings::default(), false)
+            .starts_with(
+                "📬 Currently you are notified about new issues as soon as they are found."
+            )
+    );
+}
+
+#[test]
+fn test_format_settings_text() {
+    let settings = UserSettings {
+        delivery_mode: DeliveryMode::Weekly(NaiveTime::from_hms_opt(9, 0, 0).unwrap()),
+        timezone: parse_timezone("Europe/Berlin").unwrap(),
+        quiet_hours: Some("22:00-07:00".parse().unwrap()),
+        silent_notifications: true,
+        language: Language::Spanish,
+        page_size: 5,
+        ..Default::default()
+    };
+
+    let text = TelegramMessagingService::format_settings_text(&settings, false);
+
+    assert_eq!(
+        text,
+        "⚙️ Your settings:\n\n📬 Delivery: new issues are collected into a digest sent every \
+         Monday at 09:00 (Europe/Berlin)\n🌍 Timezone: Europe/Berlin\n🌙 Quiet hours: \
+         22:00-07:00\n🔕 Silent notifications: on\n🔗 Link previews: off\n🗣️ Notification \
+         language: Español\n📄 Page size: 5\n\nTap a button below to change a setting."
+    );
+}
+
+#[test]
+fn test_is_permanent_delivery_failure() {
+    let blocked = MessagingError::TeloxideRequest(RequestError::Api(ApiError::BotBlocked));
+    let chat_not_found = MessagingError::TeloxideRequest(RequestError::Api(ApiError::ChatNotFound));
+    let retry_after =
+        MessagingError::TeloxideRequest(RequestError::RetryAfter(Seconds::from_seconds(5)));
+    let too_long = MessagingError::TeloxideRequest(RequestError::Api(ApiError::MessageIsTooLong));
+
+    assert!(blocked.is_permanent_delivery_failure());
+    assert!(chat_not_found.is_permanent_delivery_failure());
+    assert!(!retry_after.is_permanent_delivery_failure());
+    assert!(!too_long.is_permanent_delivery_failure());
+}
+
+#[test]
+fn test_after_sent_parts() {
+    let error = || MessagingError::TeloxideRequest(RequestError::Api(ApiError::BotBlocked));
+
+    assert_eq!(error().after_sent_parts(0).sent_parts(), 0);
+    let partially_sent = error().after_sent_parts(2);
+    assert_eq!(partially_sent.sent_parts(), 2);
+    assert!(partially_sent.is_permanent_delivery_failure());
+}
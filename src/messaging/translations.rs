@@ -0,0 +1,94 @@
+//! The texts of issue notifications and digests in the languages a chat can
+//! choose. The rest of the bot is in English. Arguments are inserted as given,
+//! so they must already be escaped.
+
+use crate::storage::{DeliveryMode, Language};
+
+/// The title of the new issues of a repository.
+pub fn new_issues_title(language: Language, repo: &str) -> String {
+    match language {
+        Language::English => format!("🚨 New issues in {repo}:"),
+        Language::Spanish => format!("🚨 Nuevos issues en {repo}:"),
+    }
+}
+
+/// The title of the issues of a repository that got a tracked label.
+pub fn labeled_issues_title(language: Language, repo: &str) -> String {
+    match language {
+        Language::English => format!("🏷️ Newly labeled issues in {repo}:"),
+        Language::Spanish => format!("🏷️ Issues etiquetados recientemente en {repo}:"),
+    }
+}
+
+/// The title of the issues matching a search.
+pub fn search_issues_title(language: Language, filter: &str) -> String {
+    match language {
+        Language::English => format!("🔎 New issues matching {filter}:"),
+        Language::Spanish => format!("🔎 Nuevos issues que coinciden con {filter}:"),
+    }
+}
+
+/// The title of a digest of `count` issues.
+pub fn digest_title(language: Language, delivery_mode: DeliveryMode, count: usize) -> String {
+    let issues = if count == 1 { "issue" } else { "issues" };
+    match language {
+        Language::English => {
+            let period = match delivery_mode {
+                DeliveryMode::Instant => "",
+                DeliveryMode::Hourly => "hourly ",
+                DeliveryMode::Daily(_) => "daily ",
+                DeliveryMode::Weekly(_) => "weekly ",
+            };
+            format!("📬 Your {period}digest of {count} {issues}:")
+        }
+        Language::Spanish => {
+            let period = match delivery_mode {
+                DeliveryMode::Instant => "",
+                DeliveryMode::Hourly => " por hora",
+                DeliveryMode::Daily(_) => " diario",
+                DeliveryMode::Weekly(_) => " semanal",
+            };
+            format!("📬 Tu resumen{period} de {count} {issues}:")
+        }
+    }
+}
+
+/// The group of the issues of a digest that have no label.
+pub fn unlabeled_group(language: Language) -> &'static str {
+    match language {
+        Language::English => "Other",
+        Language::Spanish => "Otros",
+    }
+}
+
+/// The button opening the only issue of a notification.
+pub fn open_button(language: Language) -> &'static str {
+    match language {
+        Language::English => "🔗 Open",
+        Language::Spanish => "🔗 Abrir",
+    }
+}
+
+/// The button that stops tracking a label.
+pub fn mute_label_button(language: Language, label: &str) -> String {
+    match language {
+        Language::English => format!("🔇 Mute {label}"),
+        Language::Spanish => format!("🔇 Silenciar {label}"),
+    }
+}
+
+/// The button that mutes a repository for a day.
+pub fn mute_repo_button(language: Language) -> &'static str {
+    match language {
+        Language::English => "🔕 Mute repo for 24h",
+        Language::Spanish => "🔕 Silenciar repo 24 h",
+    }
+}
+
+/// The button that stops tracking a repository.
+pub fn untrack_repo_button(language: Language) -> &'static str {
+    match language {
+        Language::English => "❌ Stop tracking",
+        Language::Spanish => "❌ Dejar de seguir",
+    }
+}
@@ -1,4 +1,122 @@
-use crate::bot_handler::CallbackAction;
+use crate::bot_handler::{
+    CallbackAction,
+    callback_actions::{MAX_CALLBACK_DATA_LEN, callback_ref, is_callback_ref},
+};
+
+/// Telegram rejects messages longer than this, in UTF-16 code units.
+pub const MAX_MESSAGE_LEN: usize = 4096;
+
+/// The length of `text` as Telegram counts it.
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// The name of an HTML tag, e.g. `a` for `<a href="...">` and `</a>`.
+fn tag_name(tag: &str) -> &str {
+    tag.trim_start_matches(['<', '/'])
+        .split(|c: char| c.is_whitespace() || c == '>')
+        .next()
+        .unwrap_or_default()
+}
+
+/// Splits HTML into the pieces that must not be torn apart: tags, entities
+/// and single characters.
+fn html_atoms(html: &str) -> Vec<&str> {
+    let mut atoms = Vec::new();
+    let mut rest = html;
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '<' => rest.find('>').map_or(rest.len(), |end| end + 1),
+            '&' => rest
+                .find(';')
+                .filter(|&end| !rest[..end].contains(char::is_whitespace))
+                .map_or(1, |end| end + 1),
+            _ => c.len_utf8(),
+        };
+        let (atom, tail) = rest.split_at(len);
+        atoms.push(atom);
+        rest = tail;
+    }
+    atoms
+}
+
+/// Splits an HTML message into messages of at most `max_len` UTF-16 code
+/// units. Messages are split between items, i.e. at blank lines, or else at
+/// line breaks or spaces. Tags and entities are never cut; elements open at a
+/// split are closed at the end of one message and reopened in the next.
+pub fn split_html(html: &str, max_len: usize) -> Vec<String> {
+    if utf16_len(html) <= max_len {
+        return vec![html.to_string()];
+    }
+
+    let atoms = html_atoms(html);
+    let closing_len = |tags: &[&str]| tags.iter().map(|tag| tag_name(tag).len() + 3).sum::<usize>();
+    let mut chunks = Vec::new();
+    // The opening tags of the elements that are open where the chunk starts
+    let mut open_tags: Vec<&str> = Vec::new();
+    let mut start = 0;
+
+    while start < atoms.len() {
+        let mut tags = open_tags.clone();
+        let mut len = utf16_len(&open_tags.concat());
+        // The best place to split so far: the atom after it, its priority and the
+        // elements open there
+        let mut split: Option<(usize, u8, Vec<&str>)> = None;
+        let mut end = atoms.len();
+
+        for (i, &atom) in atoms.iter().enumerate().skip(start) {
+            let mut next_tags = tags.clone();
+            if atom.starts_with("</") {
+                if let Some(pos) = next_tags.iter().rposition(|tag| tag_name(tag) == tag_name(atom))
+                {
+                    next_tags.remove(pos);
+                }
+            } else if atom.starts_with('<') {
+                next_tags.push(atom);
+            }
+
+            if i > start && len + utf16_len(atom) + closing_len(&next_tags) > max_len {
+                (end, tags) = match split.take() {
+                    Some((split_end, _, split_tags)) => (split_end, split_tags),
+                    None => (i, tags),
+                };
+                break;
+            }
+            len += utf16_len(atom);
+            tags = next_tags;
+
+            let priority = match atom {
+                "\n" if i > start && atoms[i - 1] == "\n" => 3,
+                "\n" => 2,
+                " " => 1,
+                _ => continue,
+            };
+            if split.as_ref().is_none_or(|&(_, best, _)| priority >= best) {
+                split = Some((i + 1, priority, tags.clone()));
+            }
+        }
+
+        let body = atoms[start..end].concat();
+        let body = body.trim_end();
+        if !body.is_empty() {
+            let mut chunk = open_tags.concat();
+            chunk.push_str(body);
+            for tag in tags.iter().rev() {
+                chunk.push_str(&format!("</{}>", tag_name(tag)));
+            }
+            chunks.push(chunk);
+        }
+
+        // Don't start the next message with the whitespace it was split at
+        start = end;
+        while atoms.get(start).is_some_and(|atom| atom.trim().is_empty()) {
+            start += 1;
+        }
+        open_tags = tags;
+    }
+
+    chunks
+}
 
 /// Converts a GitHub color hex code to an emoji representation.
 pub fn github_color_to_emoji(hex_color: &str) -> &str {
@@ -33,15 +151,49 @@ pub fn github_color_to_emoji(hex_color: &str) -> &str {
     }
 }
 
+/// Shortens `text` to at most `max_chars` characters for a preview, collapsing
+/// whitespace and marking cut text with an ellipsis.
+pub fn excerpt(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
 /// Serializes a `CallbackAction` to a JSON string. Used for keyboard buttons.
+/// Names that would push the data over Telegram's limit, or that could be
+/// mistaken for a reference, are replaced by a reference.
 /// expect is ok because inputs are simple and controlled.
 pub fn serialize_action(action: &CallbackAction) -> String {
-    serde_json::to_string(action).expect("Failed to serialize action")
+    let data = serde_json::to_string(action).expect("Failed to serialize action");
+
+    let target_ref;
+    let (action, data) = match action.target() {
+        Some(target) if data.len() > MAX_CALLBACK_DATA_LEN || is_callback_ref(target) => {
+            target_ref = callback_ref(target);
+            let action = action.with_target(&target_ref);
+            let data = serde_json::to_string(&action).expect("Failed to serialize action");
+            (action, data)
+        }
+        _ => (action.clone(), data),
+    };
+
+    match action.label() {
+        Some(label) if data.len() > MAX_CALLBACK_DATA_LEN || is_callback_ref(label) => {
+            let reference = callback_ref(label);
+            serde_json::to_string(&action.with_label(&reference))
+                .expect("Failed to serialize action")
+        }
+        _ => data,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bot_handler::callback_actions::refers_to;
 
     #[test]
     fn test_github_color_to_emoji() {
@@ -56,10 +208,126 @@ mod tests {
         assert_eq!(github_color_to_emoji("unknown"), "⚪️");
     }
 
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("Short\n\n  text", 20), "Short text");
+        assert_eq!(excerpt("Ünïcödé text that goes on", 10), "Ünïcödé t…");
+        assert_eq!(excerpt("", 10), "");
+    }
+
+    #[test]
+    fn test_split_html_keeps_short_messages() {
+        assert_eq!(split_html("<b>Hi</b>", 10), ["<b>Hi</b>"]);
+    }
+
+    #[test]
+    fn test_split_html_splits_between_items() {
+        let html = "Title:\n\n<b>first</b>\nline\n\n<b>second</b>\nline";
+
+        let chunks = split_html(html, 30);
+
+        assert_eq!(chunks, ["Title:\n\n<b>first</b>\nline", "<b>second</b>\nline"]);
+    }
+
+    #[test]
+    fn test_split_html_reopens_tags() {
+        let html = format!("<i>{}</i>", "word ".repeat(10).trim_end());
+
+        let chunks = split_html(&html, 30);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(utf16_len(chunk) <= 30, "{chunk} is too long");
+            assert!(chunk.starts_with("<i>") && chunk.ends_with("</i>"), "{chunk}");
+        }
+        let text: Vec<_> = chunks.iter().map(|c| &c[3..c.len() - 4]).collect();
+        assert_eq!(text.join(" "), "word ".repeat(10).trim_end());
+    }
+
+    #[test]
+    fn test_split_html_never_cuts_tags_or_entities() {
+        let html = format!("<a href=\"https://github.com\">{}</a>", "&lt;".repeat(20));
+
+        for chunk in split_html(&html, 40) {
+            assert!(utf16_len(&chunk) <= 40, "{chunk} is too long");
+            assert!(chunk.starts_with("<a href=\"https://github.com\">"), "{chunk}");
+            assert!(chunk.ends_with("&lt;</a>"), "{chunk}");
+        }
+    }
+
+    #[test]
+    fn test_split_html_counts_utf16() {
+        // Every emoji is two UTF-16 code units
+        let chunks = split_html(&"🚨".repeat(10), 10);
+
+        assert_eq!(chunks, ["🚨".repeat(5), "🚨".repeat(5)]);
+    }
+
     #[test]
     fn test_serialize_action() {
         let action = CallbackAction::CmdHelp;
         let serialized = serialize_action(&action);
         assert_eq!(serialized, r#""cmd-help""#);
     }
+
+    #[test]
+    fn test_serialize_action_keeps_short_names() {
+        let action = CallbackAction::ViewRepoDetails("owner/repo", 1);
+        let serialized = serialize_action(&action);
+        assert_eq!(serialized, r#"{"vrd":["owner/repo",1]}"#);
+    }
+
+    #[test]
+    fn test_serialize_action_fits_maximum_length_names() {
+        // GitHub allows 39 characters for owners, 100 for repositories and 50 for
+        // labels
+        let repo = format!("{}/{}", "o".repeat(39), "r".repeat(100));
+        let label = "ラ".repeat(50);
+        let actions = [
+            CallbackAction::ViewRepoDetails(&repo, 9999),
+            CallbackAction::ViewRepoLabels(&repo, 9999, 9999),
+            CallbackAction::RemoveRepoPrompt(&repo),
+            CallbackAction::ToggleLabel(&label, 9999, 9999),
+            CallbackAction::BackToRepoDetails(&repo, 9999),
+            CallbackAction::MuteRepo(&repo),
+            CallbackAction::UntrackRepo(&repo),
+        ];
+
+        for action in actions {
+            let serialized = serialize_action(&action);
+            assert!(serialized.len() <= MAX_CALLBACK_DATA_LEN, "{serialized} is too long");
+
+            let parsed: CallbackAction = serde_json::from_str(&serialized).unwrap();
+            let target = parsed.target().unwrap();
+            assert!(is_callback_ref(target));
+            assert!(refers_to(target, action.target().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_serialize_action_shortens_repository_before_label() {
+        let repo = format!("{}/{}", "o".repeat(30), "r".repeat(30));
+        let serialized = serialize_action(&CallbackAction::MuteLabel(&repo, "bug"));
+        let parsed: CallbackAction = serde_json::from_str(&serialized).unwrap();
+        assert!(is_callback_ref(parsed.target().unwrap()));
+        assert_eq!(parsed.label(), Some("bug"));
+
+        let label = "ラ".repeat(50);
+        let serialized = serialize_action(&CallbackAction::MuteLabel(&repo, &label));
+        assert!(serialized.len() <= MAX_CALLBACK_DATA_LEN, "{serialized} is too long");
+        let parsed: CallbackAction = serde_json::from_str(&serialized).unwrap();
+        assert!(refers_to(parsed.target().unwrap(), &repo));
+        assert!(refers_to(parsed.label().unwrap(), &label));
+    }
+
+    #[test]
+    fn test_serialize_action_replaces_names_that_look_like_references() {
+        let action = CallbackAction::ToggleLabel("#bug", 1, 1);
+        let serialized = serialize_action(&action);
+
+        let parsed: CallbackAction = serde_json::from_str(&serialized).unwrap();
+        let target = parsed.target().unwrap();
+        assert_ne!(target, "#bug");
+        assert!(refers_to(target, "#bug"));
+    }
 }
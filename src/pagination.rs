@@ -13,30 +13,31 @@ pub struct Paginated<T> {
     pub total_pages: usize,
 }
 
-const DEFAULT_PAGE_SIZE: usize = 10;
+/// The number of items per page, unless a chat chose another one.
+pub const DEFAULT_PAGE_SIZE: usize = 10;
 
 impl<T> Paginated<T> {
     /// Creates a new `Paginated` instance.
     pub fn new(items: Vec<T>, page: usize) -> Self {
+        Self::with_page_size(items, page, DEFAULT_PAGE_SIZE)
+    }
+
+    /// Creates a new `Paginated` instance with `page_size` items per page.
+    pub fn with_page_size(items: Vec<T>, page: usize, page_size: usize) -> Self {
+        let page_size = page_size.max(1);
         let total_items = items.len();
 
         let total_pages = if total_items == 0 {
             1 // Conventionally, an empty list is considered 1 page.
         } else {
             // Ceiling division
-            total_items.div_ceil(DEFAULT_PAGE_SIZE)
+            total_items.div_ceil(page_size)
         };
 
         // Clamp current_page to be within [1, total_pages]
         let validated_page = page.max(1).min(total_pages);
 
-        Paginated {
-            items,
-            page: validated_page,
-            page_size: DEFAULT_PAGE_SIZE,
-            total_items,
-            total_pages,
-        }
+        Paginated { items, page: validated_page, page_size, total_items, total_pages }
     }
 
     /// Returns `true` if there is a next page.
@@ -125,6 +126,14 @@ mod tests {
         assert_eq!(paginated_exact_high.page, 3);
     }
 
+    #[test]
+    fn test_with_page_size() {
+        let items = (1..=12).collect::<Vec<i32>>();
+        let paginated = Paginated::with_page_size(items, 3, 5);
+        assert_eq!(paginated.total_pages, 3);
+        assert_eq!(paginated.get_page_items(), &[11, 12]);
+    }
+
     #[test]
     fn test_has_prev_next_logic() {
         let items = (1..=22).collect::<Vec<i32>>(); // 3 pages 
@@ -0,0 +1,104 @@
+//! Sends the digests of the chats that collect their notifications instead of
+//! receiving them right away. The issues found by the poller wait in the
+//! digest table until the next scheduled time of the chat's delivery mode.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    messaging::MessagingService,
+    storage::{PendingDigest, RepoStorage, StorageResult},
+};
+
+/// How often the scheduler checks for digests that are due.
+const DIGEST_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Sends the digests that are due.
+pub struct DigestScheduler {
+    storage: Arc<dyn RepoStorage>,
+    messaging_service: Arc<dyn MessagingService>,
+}
+
+impl DigestScheduler {
+    /// Create a new `DigestScheduler`.
+    pub fn new(
+        storage: Arc<dyn RepoStorage>,
+        messaging_service: Arc<dyn MessagingService>,
+    ) -> Self {
+        Self { storage, messaging_service }
+    }
+
+    /// Send the digests that are due until `shutdown` is cancelled.
+    pub async fn run(&self, shutdown: CancellationToken) {
+        tracing::debug!("Starting digest scheduler");
+
+        let mut interval = tokio::time::interval(DIGEST_INTERVAL);
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            if let Err(e) = self.send_due_digests(Utc::now()).await {
+                tracing::error!("Failed to send digests: {e:?}");
+            }
+        }
+
+        tracing::debug!("Digest scheduler stopped");
+    }
+
+    /// Send the digest of every chat whose scheduled time passed since its
+    /// last one. A digest that cannot be sent is retried on the next check.
+    pub(super) async fn send_due_digests(&self, now: DateTime<Utc>) -> StorageResult<()> {
+        for pending in self.storage.get_pending_digests().await? {
+            if Self::is_due(&pending, now) {
+                self.send_digest(pending).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if a scheduled time passed since the chat's last
+    /// digest. Entries left over after switching to instant delivery are sent
+    /// right away. Digests that fall into the chat's quiet hours wait until
+    /// they end.
+    fn is_due(pending: &PendingDigest, now: DateTime<Utc>) -> bool {
+        if pending.settings.quiet_until(now).is_some() {
+            return false;
+        }
+
+        match pending.settings.last_digest_slot(now) {
+            Some(slot) => pending.last_digest_at.is_none_or(|sent_at| sent_at < slot.timestamp()),
+            None => true,
+        }
+    }
+
+    /// Send the entries waiting for a chat's digest and remove them once
+    /// delivered.
+    async fn send_digest(&self, pending: PendingDigest) -> StorageResult<()> {
+        let chat_id = pending.chat_id;
+        let entries = self.storage.get_digest_entries(chat_id).await?;
+        let Some(&(last_entry_id, _)) = entries.last() else {
+            return Ok(());
+        };
+
+        tracing::debug!("Sending digest of {} issues to chat {chat_id}", entries.len());
+        let entries = entries.into_iter().map(|(_, entry)| entry).collect();
+        match self.messaging_service.send_digest_msg(chat_id, &pending.settings, entries).await {
+            Ok(()) => self.storage.complete_digest(chat_id, last_entry_id).await,
+            Err(e) if e.is_permanent_delivery_failure() => {
+                tracing::info!("Chat {chat_id} can no longer be reached ({e}), deactivating it");
+                self.storage.deactivate_chat(chat_id).await.map(|_| ())
+            }
+            Err(e) => {
+                tracing::warn!("Failed to send digest to chat {chat_id}: {e}. Will be retried");
+                Ok(())
+            }
+        }
+    }
+}
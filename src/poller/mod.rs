@@ -1,21 +1,33 @@
+mod digest;
+mod outbox;
+mod supervisor;
 #[cfg(test)]
 mod tests;
 
 use std::{
     collections::{HashMap, HashSet},
+    str::FromStr,
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant},
 };
 
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
+pub use digest::DigestScheduler;
 use futures::{StreamExt, stream};
+pub use outbox::OutboxWorker;
+pub use supervisor::{PollerStatus, PollerSupervisor};
 use teloxide::prelude::*;
 use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    github::{GithubClient, GithubError, issues},
+    github::{GithubClient, GithubError, RepoIssuesRequest, RepoIssuesResult, SearchIssue, issues},
     messaging::{MessagingError, MessagingService},
-    storage::{RepoEntity, RepoStorage, StorageError},
+    storage::{
+        IssueFilters, NOTIFIED_ISSUE_RETENTION, Notification, RepoEntity, RepoStorage,
+        SearchSubscription, StorageError,
+    },
 };
 
 /// Represents errors that can occur during the polling process.
@@ -34,6 +46,31 @@ pub enum PollerError {
 
 type Result<T> = std::result::Result<T, PollerError>;
 
+/// How far the polling window reaches back before the previous poll, to
+/// tolerate clock skew between us and GitHub.
+const LAST_POLL_OVERLAP: chrono::Duration = chrono::Duration::minutes(5);
+
+/// How long the repositories of a tracked organization or user are reused
+/// before they are listed again. Newly created repositories are picked up
+/// once the list expires.
+const OWNER_REPOS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The repositories of an organization or user, as last listed on GitHub.
+#[derive(Debug, Clone)]
+struct CachedOwnerRepos {
+    repos: HashSet<RepoEntity>,
+    fetched_at: Instant,
+}
+
+/// The state of a single chat's subscription to a repository for one poll.
+#[derive(Debug, Clone)]
+struct Subscription {
+    chat_id: ChatId,
+    tracked_labels: HashSet<String>,
+    filters: IssueFilters,
+    since: Option<DateTime<Utc>>,
+}
+
 /// A poller for polling issues from GitHub and sending messages to Telegram.
 #[derive(Clone)]
 pub struct GithubPoller {
@@ -44,6 +81,8 @@ pub struct GithubPoller {
     poll_interval: u64,
     // The maximum number of concurrent requests to GitHub.
     max_concurrency: usize,
+    // The repositories of tracked organizations and users, by lowercase login.
+    owner_repos: Arc<Mutex<HashMap<String, CachedOwnerRepos>>>,
 }
 
 impl GithubPoller {
@@ -55,187 +94,871 @@ impl GithubPoller {
         poll_interval: u64,
         max_concurrency: usize,
     ) -> Self {
-        Self { github_client, storage, messaging_service, poll_interval, max_concurrency }
+        Self {
+            github_client,
+            storage,
+            messaging_service,
+            poll_interval,
+            max_concurrency,
+            owner_repos: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 
-    /// Run the poller.
-    pub async fn run(&self) -> Result<()> {
+    /// Run the poller until `shutdown` is cancelled. A cycle that is already
+    /// running when shutdown is requested is finished, so in-flight
+    /// notifications are sent and recorded; no new cycle is started.
+    pub async fn run(&self, shutdown: CancellationToken) -> Result<()> {
         tracing::debug!("Starting GitHub poller");
 
         let mut interval = tokio::time::interval(Duration::from_secs(self.poll_interval));
 
         loop {
-            interval.tick().await;
-            let repos_by_chat_id = self.storage.get_all_repos().await?;
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            let mut repos_by_chat_id = self.storage.get_all_repos().await?;
+            self.expand_owner_subscriptions(&mut repos_by_chat_id).await?;
             self.poll_all_repos(repos_by_chat_id).await?;
+            self.poll_searches().await?;
         }
+
+        tracing::debug!("GitHub poller stopped");
+        Ok(())
     }
 
-    /// Poll all repos for all users.
+    /// Poll all repos for all users. Subscriptions are grouped by repository,
+    /// so every repository is fetched from GitHub once per cycle no matter how
+    /// many chats track it. Errors that only affect single repositories or
+    /// chats are logged; fatal GitHub errors stop the poller.
     async fn poll_all_repos(
         &self,
         repos_by_chat_id: HashMap<ChatId, HashSet<RepoEntity>>,
     ) -> Result<()> {
-        let tasks = repos_by_chat_id
-            .into_iter()
-            .flat_map(|(chat_id, repos)| repos.into_iter().map(move |repo| (chat_id, repo)))
-            .map(|(chat_id, repo)| {
-                let self_clone = self.clone();
-                async move { self_clone.poll_user_repo(chat_id, repo).await }
-            });
+        let chats_by_repo = Self::group_by_repo(repos_by_chat_id).into_iter().collect();
+
+        match self.poll_repos(chats_by_repo).await {
+            // Only fatal GitHub errors make it out of `handle_fetch_error`
+            Err(e @ PollerError::Github(_)) => Err(e),
+            Err(e) => {
+                tracing::error!("Error polling repos: {e:?}");
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Poll the GitHub-wide searches of all chats. Subscriptions with the same
+    /// query share a single search, and the criteria GitHub cannot search by
+    /// are matched against its results per subscription. Like
+    /// `poll_all_repos`, only fatal GitHub errors are returned.
+    async fn poll_searches(&self) -> Result<()> {
+        let mut subscriptions_by_query: HashMap<String, Vec<SearchSubscription>> = HashMap::new();
+        for subscription in self.storage.get_all_search_subscriptions().await? {
+            subscriptions_by_query
+                .entry(subscription.filter.query())
+                .or_default()
+                .push(subscription);
+        }
+
+        for (query, subscriptions) in subscriptions_by_query {
+            let since = subscriptions
+                .iter()
+                .map(|s| s.last_poll_time)
+                .min()
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+                .map(|dt| dt - LAST_POLL_OVERLAP);
+
+            let results = match self.github_client.search_issues(&query, since).await {
+                Ok(results) => results,
+                Err(e) => {
+                    Self::handle_fetch_error(&format!("search \"{query}\""), e)?;
+                    continue;
+                }
+            };
+
+            for subscription in subscriptions {
+                let id = subscription.id;
+                match self.notify_search_subscriber(subscription, &results).await {
+                    Ok(true) =>
+                        if let Err(e) = self.storage.set_search_poll_time(id).await {
+                            tracing::error!(
+                                "Failed to update last poll time for search {id}: {e:?}"
+                            );
+                        },
+                    Ok(false) => {}
+                    Err(e) => tracing::error!("Error polling search {id}: {e:?}"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notify a chat about the search results matching its subscription that
+    /// were opened after it subscribed and have not been notified yet, e.g.
+    /// through a repository it tracks. Returns `false` if the notification
+    /// could not be delivered, see `deliver`.
+    async fn notify_search_subscriber(
+        &self,
+        subscription: SearchSubscription,
+        results: &[SearchIssue],
+    ) -> Result<bool> {
+        let SearchSubscription { chat_id, filter, created_at, .. } = subscription;
+
+        let mut issues_by_repo: HashMap<&str, Vec<&SearchIssue>> = HashMap::new();
+        for result in results {
+            // Issues opened before the retention window may have been forgotten by the
+            // ledger already
+            let opened_after_subscribing = DateTime::parse_from_rfc3339(&result.issue.created_at)
+                .is_ok_and(|dt| dt.timestamp() >= created_at && dt >= Self::retention_start());
+            if opened_after_subscribing
+                && filter.matches_repo(result.repo_stars, &result.repo_topics)
+            {
+                issues_by_repo.entry(&result.repo_name_with_owner).or_default().push(result);
+            }
+        }
+
+        // Record the issues per repository before sending, so they are not notified
+        // again by this or any other subscription, and only notify the ones this call
+        // recorded
+        let mut marked_by_repo: Vec<(RepoEntity, Vec<String>)> = Vec::new();
+        let mut unseen = Vec::new();
+        for (repo_name_with_owner, issues) in issues_by_repo {
+            let Ok(repo) = RepoEntity::from_str(repo_name_with_owner) else {
+                tracing::warn!(
+                    "Skipping search results of invalid repository {repo_name_with_owner}"
+                );
+                continue;
+            };
+            let marked = match self.mark_search_results(chat_id, &repo, &issues).await {
+                Ok(marked) => marked,
+                Err(e) => {
+                    for (repo, issue_ids) in &marked_by_repo {
+                        self.unmark_undelivered(chat_id, repo, issue_ids).await;
+                    }
+                    return Err(e);
+                }
+            };
+            if marked.is_empty() {
+                continue;
+            }
+            unseen.extend(
+                issues.into_iter().filter(|result| marked.contains(&result.issue.id)).cloned(),
+            );
+            marked_by_repo.push((repo, marked));
+        }
+
+        if unseen.is_empty() {
+            tracing::debug!("No new search results for {filter} in chat {chat_id}");
+            return Ok(true);
+        }
+
+        let notification = Notification::SearchIssues { filter, issues: unseen };
+        match self.deliver(chat_id, notification).await {
+            Ok(true) => Ok(true),
+            undelivered => {
+                for (repo, issue_ids) in &marked_by_repo {
+                    self.unmark_undelivered(chat_id, repo, issue_ids).await;
+                }
+                undelivered
+            }
+        }
+    }
+
+    /// Record the search results of a repository that are not in the ledger
+    /// yet. Returns the ids of the results this call recorded.
+    async fn mark_search_results(
+        &self,
+        chat_id: ChatId,
+        repo: &RepoEntity,
+        results: &[&SearchIssue],
+    ) -> Result<Vec<String>> {
+        let notified_issues = self.storage.get_notified_issues(chat_id, repo).await?;
+        let issue_ids: Vec<String> = results
+            .iter()
+            .filter(|result| !notified_issues.contains(&result.issue.id))
+            .map(|result| result.issue.id.clone())
+            .collect();
+        if issue_ids.is_empty() {
+            return Ok(issue_ids);
+        }
+        Ok(self.storage.mark_issues_notified(chat_id, repo, &issue_ids).await?)
+    }
+
+    /// Remove issues whose notification could not be delivered from the
+    /// ledger again, so they are notified next cycle.
+    async fn unmark_undelivered(&self, chat_id: ChatId, repo: &RepoEntity, issue_ids: &[String]) {
+        if let Err(e) = self.storage.unmark_issues_notified(chat_id, repo, issue_ids).await {
+            tracing::error!(
+                "Failed to unmark undelivered issues for repo {}: {e:?}",
+                repo.name_with_owner
+            );
+        }
+    }
 
-        let mut buffered_tasks = stream::iter(tasks).buffer_unordered(self.max_concurrency);
+    /// Add the repositories of every tracked organization or user to the chats
+    /// subscribed to them. A repository a chat also tracks directly is only
+    /// polled once, with the labels of the direct subscription.
+    async fn expand_owner_subscriptions(
+        &self,
+        repos_by_chat_id: &mut HashMap<ChatId, HashSet<RepoEntity>>,
+    ) -> Result<()> {
+        let owners_by_chat_id = self.storage.get_all_owner_subscriptions().await?;
+        let mut repos_by_owner: HashMap<String, HashSet<RepoEntity>> = HashMap::new();
 
-        while let Some(result) = buffered_tasks.next().await {
-            if let Err(e) = result {
-                tracing::error!("Error polling repo: {e:?}");
+        for (chat_id, owners) in owners_by_chat_id {
+            for owner in owners {
+                let key = owner.to_lowercase();
+                if !repos_by_owner.contains_key(&key) {
+                    let repos = self.owner_repos(&owner).await?;
+                    repos_by_owner.insert(key.clone(), repos);
+                }
+                repos_by_chat_id.entry(chat_id).or_default().extend(repos_by_owner[&key].clone());
             }
         }
 
         Ok(())
     }
 
-    /// Poll a single repo for a single user.
-    async fn poll_user_repo(&self, chat_id: ChatId, repo: RepoEntity) -> Result<()> {
-        tracing::debug!("Polling issues for repository: {}", repo.name_with_owner);
+    /// The repositories of an organization or user, listed again once the
+    /// cached list expires. If listing fails, the expired list is used for
+    /// this cycle; fatal GitHub errors are returned.
+    async fn owner_repos(&self, owner: &str) -> Result<HashSet<RepoEntity>> {
+        let key = owner.to_lowercase();
+        let cached = self.owner_repos.lock().await.get(&key).cloned();
+        if let Some(cached) = &cached
+            && cached.fetched_at.elapsed() < OWNER_REPOS_TTL
+        {
+            return Ok(cached.repos.clone());
+        }
+
+        let repos = match self.github_client.owner_repositories(owner).await {
+            Ok(Some(owner_repos)) => owner_repos
+                .repos
+                .iter()
+                .filter_map(|name_with_owner| match RepoEntity::from_str(name_with_owner) {
+                    Ok(repo) => Some(repo),
+                    Err(e) => {
+                        tracing::warn!("Skipping repository {name_with_owner} of {owner}: {e}");
+                        None
+                    }
+                })
+                .collect(),
+            Ok(None) => {
+                tracing::warn!("Tracked organization or user {owner} no longer exists on GitHub");
+                HashSet::new()
+            }
+            Err(e) => {
+                Self::handle_fetch_error(&format!("the repositories of {owner}"), e)?;
+                return Ok(cached.map(|cached| cached.repos).unwrap_or_default());
+            }
+        };
+
+        tracing::debug!("Listed {} repositories of {owner}", repos.len());
+        self.owner_repos
+            .lock()
+            .await
+            .insert(key, CachedOwnerRepos { repos: repos.clone(), fetched_at: Instant::now() });
+
+        Ok(repos)
+    }
+
+    /// Invert the per-chat subscriptions into the chats subscribed to each
+    /// repository.
+    fn group_by_repo(
+        repos_by_chat_id: HashMap<ChatId, HashSet<RepoEntity>>,
+    ) -> HashMap<RepoEntity, Vec<ChatId>> {
+        let mut chats_by_repo: HashMap<RepoEntity, Vec<ChatId>> = HashMap::new();
+        for (chat_id, repos) in repos_by_chat_id {
+            for repo in repos {
+                chats_by_repo.entry(repo).or_default().push(chat_id);
+            }
+        }
+        chats_by_repo
+    }
 
+    /// Load the subscription state of a single chat for a repository. Returns
+    /// `None` if the chat does not track any labels or muted the repository.
+    async fn load_subscription(
+        &self,
+        chat_id: ChatId,
+        repo: &RepoEntity,
+    ) -> Result<Option<Subscription>> {
         let tracked_labels =
-            self.storage.get_tracked_labels(chat_id, &repo).await.map_err(PollerError::Storage)?;
+            self.storage.get_tracked_labels(chat_id, repo).await.map_err(PollerError::Storage)?;
 
-        // If there are no tracked labels, skip this repo
+        // If there are no tracked labels, skip this chat
         if tracked_labels.is_empty() {
-            tracing::debug!("No tracked labels for repository: {}", repo.name_with_owner);
-            return Ok(());
+            tracing::debug!(
+                "No tracked labels for repository {} in chat {}",
+                repo.name_with_owner,
+                chat_id
+            );
+            return Ok(None);
         }
 
-        // Get the last poll time for this repo
-        let last_poll_time = self.storage.get_last_poll_time(chat_id, &repo).await?;
-        let last_poll_time = last_poll_time
-            .map(|t| SystemTime::UNIX_EPOCH + Duration::from_secs(t as u64))
-            .unwrap_or(SystemTime::UNIX_EPOCH);
+        // Muting moved the polling window to the end of the mute, nothing to do
+        // until then
+        let muted_until = self.storage.get_muted_until(chat_id, repo).await?;
+        if muted_until.is_some_and(|until| until > Utc::now().timestamp()) {
+            tracing::debug!("Repository {} is muted in chat {}", repo.name_with_owner, chat_id);
+            return Ok(None);
+        }
 
-        let issues = self
-            .github_client
-            .repo_issues_by_label(&repo.owner, &repo.name, tracked_labels)
-            .await
-            .map_err(PollerError::Github);
-
-        match issues {
-            Ok(issues) => {
-                let issues_to_notify = Self::filter_new_issues(issues, &last_poll_time);
-
-                if !issues_to_notify.is_empty() {
-                    tracing::debug!("Sending new issues message to chat: {chat_id}");
-
-                    let msg_result = self
-                        .messaging_service
-                        .send_new_issues_msg(chat_id, &repo.name_with_owner, issues_to_notify)
-                        .await;
-
-                    // If sending the message fails, log the error and return without updating the
-                    // last poll time
-                    if let Err(e) = msg_result {
-                        tracing::error!(
-                            "Failed to send new issues message for repo {}: {e:?}. Will be \
-                             retried next cycle",
-                            repo.name_with_owner
-                        );
-                        return Ok(());
-                    }
+        let filters = self.storage.get_issue_filters(chat_id, repo).await?;
 
-                    // If the message was sent successfully, update the last poll time
-                    let set_last_poll_result =
-                        self.storage.set_last_poll_time(chat_id, &repo).await;
-
-                    if let Err(e) = set_last_poll_result {
-                        tracing::error!(
-                            "Failed to update last poll time for repo {}: {e:?}",
-                            repo.name_with_owner
-                        );
-                    } else {
-                        tracing::debug!(
-                            "Sent notifications and updated last poll time for repo {} in chat {}",
-                            repo.name_with_owner,
-                            chat_id
-                        );
-                    }
-                } else {
-                    tracing::debug!("No new issues to notify for {}", repo.name_with_owner);
+        // Only issues touched since the last poll can be new or newly labeled. The
+        // window overlaps the previous poll a little, the ledger takes care of
+        // duplicates.
+        let since = self
+            .storage
+            .get_last_poll_time(chat_id, repo)
+            .await?
+            .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0))
+            .map(|dt| dt - LAST_POLL_OVERLAP);
+
+        Ok(Some(Subscription { chat_id, tracked_labels, filters, since }))
+    }
+
+    /// Load the subscriptions of all chats tracking labels in a repository.
+    async fn load_subscriptions(
+        &self,
+        repo: &RepoEntity,
+        chat_ids: Vec<ChatId>,
+    ) -> Result<Vec<Subscription>> {
+        let mut subscriptions = Vec::with_capacity(chat_ids.len());
+        for chat_id in chat_ids {
+            if let Some(subscription) = self.load_subscription(chat_id, repo).await? {
+                subscriptions.push(subscription);
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    /// Build the GitHub request covering all subscriptions of a repository: the
+    /// union of all tracked labels, reaching back as far as the chat that was
+    /// polled least recently.
+    fn issues_request(repo: &RepoEntity, subscriptions: &[Subscription]) -> RepoIssuesRequest {
+        RepoIssuesRequest {
+            owner: repo.owner.clone(),
+            name: repo.name.clone(),
+            labels: subscriptions.iter().flat_map(|s| s.tracked_labels.iter().cloned()).collect(),
+            since: subscriptions.iter().map(|s| s.since).min().flatten(),
+        }
+    }
+
+    /// Poll the given repos with batched GitHub requests and fan the issues out
+    /// to every subscribed chat. A failure in one repository does not stop the
+    /// others; the first error is returned once all repositories are done.
+    async fn poll_repos(&self, chats_by_repo: Vec<(RepoEntity, Vec<ChatId>)>) -> Result<()> {
+        let mut first_error = None;
+        let mut polled = Vec::with_capacity(chats_by_repo.len());
+
+        for (repo, chat_ids) in chats_by_repo {
+            tracing::debug!(
+                "Polling issues for repository {} on behalf of {} chats",
+                repo.name_with_owner,
+                chat_ids.len()
+            );
+
+            match self.load_subscriptions(&repo, chat_ids).await {
+                Ok(subscriptions) if subscriptions.is_empty() => tracing::debug!(
+                    "No chats track labels for repository: {}",
+                    repo.name_with_owner
+                ),
+                Ok(subscriptions) => polled.push((repo, subscriptions)),
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to load subscriptions for repo {}: {e:?}",
+                        repo.name_with_owner
+                    );
+                    first_error.get_or_insert(e);
                 }
             }
-            Err(e) => match e {
-                PollerError::Github(github_error) => match github_error {
-                    GithubError::GraphQLApiError(msg) => {
-                        tracing::error!(
-                            "A GraphQL API error occurred while polling repo {} (chat {}): {}. \
-                             Skipping this repo for this cycle.",
-                            repo.name_with_owner,
-                            chat_id,
-                            msg
-                        );
-                    }
-                    GithubError::RateLimited => {
-                        tracing::warn!(
-                            "Rate limit exceeded while polling issues for repository {}. Will \
-                             retry later.",
-                            repo.name_with_owner
-                        );
-                    }
-                    GithubError::RequestError { source } => {
-                        tracing::warn!(
-                            "A network/HTTP request error occurred for repo {} (chat {}): {}. \
-                             Skipping this repo for this cycle.",
-                            repo.name_with_owner,
-                            chat_id,
-                            source
-                        );
-                    }
-                    GithubError::Unauthorized
-                    | GithubError::InvalidHeader(_)
-                    | GithubError::SerializationError { .. } => {
-                        tracing::error!(
-                            "Fatal error while polling issues for repository {}: {github_error:?}",
-                            repo.name_with_owner
-                        );
-                        return Err(PollerError::Github(github_error));
+        }
+
+        if !polled.is_empty() {
+            let requests = polled
+                .iter()
+                .map(|(repo, subscriptions)| Self::issues_request(repo, subscriptions))
+                .collect();
+
+            match self.github_client.repos_issues_by_label_batch(requests).await {
+                Ok(results) => {
+                    let tasks =
+                        polled.into_iter().zip(results).map(|((repo, subscriptions), result)| {
+                            let self_clone = self.clone();
+                            async move {
+                                let result = self_clone.fan_out(&repo, subscriptions, result).await;
+                                (repo, result)
+                            }
+                        });
+
+                    let mut buffered_tasks =
+                        stream::iter(tasks).buffer_unordered(self.max_concurrency);
+
+                    while let Some((repo, result)) = buffered_tasks.next().await {
+                        if let Err(e) = result {
+                            tracing::error!("Error polling repo {}: {e:?}", repo.name_with_owner);
+                            first_error.get_or_insert(e);
+                        }
                     }
-                    GithubError::HeaderError(msg) => {
-                        tracing::warn!(
-                            "Could not parse rate limit headers for repo {} (chat {}): {}. \
-                             Skipping this repo for this cycle.",
-                            repo.name_with_owner,
-                            chat_id,
-                            msg
-                        );
+                }
+                Err(e) => {
+                    let target = format!("a batch of {} repositories", polled.len());
+                    if let Err(e) = Self::handle_fetch_error(&target, e) {
+                        first_error.get_or_insert(e);
                     }
-                },
-                unexpected_error => {
+                }
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Notify every subscription of a repository about the fetched issues. A
+    /// subscription that fails does not hold back the others; the first error
+    /// is returned once all of them were handled.
+    async fn fan_out(
+        &self,
+        repo: &RepoEntity,
+        subscriptions: Vec<Subscription>,
+        issues: RepoIssuesResult,
+    ) -> Result<()> {
+        let issues = match issues {
+            Ok(issues) => issues,
+            Err(e) => return Self::handle_fetch_error(&repo.name_with_owner, e),
+        };
+
+        let mut first_error = None;
+        for subscription in subscriptions {
+            let chat_id = subscription.chat_id;
+            match self.notify_subscriber(repo, subscription, &issues).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to notify chat {chat_id} about repo {}: {e:?}",
+                        repo.name_with_owner
+                    );
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            }
+
+            // Move the polling window forward, so the next poll only looks at issues
+            // touched after this one
+            if let Err(e) = self.storage.set_last_poll_time(chat_id, repo).await {
+                tracing::error!(
+                    "Failed to update last poll time for repo {}: {e:?}",
+                    repo.name_with_owner
+                );
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Notify the chats subscribed to a repository, directly or through its
+    /// owner, about a single issue pushed to us, e.g. by a webhook, instead of
+    /// being polled. The issue goes through the same label filter, ledger and
+    /// classification as polled issues, but the polling window is left
+    /// untouched. A chat that fails does not hold back the others; the first
+    /// error is returned once all of them were handled.
+    pub async fn handle_pushed_issue(
+        &self,
+        repo_name_with_owner: &str,
+        issue: issues::IssuesRepositoryIssuesNodes,
+    ) -> Result<()> {
+        let mut chats_by_repo: HashMap<RepoEntity, Vec<ChatId>> = HashMap::new();
+        let mut direct_subscribers = HashSet::new();
+        for (chat_id, repo) in self.storage.get_repo_subscribers(repo_name_with_owner).await? {
+            direct_subscribers.insert(chat_id);
+            chats_by_repo.entry(repo).or_default().push(chat_id);
+        }
+        self.add_owner_subscribers(repo_name_with_owner, &direct_subscribers, &mut chats_by_repo)
+            .await?;
+
+        if chats_by_repo.is_empty() {
+            tracing::debug!("No chats subscribed to repository: {repo_name_with_owner}");
+            return Ok(());
+        }
+
+        let issues = [issue];
+        let mut first_error = None;
+        for (repo, chat_ids) in chats_by_repo {
+            let subscriptions = match self.load_subscriptions(&repo, chat_ids).await {
+                Ok(subscriptions) => subscriptions,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to load subscriptions for repo {}: {e:?}",
+                        repo.name_with_owner
+                    );
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+
+            for subscription in subscriptions {
+                let chat_id = subscription.chat_id;
+                if let Err(e) = self.notify_subscriber(&repo, subscription, &issues).await {
                     tracing::error!(
-                        "Unexpected error type {:?} at GitHub fetch stage for repo {} (chat {}). \
-                         Propagating.",
-                        unexpected_error,
-                        repo.name_with_owner,
-                        chat_id
+                        "Failed to notify chat {chat_id} about pushed issue in repo {}: {e:?}",
+                        repo.name_with_owner
                     );
-                    return Err(unexpected_error);
+                    first_error.get_or_insert(e);
                 }
-            },
+            }
         }
 
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Add the chats subscribed to the owner of a repository, as the poller
+    /// would when expanding owner subscriptions. Chats that also track the
+    /// repository directly keep the labels of the direct subscription.
+    async fn add_owner_subscribers(
+        &self,
+        repo_name_with_owner: &str,
+        direct_subscribers: &HashSet<ChatId>,
+        chats_by_repo: &mut HashMap<RepoEntity, Vec<ChatId>>,
+    ) -> Result<()> {
+        let Ok(pushed_repo) = RepoEntity::from_str(repo_name_with_owner) else {
+            tracing::warn!(
+                "Skipping owner subscribers of invalid repository {repo_name_with_owner}"
+            );
+            return Ok(());
+        };
+
+        let owner_subscribers: Vec<ChatId> = self
+            .storage
+            .get_owner_subscribers(&pushed_repo.owner)
+            .await?
+            .into_iter()
+            .filter(|chat_id| !direct_subscribers.contains(chat_id))
+            .collect();
+        if owner_subscribers.is_empty() {
+            return Ok(());
+        }
+
+        // Use the repository as listed for the owner, so the ledger and labels match
+        // the ones of polled issues. Archived repositories and forks are not listed.
+        let Some(repo) =
+            self.owner_repos(&pushed_repo.owner).await?.into_iter().find(|repo| {
+                repo.name_with_owner.eq_ignore_ascii_case(&pushed_repo.name_with_owner)
+            })
+        else {
+            tracing::debug!(
+                "Repository {repo_name_with_owner} is not among the repositories of {}",
+                pushed_repo.owner
+            );
+            return Ok(());
+        };
+
+        chats_by_repo.entry(repo).or_default().extend(owner_subscribers);
         Ok(())
     }
 
+    /// Log a GitHub error that occurred while fetching issues for `target`.
+    /// Transient errors skip the target for this cycle, fatal ones are
+    /// returned.
+    fn handle_fetch_error(target: &str, github_error: GithubError) -> Result<()> {
+        match github_error {
+            GithubError::GraphQLApiError(msg) => {
+                tracing::error!(
+                    "A GraphQL API error occurred while polling {}: {}. Skipping it for this \
+                     cycle.",
+                    target,
+                    msg
+                );
+            }
+            GithubError::RateLimited => {
+                tracing::warn!(
+                    "Rate limit exceeded while polling issues for {}. Will retry later.",
+                    target
+                );
+            }
+            GithubError::RequestError { source } => {
+                tracing::warn!(
+                    "A network/HTTP request error occurred for {}: {}. Skipping it for this cycle.",
+                    target,
+                    source
+                );
+            }
+            GithubError::Unauthorized
+            | GithubError::InvalidHeader(_)
+            | GithubError::SerializationError { .. } => {
+                tracing::error!(
+                    "Fatal error while polling issues for {}: {github_error:?}",
+                    target
+                );
+                return Err(PollerError::Github(github_error));
+            }
+            GithubError::RepositoryNotFound(name) => {
+                tracing::warn!(
+                    "Repository {} no longer exists or is not accessible while polling {}. \
+                     Skipping it for this cycle.",
+                    name,
+                    target
+                );
+            }
+            GithubError::HeaderError(msg) => {
+                tracing::warn!(
+                    "Could not parse rate limit headers for {}: {}. Skipping it for this cycle.",
+                    target,
+                    msg
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notify a single chat about the fetched issues that match its labels and
+    /// have not been notified yet. Returns `false` if the notification could
+    /// not be delivered, see `deliver`.
+    async fn notify_subscriber(
+        &self,
+        repo: &RepoEntity,
+        subscription: Subscription,
+        issues: &[issues::IssuesRepositoryIssuesNodes],
+    ) -> Result<bool> {
+        let Subscription { chat_id, tracked_labels, filters, since } = subscription;
+
+        // Get the issues this user has already been notified about
+        let notified_issues = self.storage.get_notified_issues(chat_id, repo).await?;
+
+        let matching_issues: Vec<_> = issues
+            .iter()
+            .filter(|issue| issue.has_any_label(&tracked_labels))
+            .filter(|issue| Self::passes_filters(issue, &filters))
+            .cloned()
+            .map(|mut issue| {
+                // Only show the labels the chat tracks
+                issue.retain_labels(&tracked_labels);
+                issue
+            })
+            .collect();
+        let unseen_issues = Self::filter_new_issues(matching_issues, &notified_issues);
+        let (new_issues, labeled_issues) =
+            Self::classify_issues(unseen_issues, &tracked_labels, since);
+
+        if new_issues.is_empty() && labeled_issues.is_empty() {
+            tracing::debug!(
+                "No new issues to notify for {} in chat {}",
+                repo.name_with_owner,
+                chat_id
+            );
+            return Ok(true);
+        }
+
+        // Record the issues in the ledger before sending, and only notify the ones
+        // this call recorded. A webhook and a poller pass racing for the same issue
+        // thus notify it once.
+        let issue_ids: Vec<String> =
+            new_issues.iter().chain(labeled_issues.iter()).map(|issue| issue.id.clone()).collect();
+        let marked: HashSet<String> = self
+            .storage
+            .mark_issues_notified(chat_id, repo, &issue_ids)
+            .await?
+            .into_iter()
+            .collect();
+        let new_issues: Vec<_> =
+            new_issues.into_iter().filter(|issue| marked.contains(&issue.id)).collect();
+        let labeled_issues: Vec<_> =
+            labeled_issues.into_iter().filter(|issue| marked.contains(&issue.id)).collect();
+
+        if new_issues.is_empty() && labeled_issues.is_empty() {
+            tracing::debug!(
+                "Issues for {} were already notified to chat {}",
+                repo.name_with_owner,
+                chat_id
+            );
+            return Ok(true);
+        }
+
+        tracing::debug!("Sending new issues message to chat: {chat_id}");
+        let notification = Notification::NewIssues {
+            repo_name_with_owner: repo.name_with_owner.clone(),
+            new_issues,
+            labeled_issues,
+        };
+        // Report a failed delivery, so the last poll time is not updated and the
+        // issues are notified again next cycle
+        match self.deliver(chat_id, notification).await {
+            Ok(true) => {}
+            undelivered => {
+                let marked: Vec<String> = marked.into_iter().collect();
+                self.unmark_undelivered(chat_id, repo, &marked).await;
+                return undelivered;
+            }
+        }
+
+        tracing::debug!("Sent notifications for repo {} in chat {}", repo.name_with_owner, chat_id);
+
+        Ok(true)
+    }
+
+    /// Deliver a notification to a chat: right away, with its next digest if
+    /// the chat prefers one, or from the outbox once the chat's quiet hours
+    /// end. A message that cannot be sent is retried from the outbox. Returns
+    /// `false` if adding it to the outbox failed as well, or the chat was
+    /// deactivated because it can no longer be reached.
+    async fn deliver(&self, chat_id: ChatId, notification: Notification) -> Result<bool> {
+        let settings = self.storage.get_user_settings(chat_id).await?;
+        if settings.delivery_mode.is_digest() {
+            self.storage.add_digest_entries(chat_id, &notification.into_digest_entries()).await?;
+            return Ok(true);
+        }
+
+        if let Some(quiet_until) = settings.quiet_until(Utc::now()) {
+            tracing::debug!("Holding back notification to chat {chat_id} until {quiet_until}");
+            return match self
+                .storage
+                .enqueue_notification(chat_id, &notification, quiet_until.timestamp(), 0)
+                .await
+            {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to hold back notification to chat {chat_id} during quiet hours: \
+                         {e:?}. Will be retried next cycle"
+                    );
+                    Ok(false)
+                }
+            };
+        }
+
+        match outbox::send_notification(
+            self.messaging_service.as_ref(),
+            chat_id,
+            notification.clone(),
+            &settings,
+            0,
+        )
+        .await
+        {
+            Ok(()) => Ok(true),
+            Err(e) if e.is_permanent_delivery_failure() => {
+                self.deactivate_chat(chat_id, &e).await;
+                Ok(false)
+            }
+            Err(e) => Ok(self.defer_notification(chat_id, &notification, &e).await),
+        }
+    }
+
+    /// Add a notification that could not be sent to the outbox, to be retried
+    /// with backoff. Returns `false` if that failed as well.
+    async fn defer_notification(
+        &self,
+        chat_id: ChatId,
+        notification: &Notification,
+        error: &MessagingError,
+    ) -> bool {
+        let next_retry_at =
+            Utc::now().timestamp().saturating_add_unsigned(outbox::retry_delay(0).as_secs());
+        match self
+            .storage
+            .enqueue_notification(chat_id, notification, next_retry_at, error.sent_parts())
+            .await
+        {
+            Ok(()) => {
+                tracing::warn!(
+                    "Failed to send notification to chat {chat_id}: {error:?}. Will be retried \
+                     from the outbox"
+                );
+                true
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to send notification to chat {chat_id}: {error:?}, and to add it to \
+                     the outbox: {e:?}. Will be retried next cycle"
+                );
+                false
+            }
+        }
+    }
+
+    /// Mark a chat that can no longer be reached as inactive, so it is not
+    /// polled anymore. Its subscriptions are kept for a grace period, in case
+    /// the user unblocks the bot.
+    async fn deactivate_chat(&self, chat_id: ChatId, error: &MessagingError) {
+        match self.storage.deactivate_chat(chat_id).await {
+            Ok(true) => {
+                tracing::info!("Chat {chat_id} can no longer be reached ({error}), deactivating it")
+            }
+            Ok(false) => {}
+            Err(e) => tracing::error!("Failed to deactivate chat {chat_id}: {e:?}"),
+        }
+    }
+
+    /// The start of the window issues are kept in the ledger of notified
+    /// issues for.
+    fn retention_start() -> DateTime<Utc> {
+        Utc::now() - NOTIFIED_ISSUE_RETENTION
+    }
+
+    /// Returns `false` if the chat asked to skip the issue because someone is
+    /// already working on it.
+    fn passes_filters(issue: &issues::IssuesRepositoryIssuesNodes, filters: &IssueFilters) -> bool {
+        !(filters.exclude_assigned && issue.is_assigned()
+            || filters.exclude_linked_prs && issue.has_open_linked_pr())
+    }
+
+    /// Keep only the issues that have not been notified to the user yet.
     fn filter_new_issues(
         issues: Vec<issues::IssuesRepositoryIssuesNodes>,
-        last_poll_time: &SystemTime,
+        notified_issues: &HashSet<String>,
     ) -> Vec<issues::IssuesRepositoryIssuesNodes> {
-        issues
-            .into_iter()
-            .filter(|issue| {
-                DateTime::parse_from_rfc3339(&issue.created_at)
-                    .map(|dt| SystemTime::from(dt) > *last_poll_time)
-                    .unwrap_or(false)
-            })
-            .collect()
+        issues.into_iter().filter(|issue| !notified_issues.contains(&issue.id)).collect()
+    }
+
+    /// Split issues into freshly opened ones and older ones that gained a
+    /// tracked label after `since`. Issues that are neither (e.g. an old issue
+    /// that only received a comment) are dropped. Without a previous poll every
+    /// issue counts as new. Issues opened and labeled before the retention
+    /// window of the ledger are dropped either way, as they may have been
+    /// notified and forgotten already.
+    fn classify_issues(
+        issues: Vec<issues::IssuesRepositoryIssuesNodes>,
+        tracked_labels: &HashSet<String>,
+        since: Option<DateTime<Utc>>,
+    ) -> (Vec<issues::IssuesRepositoryIssuesNodes>, Vec<issues::IssuesRepositoryIssuesNodes>) {
+        let retention_start = Self::retention_start();
+        let Some(since) = since else {
+            let new_issues = issues
+                .into_iter()
+                .filter(|issue| {
+                    let opened_before_retention = DateTime::parse_from_rfc3339(&issue.created_at)
+                        .is_ok_and(|dt| dt < retention_start);
+                    !opened_before_retention
+                        || issue
+                            .last_labeled_at(tracked_labels)
+                            .is_some_and(|dt| dt >= retention_start)
+                })
+                .collect();
+            return (new_issues, Vec::new());
+        };
+        let since = since.max(retention_start);
+
+        let mut new_issues = Vec::new();
+        let mut labeled_issues = Vec::new();
+
+        for issue in issues {
+            let created_after_since = DateTime::parse_from_rfc3339(&issue.created_at)
+                .map(|dt| dt >= since)
+                .unwrap_or(false);
+
+            if created_after_since {
+                new_issues.push(issue);
+            } else if issue.last_labeled_at(tracked_labels).is_some_and(|dt| dt >= since) {
+                labeled_issues.push(issue);
+            }
+        }
+
+        (new_issues, labeled_issues)
     }
 }
@@ -0,0 +1,175 @@
+//! Delivers the notifications that could not be sent right away, or were held
+//! back during a chat's quiet hours. They wait in a persistent outbox and
+//! failed ones are retried with exponential backoff; the ones that keep
+//! failing are moved to the dead-letter table and logged to `dlq_log`.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use teloxide::types::ChatId;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    messaging::{MessagingError, MessagingService},
+    storage::{Notification, PendingNotification, RepoStorage, StorageResult, UserSettings},
+};
+
+/// How often the outbox is checked for notifications that are due.
+const OUTBOX_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The maximum number of notifications delivered per check.
+const OUTBOX_BATCH_SIZE: u32 = 100;
+
+/// How many times delivering a notification from the outbox is attempted
+/// before it is moved to the dead-letter table.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// The time to wait before the next delivery attempt, after `attempts` failed
+/// ones. It doubles with every attempt, up to an hour.
+pub(super) fn retry_delay(attempts: u32) -> Duration {
+    INITIAL_RETRY_DELAY.saturating_mul(2u32.saturating_pow(attempts)).min(MAX_RETRY_DELAY)
+}
+
+/// Send a notification to a chat, as its settings ask for, skipping the
+/// `sent_parts` parts an earlier attempt delivered.
+pub(super) async fn send_notification(
+    messaging_service: &dyn MessagingService,
+    chat_id: ChatId,
+    notification: Notification,
+    settings: &UserSettings,
+    sent_parts: usize,
+) -> Result<(), MessagingError> {
+    match notification {
+        Notification::NewIssues { repo_name_with_owner, new_issues, labeled_issues } =>
+            messaging_service
+                .send_new_issues_msg(
+                    chat_id,
+                    &repo_name_with_owner,
+                    new_issues,
+                    labeled_issues,
+                    settings,
+                    sent_parts,
+                )
+                .await,
+        Notification::SearchIssues { filter, issues } =>
+            messaging_service
+                .send_search_issues_msg(chat_id, &filter, issues, settings, sent_parts)
+                .await,
+    }
+}
+
+/// Drains the notification outbox.
+pub struct OutboxWorker {
+    storage: Arc<dyn RepoStorage>,
+    messaging_service: Arc<dyn MessagingService>,
+}
+
+impl OutboxWorker {
+    /// Create a new `OutboxWorker`.
+    pub fn new(
+        storage: Arc<dyn RepoStorage>,
+        messaging_service: Arc<dyn MessagingService>,
+    ) -> Self {
+        Self { storage, messaging_service }
+    }
+
+    /// Deliver the notifications that are due until `shutdown` is cancelled.
+    pub async fn run(&self, shutdown: CancellationToken) {
+        tracing::debug!("Starting notification outbox worker");
+
+        let mut interval = tokio::time::interval(OUTBOX_INTERVAL);
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            if let Err(e) = self.drain().await {
+                tracing::error!("Failed to drain the notification outbox: {e:?}");
+            }
+        }
+
+        tracing::debug!("Notification outbox worker stopped");
+    }
+
+    /// Attempt to deliver every notification that is due. A notification that
+    /// fails on storage is logged and left for the next check, without holding
+    /// back the others.
+    pub(super) async fn drain(&self) -> StorageResult<()> {
+        let due =
+            self.storage.get_due_notifications(Utc::now().timestamp(), OUTBOX_BATCH_SIZE).await?;
+        if !due.is_empty() {
+            tracing::debug!("Delivering {} notifications from the outbox", due.len());
+        }
+
+        for pending in due {
+            let id = pending.id;
+            if let Err(e) = self.deliver(pending).await {
+                tracing::error!("Failed to deliver notification {id} from the outbox: {e:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Attempt to deliver a single notification. Failed attempts are retried
+    /// later, unless the chat can no longer be reached or the notification ran
+    /// out of attempts. During the chat's quiet hours, the notification waits
+    /// until they end.
+    async fn deliver(&self, pending: PendingNotification) -> StorageResult<()> {
+        let PendingNotification { id, chat_id, notification, attempts, sent_parts } = pending;
+
+        let settings = self.storage.get_user_settings(chat_id).await?;
+        if let Some(quiet_until) = settings.quiet_until(Utc::now()) {
+            return self.storage.postpone_notification(id, quiet_until.timestamp()).await;
+        }
+
+        let error = match send_notification(
+            self.messaging_service.as_ref(),
+            chat_id,
+            notification,
+            &settings,
+            sent_parts,
+        )
+        .await
+        {
+            Ok(()) => return self.storage.delete_notification(id).await,
+            Err(e) => e,
+        };
+
+        if error.is_permanent_delivery_failure() {
+            tracing::info!("Chat {chat_id} can no longer be reached ({error}), deactivating it");
+            self.storage.deactivate_chat(chat_id).await?;
+            return self
+                .storage
+                .dead_letter_notification(id, &format!("Chat unreachable: {error}"))
+                .await;
+        }
+
+        let attempts = attempts + 1;
+        if attempts < MAX_DELIVERY_ATTEMPTS {
+            let next_retry_at =
+                Utc::now().timestamp().saturating_add_unsigned(retry_delay(attempts).as_secs());
+            tracing::warn!(
+                "Failed to deliver notification {id} to chat {chat_id}, attempt {attempts} of \
+                 {MAX_DELIVERY_ATTEMPTS}: {error}"
+            );
+            return self
+                .storage
+                .reschedule_notification(id, next_retry_at, &error.to_string(), error.sent_parts())
+                .await;
+        }
+
+        tracing::error!(
+            target: "dlq_log",
+            "Notification {id} to chat {chat_id} failed after {attempts} attempts and was moved \
+             to the dead-letter queue: {error}"
+        );
+        self.storage.dead_letter_notification(id, &error.to_string()).await
+    }
+}
@@ -0,0 +1,180 @@
+//! Keeps the poller alive: failed runs are restarted with exponential backoff,
+//! and a configured admin chat is told when the poller gives up for good.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use backoff::{ExponentialBackoff, backoff::Backoff};
+use teloxide::types::ChatId;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use super::{GithubPoller, PollerError};
+use crate::{github::GithubError, messaging::MessagingService};
+
+/// A run lasting at least this long counts as healthy, and resets the restart
+/// count and backoff.
+const HEALTHY_RUN: Duration = Duration::from_secs(10 * 60);
+
+const DEFAULT_INITIAL_RESTART_DELAY: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_RESTART_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// The state of the supervised poller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PollerStatus {
+    /// The poller is polling normally.
+    Running,
+    /// The poller failed and is waiting to be restarted.
+    Degraded {
+        /// The number of restarts since the last healthy run.
+        restarts: u32,
+        /// The error the poller failed with.
+        last_error: String,
+    },
+    /// The poller is not running and will not be restarted. `last_error` is
+    /// `None` after a regular shutdown.
+    Stopped {
+        /// The error the poller failed with, if any.
+        last_error: Option<String>,
+    },
+}
+
+/// Supervises a `GithubPoller`, restarting it after failures.
+pub struct PollerSupervisor {
+    poller: GithubPoller,
+    messaging_service: Arc<dyn MessagingService>,
+    admin_chat_id: Option<ChatId>,
+    max_restarts: u32,
+    initial_restart_delay: Duration,
+    max_restart_delay: Duration,
+    status: watch::Sender<PollerStatus>,
+}
+
+impl PollerSupervisor {
+    /// Create a new `PollerSupervisor`. The poller is restarted up to
+    /// `max_restarts` times in a row before it is stopped, and
+    /// `admin_chat_id` is notified when that happens.
+    pub fn new(
+        poller: GithubPoller,
+        messaging_service: Arc<dyn MessagingService>,
+        admin_chat_id: Option<ChatId>,
+        max_restarts: u32,
+    ) -> Self {
+        Self {
+            poller,
+            messaging_service,
+            admin_chat_id,
+            max_restarts,
+            initial_restart_delay: DEFAULT_INITIAL_RESTART_DELAY,
+            max_restart_delay: DEFAULT_MAX_RESTART_DELAY,
+            status: watch::channel(PollerStatus::Running).0,
+        }
+    }
+
+    /// Set the delay before the first restart, and the upper bound the delay
+    /// grows to.
+    pub fn with_restart_delay(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_restart_delay = initial;
+        self.max_restart_delay = max;
+        self
+    }
+
+    /// Subscribe to the status of the poller.
+    pub fn status(&self) -> watch::Receiver<PollerStatus> {
+        self.status.subscribe()
+    }
+
+    /// Run the poller until `shutdown` is cancelled or it fails fatally.
+    pub async fn run(&self, shutdown: CancellationToken) {
+        let mut backoff = ExponentialBackoff {
+            initial_interval: self.initial_restart_delay,
+            max_interval: self.max_restart_delay,
+            max_elapsed_time: None,
+            ..Default::default()
+        };
+        let mut restarts = 0;
+
+        loop {
+            self.status.send_replace(PollerStatus::Running);
+            let started = Instant::now();
+
+            let error = match self.poller.run(shutdown.clone()).await {
+                Ok(()) => {
+                    self.status.send_replace(PollerStatus::Stopped { last_error: None });
+                    return;
+                }
+                Err(e) => e,
+            };
+            let last_error = describe(&error);
+            tracing::error!("Poller failed: {last_error}");
+
+            if shutdown.is_cancelled() {
+                self.status.send_replace(PollerStatus::Stopped { last_error: Some(last_error) });
+                return;
+            }
+
+            if started.elapsed() >= HEALTHY_RUN {
+                restarts = 0;
+                backoff.reset();
+            }
+
+            if is_fatal(&error) || restarts >= self.max_restarts {
+                self.stop(last_error).await;
+                return;
+            }
+
+            restarts += 1;
+            let delay = backoff.next_backoff().unwrap_or(self.max_restart_delay);
+            tracing::warn!(
+                "Restarting poller in {}s (restart {restarts}/{})",
+                delay.as_secs(),
+                self.max_restarts
+            );
+            self.status
+                .send_replace(PollerStatus::Degraded { restarts, last_error: last_error.clone() });
+
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    self.status.send_replace(PollerStatus::Stopped { last_error: Some(last_error) });
+                    return;
+                }
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    /// Give up on the poller and tell the admin chat about it.
+    async fn stop(&self, last_error: String) {
+        tracing::error!("Poller stopped and will not be restarted: {last_error}");
+
+        if let Some(chat_id) = self.admin_chat_id
+            && let Err(e) =
+                self.messaging_service.send_poller_stopped_msg(chat_id, &last_error).await
+        {
+            tracing::error!("Failed to notify admin chat {chat_id} about the poller: {e:?}");
+        }
+
+        self.status.send_replace(PollerStatus::Stopped { last_error: Some(last_error) });
+    }
+}
+
+/// Returns `true` if restarting the poller cannot help, e.g. because the GitHub
+/// token was revoked.
+fn is_fatal(error: &PollerError) -> bool {
+    matches!(error, PollerError::Github(GithubError::Unauthorized))
+}
+
+/// Describe an error together with its sources, e.g. "Failed to access
+/// storage: Database error: ...".
+fn describe(error: &PollerError) -> String {
+    let mut description = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        description.push_str(": ");
+        description.push_str(&e.to_string());
+        source = e.source();
+    }
+    description
+}
@@ -1,20 +1,25 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::LazyLock};
 
-use chrono::{DateTime, Utc};
 use mockall::predicate::*;
 
 use super::*;
 use crate::{
-    github::{GithubError, MockGithubClient},
+    github::{GithubError, MockGithubClient, OwnerRepos, SearchIssue},
     messaging::MockMessagingService,
-    storage::{MockRepoStorage, RepoEntity},
+    storage::{
+        DeliveryMode, DigestEntry, IssueFilters, MockRepoStorage, Notification, PendingDigest,
+        PendingNotification, QuietHours, RepoEntity, SearchFilter, SearchSubscription,
+        UserSettings,
+    },
 };
 
 const OWNER: &str = "owner";
 const REPO_NAME: &str = "repo";
 const REPO_NAME_WITH_OWNER: &str = "owner/repo";
 const CHAT_ID: ChatId = ChatId(123);
-const LAST_POLL_TIME: i64 = 1715817600_i64;
+// A day ago, so the polling window lies within the retention window of the
+// ledger
+static LAST_POLL_TIME: LazyLock<i64> = LazyLock::new(|| Utc::now().timestamp() - 24 * 60 * 60);
 
 // Helper for creating a default repo entity for tests
 fn default_repo_entity() -> RepoEntity {
@@ -29,67 +34,236 @@ fn default_tracked_labels() -> HashSet<String> {
     labels
 }
 
-fn last_poll_time_system_time() -> SystemTime {
-    SystemTime::UNIX_EPOCH + Duration::from_secs(LAST_POLL_TIME as u64)
+// Helper to build the label connection of an issue
+fn issue_labels(labels: &[&str]) -> Option<issues::IssuesRepositoryIssuesNodesLabels> {
+    Some(issues::IssuesRepositoryIssuesNodesLabels {
+        nodes: Some(
+            labels
+                .iter()
+                .map(|name| issues::IssuesRepositoryIssuesNodesLabelsNodes {
+                    name: name.to_string(),
+                    color: "d73a4a".to_string(),
+                })
+                .collect(),
+        ),
+    })
+}
+
+// Helper to create an issue with the given id, labeled with "bug"
+fn issue_with_id(id: &str) -> issues::IssuesRepositoryIssuesNodes {
+    issue_with_labels(id, &["bug"])
+}
+
+// Helper to create an issue with the given id and labels
+fn issue_with_labels(id: &str, labels: &[&str]) -> issues::IssuesRepositoryIssuesNodes {
+    issues::IssuesRepositoryIssuesNodes {
+        id: id.to_string(),
+        labels: issue_labels(labels),
+        ..Default::default()
+    }
+}
+
+fn last_poll_time() -> DateTime<Utc> {
+    DateTime::<Utc>::from_timestamp(*LAST_POLL_TIME, 0).unwrap()
+}
+
+// Helper to create an issue created at the given time, labeled with `label` at
+// `labeled_at`
+fn labeled_issue(
+    id: &str,
+    created_at: DateTime<Utc>,
+    label: &str,
+    labeled_at: DateTime<Utc>,
+) -> issues::IssuesRepositoryIssuesNodes {
+    let event = issues::IssuesRepositoryIssuesNodesTimelineItemsNodes::LabeledEvent(
+        issues::IssuesRepositoryIssuesNodesTimelineItemsNodesOnLabeledEvent {
+            created_at: labeled_at.to_rfc3339(),
+            label: issues::IssuesRepositoryIssuesNodesTimelineItemsNodesOnLabeledEventLabel {
+                name: label.to_string(),
+            },
+        },
+    );
+    issues::IssuesRepositoryIssuesNodes {
+        id: id.to_string(),
+        created_at: created_at.to_rfc3339(),
+        labels: issue_labels(&[label]),
+        timeline_items: issues::IssuesRepositoryIssuesNodesTimelineItems {
+            nodes: Some(vec![Some(event)]),
+        },
+        ..Default::default()
+    }
 }
 
 #[test]
 fn test_filter_new_issues() {
-    let last_poll_time_system_time = last_poll_time_system_time();
-    let before_last_poll_time = last_poll_time_system_time - Duration::from_secs(1);
-    let after_last_poll_time = last_poll_time_system_time + Duration::from_secs(1);
+    let issues = vec![issue_with_id("new_id"), issue_with_id("notified_id")];
+    let notified_issues = HashSet::from(["notified_id".to_string()]);
+
+    // Only the issue missing from the ledger should be included
+    let new_issues = GithubPoller::filter_new_issues(issues, &notified_issues);
+
+    assert_eq!(new_issues.len(), 1);
+    assert_eq!(new_issues[0].id, "new_id");
+}
+
+#[test]
+fn test_passes_filters() {
+    let unassigned = issue_with_id("unassigned");
+    let assigned = issues::IssuesRepositoryIssuesNodes {
+        assignees: issues::IssuesRepositoryIssuesNodesAssignees { total_count: 2 },
+        ..issue_with_id("assigned")
+    };
+    let exclude_assigned = IssueFilters { exclude_assigned: true, ..Default::default() };
+
+    assert!(GithubPoller::passes_filters(&assigned, &IssueFilters::default()));
+    assert!(GithubPoller::passes_filters(&unassigned, &exclude_assigned));
+    assert!(!GithubPoller::passes_filters(&assigned, &exclude_assigned));
+}
+
+#[test]
+fn test_classify_issues() {
+    let since = last_poll_time();
+    let before = since - chrono::Duration::hours(1);
+    let after = since + chrono::Duration::minutes(1);
+    let tracked_labels = default_tracked_labels();
 
     let issues = vec![
-        issues::IssuesRepositoryIssuesNodes {
-            created_at: DateTime::<Utc>::from(after_last_poll_time).to_rfc3339(),
-            ..Default::default()
-        },
-        issues::IssuesRepositoryIssuesNodes {
-            created_at: DateTime::<Utc>::from(before_last_poll_time).to_rfc3339(),
-            ..Default::default()
-        },
+        // Opened after the last poll
+        labeled_issue("opened", after, "bug", after),
+        // Opened long ago, labeled with a tracked label after the last poll
+        labeled_issue("labeled", before, "bug", after),
+        // Opened long ago, labeled with an untracked label after the last poll
+        labeled_issue("untracked_label", before, "question", after),
+        // Opened and labeled long ago, only updated after the last poll
+        labeled_issue("commented", before, "bug", before),
     ];
 
-    // Only 1 issue should be included
-    let new_issues = GithubPoller::filter_new_issues(issues.clone(), &last_poll_time_system_time);
+    let (new_issues, labeled_issues) =
+        GithubPoller::classify_issues(issues, &tracked_labels, Some(since));
+
+    assert_eq!(new_issues.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), ["opened"]);
+    assert_eq!(labeled_issues.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), ["labeled"]);
+}
+
+#[test]
+fn test_classify_issues_first_poll() {
+    let before = last_poll_time() - chrono::Duration::hours(1);
+    let issues = vec![labeled_issue("old", before, "bug", before)];
+
+    let (new_issues, labeled_issues) =
+        GithubPoller::classify_issues(issues, &default_tracked_labels(), None);
 
     assert_eq!(new_issues.len(), 1);
+    assert!(labeled_issues.is_empty());
+}
+
+#[test]
+fn test_classify_issues_skips_issues_before_retention() {
+    let stale = Utc::now() - NOTIFIED_ISSUE_RETENTION - chrono::Duration::days(1);
+    let recent = last_poll_time() + chrono::Duration::minutes(1);
+    let issues = vec![
+        // Opened and labeled before the ledger may have forgotten it
+        labeled_issue("forgotten", stale, "bug", stale),
+        // Opened long ago, labeled within the retention window
+        labeled_issue("relabeled", stale, "bug", recent),
+    ];
+
+    // Without a previous poll
+    let (new_issues, _) =
+        GithubPoller::classify_issues(issues.clone(), &default_tracked_labels(), None);
+    assert_eq!(new_issues.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), ["relabeled"]);
+
+    // With a previous poll older than the retention window
+    let (new_issues, labeled_issues) =
+        GithubPoller::classify_issues(issues, &default_tracked_labels(), Some(stale));
+    assert!(new_issues.is_empty());
+    assert_eq!(labeled_issues.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), ["relabeled"]);
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_new_issues() {
+async fn test_poll_repos_labeled_issue() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
     let mut mock_messaging_service = MockMessagingService::new();
 
-    let last_poll_time_system_time = last_poll_time_system_time();
-    let before_last_poll_time_system_time = last_poll_time_system_time - Duration::from_secs(1);
-    let after_last_poll_time_system_time = last_poll_time_system_time + Duration::from_secs(1);
+    let since = last_poll_time() - LAST_POLL_OVERLAP;
+    let issue = labeled_issue(
+        "labeled_id",
+        since - chrono::Duration::days(30),
+        "bug",
+        last_poll_time() + chrono::Duration::seconds(1),
+    );
+    let tracked_labels = default_tracked_labels();
 
-    // Create two issues: one new (after the last poll) and one old (before the last
-    // poll)
-    let issue_new = issues::IssuesRepositoryIssuesNodes {
-        created_at: DateTime::<Utc>::from(after_last_poll_time_system_time).to_rfc3339(),
-        ..Default::default()
-    };
-    let issue_old = issues::IssuesRepositoryIssuesNodes {
-        created_at: DateTime::<Utc>::from(before_last_poll_time_system_time).to_rfc3339(),
-        ..Default::default()
-    };
+    mock_repo_storage
+        .expect_get_tracked_labels()
+        .returning_st(move |_, _| Ok(tracked_labels.clone()));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(*LAST_POLL_TIME)));
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .withf(move |requests| requests.len() == 1 && requests[0].since == Some(since))
+        .times(1)
+        .returning_st(move |_| Ok(vec![Ok(vec![issue.clone()])]));
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(|_, _, new_issues, labeled_issues, _, _| {
+            new_issues.is_empty()
+                && labeled_issues.len() == 1
+                && labeled_issues[0].id == "labeled_id"
+        })
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .withf(|_, _, issue_ids| issue_ids == ["labeled_id".to_string()])
+        .times(1)
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage.expect_set_last_poll_time().times(1).returning_st(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_repos_new_issues() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    // Create two issues: one new and one already notified
+    let issue_new = issue_with_id("new_id");
+    let issue_old = issue_with_id("notified_id");
     let issues = vec![issue_new.clone(), issue_old.clone()];
-    let mut tracked_labels = HashSet::new();
-    tracked_labels.insert("label name".to_string());
+    let tracked_labels = default_tracked_labels();
     let tracked_labels_clone = tracked_labels.clone();
 
     mock_github_client
-        .expect_repo_issues_by_label()
-        .with(
-            eq(OWNER),
-            eq(REPO_NAME),
-            function(move |labels: &HashSet<String>| *labels == tracked_labels_clone),
-        )
-        .returning(move |_, _, _| Ok(issues.clone()));
+        .expect_repos_issues_by_label_batch()
+        .withf(move |requests| {
+            requests.len() == 1
+                && requests[0].owner == OWNER
+                && requests[0].name == REPO_NAME
+                && requests[0].labels == tracked_labels_clone
+        })
+        .returning(move |_| Ok(vec![Ok(issues.clone())]));
 
     mock_repo_storage
         .expect_get_tracked_labels()
@@ -98,22 +272,42 @@ async fn test_poll_user_repo_new_issues() {
         })
         .returning(move |_, _| Ok(tracked_labels.clone()));
 
+    mock_repo_storage
+        .expect_get_notified_issues()
+        .withf(|chat_id_param, repo| {
+            *chat_id_param == CHAT_ID && repo.name_with_owner == REPO_NAME_WITH_OWNER
+        })
+        .returning(move |_, _| Ok(HashSet::from(["notified_id".to_string()])));
+
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
     mock_repo_storage
         .expect_get_last_poll_time()
         .withf(|chat_id_param, repo| {
             *chat_id_param == CHAT_ID && repo.name_with_owner == REPO_NAME_WITH_OWNER
         })
-        .returning(move |_, _| Ok(Some(LAST_POLL_TIME)));
+        .returning(|_, _| Ok(None));
 
     mock_messaging_service
         .expect_send_new_issues_msg()
-        .withf(move |chat_id_param, repo_name_param, issues_list| {
+        .withf(move |chat_id_param, repo_name_param, new_issues, labeled_issues, _, _| {
             *chat_id_param == CHAT_ID
                 && repo_name_param == REPO_NAME_WITH_OWNER
-                && issues_list.len() == 1
-                && issues_list[0].created_at == issue_new.created_at
+                && new_issues.len() == 1
+                && new_issues[0].id == issue_new.id
+                && labeled_issues.is_empty()
         })
-        .returning(|_, _, _| Ok(()));
+        .returning(|_, _, _, _, _, _| Ok(()));
+
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .withf(|chat_id_param, repo, issue_ids| {
+            *chat_id_param == CHAT_ID
+                && repo.name_with_owner == REPO_NAME_WITH_OWNER
+                && issue_ids == ["new_id".to_string()]
+        })
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
 
     mock_repo_storage
         .expect_set_last_poll_time()
@@ -133,30 +327,22 @@ async fn test_poll_user_repo_new_issues() {
     let repo = RepoEntity::from_str(REPO_NAME_WITH_OWNER).unwrap();
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, repo).await;
+    let result = poller.poll_repos(vec![(repo, vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_ok());
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_no_issues() {
+async fn test_poll_repos_no_issues() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
     let mut mock_messaging_service = MockMessagingService::new();
 
-    let last_poll_time_system_time = last_poll_time_system_time();
-    let before_last_poll_time_system_time = last_poll_time_system_time - Duration::from_secs(1);
-
-    let issue_old = issues::IssuesRepositoryIssuesNodes {
-        created_at: DateTime::<Utc>::from(before_last_poll_time_system_time).to_rfc3339(),
-        ..Default::default()
-    };
-    // Create two issues: both are old (before the last poll)
-    let issues = vec![issue_old.clone(), issue_old];
-    let mut tracked_labels = HashSet::new();
-    tracked_labels.insert("label name".to_string());
+    // Create two issues: both have already been notified
+    let issues = vec![issue_with_id("notified_1"), issue_with_id("notified_2")];
+    let tracked_labels = default_tracked_labels();
     let labels_clone = tracked_labels.clone();
 
     mock_repo_storage
@@ -167,31 +353,38 @@ async fn test_poll_user_repo_no_issues() {
         .returning(move |_, _| Ok(tracked_labels.clone()));
 
     mock_github_client
-        .expect_repo_issues_by_label()
-        .with(
-            eq(OWNER),
-            eq(REPO_NAME),
-            function(move |labels: &HashSet<String>| *labels == labels_clone),
-        )
-        .returning(move |_, _, _| Ok(issues.clone()));
+        .expect_repos_issues_by_label_batch()
+        .withf(move |requests| {
+            requests.len() == 1
+                && requests[0].owner == OWNER
+                && requests[0].name == REPO_NAME
+                && requests[0].labels == labels_clone
+        })
+        .returning(move |_| Ok(vec![Ok(issues.clone())]));
 
     mock_repo_storage
-        .expect_get_last_poll_time()
+        .expect_get_notified_issues()
         .withf(|chat_id_param, repo| {
             *chat_id_param == CHAT_ID && repo.name_with_owner == REPO_NAME_WITH_OWNER
         })
-        .returning(move |_, _| Ok(Some(LAST_POLL_TIME)));
+        .returning(move |_, _| {
+            Ok(HashSet::from(["notified_1".to_string(), "notified_2".to_string()]))
+        });
 
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
     mock_repo_storage
-        .expect_set_last_poll_time()
+        .expect_get_last_poll_time()
         .withf(|chat_id_param, repo| {
             *chat_id_param == CHAT_ID && repo.name_with_owner == REPO_NAME_WITH_OWNER
         })
-        .returning(|_, _| Ok(()));
+        .returning(|_, _| Ok(None));
 
-    // Messaging service should not be called since there are no new issues
+    // Messaging service should not be called since there are no new issues, but the
+    // polling window still moves forward
     mock_messaging_service.expect_send_new_issues_msg().times(0);
-    mock_repo_storage.expect_set_last_poll_time().times(0);
+    mock_repo_storage.expect_mark_issues_notified().times(0);
+    mock_repo_storage.expect_set_last_poll_time().times(1).returning(|_, _| Ok(()));
 
     let poller = GithubPoller::new(
         Arc::new(mock_github_client),
@@ -204,14 +397,14 @@ async fn test_poll_user_repo_no_issues() {
     let repo = RepoEntity::from_str(REPO_NAME_WITH_OWNER).unwrap();
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, repo).await;
+    let result = poller.poll_repos(vec![(repo, vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_ok());
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_no_tracked_labels_skips() {
+async fn test_poll_repos_no_tracked_labels_skips() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new(); // Not called
     let mut mock_repo_storage = MockRepoStorage::new();
@@ -224,8 +417,48 @@ async fn test_poll_user_repo_no_tracked_labels_skips() {
         .returning(|_, _| Ok(HashSet::new())); // Return empty set
 
     // These should not be called if there are no tracked labels
+    mock_repo_storage.expect_get_notified_issues().times(0);
+    mock_repo_storage.expect_get_last_poll_time().times(0);
+    mock_github_client.expect_repos_issues_by_label_batch().times(0);
+    mock_messaging_service.expect_send_new_issues_msg().times(0);
+    mock_repo_storage.expect_set_last_poll_time().times(0);
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_repos_muted_repo_skips() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new(); // Not called
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging_service = MockMessagingService::new(); // Not called
+    let tracked_labels = default_tracked_labels();
+    let muted_until = Utc::now().timestamp() + 60;
+
+    mock_repo_storage
+        .expect_get_tracked_labels()
+        .returning_st(move |_, _| Ok(tracked_labels.clone()));
+    mock_repo_storage
+        .expect_get_muted_until()
+        .with(eq(CHAT_ID), eq(default_repo_entity()))
+        .times(1)
+        .returning(move |_, _| Ok(Some(muted_until)));
+
+    // Muted repositories are not fetched and their polling window stays put
     mock_repo_storage.expect_get_last_poll_time().times(0);
-    mock_github_client.expect_repo_issues_by_label().times(0);
+    mock_github_client.expect_repos_issues_by_label_batch().times(0);
     mock_messaging_service.expect_send_new_issues_msg().times(0);
     mock_repo_storage.expect_set_last_poll_time().times(0);
 
@@ -238,14 +471,14 @@ async fn test_poll_user_repo_no_tracked_labels_skips() {
     );
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, default_repo_entity()).await;
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_ok());
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_github_unauthorized_error() {
+async fn test_poll_repos_github_unauthorized_error() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
@@ -262,16 +495,21 @@ async fn test_poll_user_repo_github_unauthorized_error() {
         })
         .times(1);
 
+    // The ledger is only read once issues were fetched
+    mock_repo_storage.expect_get_notified_issues().times(0);
+
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
     mock_repo_storage
         .expect_get_last_poll_time()
         .with(eq(CHAT_ID), eq(default_repo_entity()))
-        .returning(|_, _| Ok(Some(LAST_POLL_TIME)))
+        .returning(|_, _| Ok(Some(*LAST_POLL_TIME)))
         .times(1);
 
     mock_github_client
-        .expect_repo_issues_by_label()
-        .with(eq(OWNER), eq(REPO_NAME), eq(tracked_labels.clone()))
-        .returning(|_, _, _| Err(GithubError::Unauthorized))
+        .expect_repos_issues_by_label_batch()
+        .withf(move |requests| requests.len() == 1 && requests[0].labels == tracked_labels)
+        .returning(|_| Err(GithubError::Unauthorized))
         .times(1);
 
     // No messaging or LPT update expected
@@ -287,7 +525,7 @@ async fn test_poll_user_repo_github_unauthorized_error() {
     );
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, default_repo_entity()).await;
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_err());
@@ -298,7 +536,7 @@ async fn test_poll_user_repo_github_unauthorized_error() {
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_github_rate_limited() {
+async fn test_poll_repos_github_rate_limited() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
@@ -309,13 +547,14 @@ async fn test_poll_user_repo_github_rate_limited() {
     mock_repo_storage
         .expect_get_tracked_labels()
         .returning_st(move |_, _| Ok(tracked_labels.clone()));
-    mock_repo_storage
-        .expect_get_last_poll_time()
-        .returning_st(move |_, _| Ok(Some(LAST_POLL_TIME)));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(*LAST_POLL_TIME)));
 
     mock_github_client
-        .expect_repo_issues_by_label()
-        .returning_st(|_, _, _| Err(GithubError::RateLimited));
+        .expect_repos_issues_by_label_batch()
+        .returning_st(|_| Err(GithubError::RateLimited));
 
     mock_messaging_service.expect_send_new_issues_msg().times(0);
     mock_repo_storage.expect_set_last_poll_time().times(0); // LPT not updated
@@ -329,14 +568,14 @@ async fn test_poll_user_repo_github_rate_limited() {
     );
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, default_repo_entity()).await;
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_ok()); // Non-fatal for this repo, logs and continues
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_github_graphql_error() {
+async fn test_poll_repos_github_graphql_error() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
@@ -348,12 +587,13 @@ async fn test_poll_user_repo_github_graphql_error() {
     mock_repo_storage
         .expect_get_tracked_labels()
         .returning_st(move |_, _| Ok(tracked_labels.clone()));
-    mock_repo_storage
-        .expect_get_last_poll_time()
-        .returning_st(move |_, _| Ok(Some(LAST_POLL_TIME)));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(*LAST_POLL_TIME)));
 
-    mock_github_client.expect_repo_issues_by_label().returning_st(|_, _, _| {
-        Err(GithubError::GraphQLApiError("Could not resolve to a Repository".to_string()))
+    mock_github_client.expect_repos_issues_by_label_batch().returning_st(|_| {
+        Ok(vec![Err(GithubError::GraphQLApiError("Could not resolve to a Repository".to_string()))])
     });
 
     mock_messaging_service.expect_send_new_issues_msg().times(0);
@@ -368,36 +608,41 @@ async fn test_poll_user_repo_github_graphql_error() {
     );
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, repo_entity).await;
+    let result = poller.poll_repos(vec![(repo_entity, vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_ok());
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_set_lpt_fails() {
+async fn test_poll_repos_set_lpt_fails() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
     let mut mock_messaging_service = MockMessagingService::new();
 
     let tracked_labels = default_tracked_labels();
-    let after_last_poll_time_system_time = last_poll_time_system_time() + Duration::from_secs(1);
-    let issue_new = issues::IssuesRepositoryIssuesNodes {
-        created_at: DateTime::<Utc>::from(after_last_poll_time_system_time).to_rfc3339(),
-        id: "new_id_lpt_fail".to_string(),
-        ..Default::default()
-    };
+    let issue_new = issue_with_id("new_id_lpt_fail");
     let issues_from_github = vec![issue_new.clone()];
 
     mock_repo_storage
         .expect_get_tracked_labels()
         .returning_st(move |_, _| Ok(tracked_labels.clone()));
-    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(LAST_POLL_TIME)));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(None));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
     mock_github_client
-        .expect_repo_issues_by_label()
-        .returning_st(move |_, _, _| Ok(issues_from_github.clone()));
-    mock_messaging_service.expect_send_new_issues_msg().returning_st(|_, _, _| Ok(())); // Message sent fine
+        .expect_repos_issues_by_label_batch()
+        .returning_st(move |_| Ok(vec![Ok(issues_from_github.clone())]));
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(())); // Message sent fine
 
     mock_repo_storage
         .expect_set_last_poll_time()
@@ -414,14 +659,14 @@ async fn test_poll_user_repo_set_lpt_fails() {
     );
 
     // Act
-    let result = poller.poll_user_repo(CHAT_ID, default_repo_entity()).await;
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
 
     // Assert
     assert!(result.is_ok()); // Non-fatal, logs error but continues
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_get_tracked_labels_storage_error() {
+async fn test_poll_repos_get_tracked_labels_storage_error() {
     let mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
     let mock_messaging_service = MockMessagingService::new();
@@ -439,7 +684,7 @@ async fn test_poll_user_repo_get_tracked_labels_storage_error() {
         10,
         10,
     );
-    let result = poller.poll_user_repo(CHAT_ID, default_repo_entity()).await;
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
 
     assert!(result.is_err());
     match result.unwrap_err() {
@@ -449,8 +694,8 @@ async fn test_poll_user_repo_get_tracked_labels_storage_error() {
 }
 
 #[tokio::test]
-async fn test_poll_user_repo_get_last_poll_time_storage_error() {
-    let mock_github_client = MockGithubClient::new();
+async fn test_poll_repos_get_notified_issues_storage_error() {
+    let mut mock_github_client = MockGithubClient::new();
     let mut mock_repo_storage = MockRepoStorage::new();
     let mock_messaging_service = MockMessagingService::new();
     let tracked_labels = default_tracked_labels();
@@ -458,11 +703,17 @@ async fn test_poll_user_repo_get_last_poll_time_storage_error() {
     mock_repo_storage
         .expect_get_tracked_labels()
         .returning_st(move |_, _| Ok(tracked_labels.clone()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(None));
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning_st(|_| Ok(vec![Ok(vec![issue_with_id("new_id")])]));
     mock_repo_storage
-        .expect_get_last_poll_time()
+        .expect_get_notified_issues()
         .with(eq(CHAT_ID), eq(default_repo_entity()))
         .times(1)
-        .returning_st(|_, _| Err(StorageError::DbError("LPT read fail".to_string())));
+        .returning_st(|_, _| Err(StorageError::DbError("Ledger read fail".to_string())));
 
     let poller = GithubPoller::new(
         Arc::new(mock_github_client),
@@ -471,11 +722,1451 @@ async fn test_poll_user_repo_get_last_poll_time_storage_error() {
         10,
         10,
     );
-    let result = poller.poll_user_repo(CHAT_ID, default_repo_entity()).await;
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
 
     assert!(result.is_err());
     match result.unwrap_err() {
-        PollerError::Storage(StorageError::DbError(msg)) if msg == "LPT read fail" => {}
+        PollerError::Storage(StorageError::DbError(msg)) if msg == "Ledger read fail" => {}
+        other => panic!("Expected PollerError::Storage(DbError(...)), got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_poll_repos_storage_error_does_not_hold_back_other_chats() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    const FAILING_CHAT_ID: ChatId = ChatId(1);
+    const OTHER_CHAT_ID: ChatId = ChatId(2);
+    let tracked_labels = default_tracked_labels();
+
+    mock_repo_storage
+        .expect_get_tracked_labels()
+        .returning_st(move |_, _| Ok(tracked_labels.clone()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(None));
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning_st(|_| Ok(vec![Ok(vec![issue_with_id("new_id")])]));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|chat_id, _| match chat_id {
+        FAILING_CHAT_ID => Err(StorageError::DbError("Ledger read fail".to_string())),
+        _ => Ok(HashSet::new()),
+    });
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(|chat_id, _, _, _, _, _| *chat_id == OTHER_CHAT_ID)
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    // The polling window of the failing chat stays where it was
+    mock_repo_storage
+        .expect_set_last_poll_time()
+        .withf(|chat_id, _| *chat_id == OTHER_CHAT_ID)
+        .times(1)
+        .returning_st(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller
+        .poll_repos(vec![(default_repo_entity(), vec![FAILING_CHAT_ID, OTHER_CHAT_ID])])
+        .await;
+
+    // Assert
+    match result {
+        Err(PollerError::Storage(StorageError::DbError(msg))) if msg == "Ledger read fail" => {}
+        other => panic!("Expected PollerError::Storage(DbError(...)), got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_poll_repos_mark_notified_fails() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    let tracked_labels = default_tracked_labels();
+    let issues_from_github = vec![issue_with_id("new_id")];
+
+    mock_repo_storage
+        .expect_get_tracked_labels()
+        .returning_st(move |_, _| Ok(tracked_labels.clone()));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(None));
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning_st(move |_| Ok(vec![Ok(issues_from_github.clone())]));
+    // Issues that could not be recorded are not sent, they are retried next cycle
+    mock_messaging_service.expect_send_new_issues_msg().never();
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning_st(|_, _, _| Err(StorageError::DbError("Ledger write fail".to_string())));
+    mock_repo_storage.expect_set_last_poll_time().never();
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    // Assert
+    match result {
+        Err(PollerError::Storage(StorageError::DbError(msg))) if msg == "Ledger write fail" => {}
         other => panic!("Expected PollerError::Storage(DbError(...)), got {:?}", other),
     }
 }
+
+#[tokio::test]
+async fn test_poll_repos_fetches_once_for_all_chats() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    const BUG_CHAT_ID: ChatId = ChatId(1);
+    const DOCS_CHAT_ID: ChatId = ChatId(2);
+    const IDLE_CHAT_ID: ChatId = ChatId(3);
+
+    mock_repo_storage.expect_get_tracked_labels().returning_st(|chat_id, _| {
+        Ok(match chat_id {
+            BUG_CHAT_ID => HashSet::from(["bug".to_string()]),
+            DOCS_CHAT_ID => HashSet::from(["docs".to_string()]),
+            _ => HashSet::new(),
+        })
+    });
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|chat_id, _| {
+        Ok(match chat_id {
+            BUG_CHAT_ID => Some(*LAST_POLL_TIME),
+            _ => None,
+        })
+    });
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+
+    // One request with the union of the labels, reaching back to the chat that was
+    // never polled
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .withf(|requests| {
+            requests.len() == 1
+                && requests[0].labels == HashSet::from(["bug".to_string(), "docs".to_string()])
+                && requests[0].since.is_none()
+        })
+        .times(1)
+        .returning_st(|_| {
+            let after = last_poll_time() + chrono::Duration::minutes(1);
+            Ok(vec![Ok(vec![
+                labeled_issue("bug_issue", after, "bug", after),
+                labeled_issue("docs_issue", after, "docs", after),
+            ])])
+        });
+
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(|chat_id, _, new_issues, _, _, _| {
+            let ids: Vec<_> = new_issues.iter().map(|i| i.id.as_str()).collect();
+            match *chat_id {
+                BUG_CHAT_ID => ids == ["bug_issue"],
+                DOCS_CHAT_ID => ids == ["docs_issue"],
+                _ => false,
+            }
+        })
+        .times(2)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(2)
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage
+        .expect_set_last_poll_time()
+        .withf(|chat_id, _| *chat_id != IDLE_CHAT_ID)
+        .times(2)
+        .returning_st(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller
+        .poll_repos(vec![(default_repo_entity(), vec![BUG_CHAT_ID, DOCS_CHAT_ID, IDLE_CHAT_ID])])
+        .await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_group_by_repo() {
+    let repo = default_repo_entity();
+    let other_repo = RepoEntity::from_str("owner/other").unwrap();
+    let repos_by_chat_id = HashMap::from([
+        (ChatId(1), HashSet::from([repo.clone(), other_repo.clone()])),
+        (ChatId(2), HashSet::from([repo.clone()])),
+    ]);
+
+    let chats_by_repo = GithubPoller::group_by_repo(repos_by_chat_id);
+
+    assert_eq!(chats_by_repo.len(), 2);
+    let mut chat_ids = chats_by_repo[&repo].clone();
+    chat_ids.sort_by_key(|chat_id| chat_id.0);
+    assert_eq!(chat_ids, [ChatId(1), ChatId(2)]);
+    assert_eq!(chats_by_repo[&other_repo], [ChatId(1)]);
+}
+
+#[tokio::test]
+async fn test_poll_repos_batches_repos_and_isolates_failures() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    let failing_repo = RepoEntity::from_str("owner/missing").unwrap();
+
+    mock_repo_storage.expect_get_tracked_labels().returning_st(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(None));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+
+    // Both repositories are fetched with a single call, the failing one only fails
+    // its own entry
+    mock_github_client.expect_repos_issues_by_label_batch().times(1).returning_st(|requests| {
+        Ok(requests
+            .iter()
+            .map(|request| match request.name.as_str() {
+                REPO_NAME => Ok(vec![issue_with_id("new_id")]),
+                _ => Err(GithubError::GraphQLApiError("NOT_FOUND".to_string())),
+            })
+            .collect())
+    });
+
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(|_, repo_name, _, _, _, _| repo_name == REPO_NAME_WITH_OWNER)
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage
+        .expect_set_last_poll_time()
+        .withf(|_, repo| repo.name_with_owner == REPO_NAME_WITH_OWNER)
+        .times(1)
+        .returning_st(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller
+        .poll_repos(vec![(failing_repo, vec![CHAT_ID]), (default_repo_entity(), vec![CHAT_ID])])
+        .await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_handle_pushed_issue_notifies_subscribers() {
+    // Arrange
+    let mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    let after = last_poll_time() + chrono::Duration::minutes(1);
+    let issue = labeled_issue("pushed_id", after, "bug", after);
+
+    mock_repo_storage
+        .expect_get_repo_subscribers()
+        .with(eq(REPO_NAME_WITH_OWNER))
+        .times(1)
+        .returning_st(|_| Ok(vec![(CHAT_ID, default_repo_entity())]));
+    mock_repo_storage.expect_get_owner_subscribers().with(eq(OWNER)).returning(|_| Ok(vec![]));
+    mock_repo_storage.expect_get_tracked_labels().returning_st(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(*LAST_POLL_TIME)));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(|chat_id, _, new_issues, _, _, _| {
+            *chat_id == CHAT_ID && new_issues[0].id == "pushed_id"
+        })
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .withf(|_, _, issue_ids| issue_ids == ["pushed_id".to_string()])
+        .times(1)
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    // Pushed issues do not move the polling window
+    mock_repo_storage.expect_set_last_poll_time().times(0);
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller.handle_pushed_issue(REPO_NAME_WITH_OWNER, issue).await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_handle_pushed_issue_notifies_owner_subscribers() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging_service = MockMessagingService::new();
+    let owner_chat = ChatId(456);
+
+    let after = last_poll_time() + chrono::Duration::minutes(1);
+    let issue = labeled_issue("pushed_id", after, "bug", after);
+
+    // One chat tracks the repository directly and through its owner, the other
+    // only through the owner
+    mock_repo_storage
+        .expect_get_repo_subscribers()
+        .returning_st(|_| Ok(vec![(CHAT_ID, default_repo_entity())]));
+    mock_repo_storage
+        .expect_get_owner_subscribers()
+        .with(eq(OWNER))
+        .times(1)
+        .returning(move |_| Ok(vec![CHAT_ID, owner_chat]));
+    mock_github_client
+        .expect_owner_repositories()
+        .withf(|login| login == OWNER)
+        .times(1)
+        .returning(|_| {
+            Ok(Some(OwnerRepos {
+                login: OWNER.to_string(),
+                repos: vec![REPO_NAME_WITH_OWNER.to_string()],
+            }))
+        });
+    mock_repo_storage.expect_get_tracked_labels().returning_st(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(*LAST_POLL_TIME)));
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(2)
+        .returning_st(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    // Each chat is notified once
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(|&chat_id, _, _, _, _, _| chat_id == CHAT_ID)
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+    mock_messaging_service
+        .expect_send_new_issues_msg()
+        .withf(move |&chat_id, repo_name, _, _, _, _| {
+            chat_id == owner_chat && repo_name == REPO_NAME_WITH_OWNER
+        })
+        .times(1)
+        .returning_st(|_, _, _, _, _, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller.handle_pushed_issue(REPO_NAME_WITH_OWNER, issue).await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_handle_pushed_issue_skips_issue_marked_concurrently() {
+    // Arrange
+    let mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging_service = MockMessagingService::new();
+
+    let after = last_poll_time() + chrono::Duration::minutes(1);
+    let issue = labeled_issue("pushed_id", after, "bug", after);
+
+    mock_repo_storage
+        .expect_get_repo_subscribers()
+        .returning_st(|_| Ok(vec![(CHAT_ID, default_repo_entity())]));
+    mock_repo_storage.expect_get_owner_subscribers().returning(|_| Ok(vec![]));
+    mock_repo_storage.expect_get_tracked_labels().returning_st(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning_st(|_, _| Ok(Some(*LAST_POLL_TIME)));
+    // The ledger was read before a poller pass recorded the issue
+    mock_repo_storage.expect_get_notified_issues().returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_mark_issues_notified().times(1).returning_st(|_, _, _| Ok(vec![]));
+    mock_messaging_service.expect_send_new_issues_msg().never();
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging_service),
+        10,
+        10,
+    );
+
+    // Act
+    let result = poller.handle_pushed_issue(REPO_NAME_WITH_OWNER, issue).await;
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_run_stops_when_cancelled() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_all_repos().times(0);
+
+    let poller = GithubPoller::new(
+        Arc::new(MockGithubClient::new()),
+        Arc::new(mock_repo_storage),
+        Arc::new(MockMessagingService::new()),
+        10,
+        10,
+    );
+    let shutdown = CancellationToken::new();
+    shutdown.cancel();
+
+    let result = poller.run(shutdown).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_run_finishes_cycle_in_flight_on_shutdown() {
+    let shutdown = CancellationToken::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+
+    // Shutdown is requested while the first cycle is running; the cycle completes
+    // and no other one is started
+    let token = shutdown.clone();
+    mock_repo_storage.expect_get_all_repos().times(1).returning_st(move || {
+        token.cancel();
+        Ok(HashMap::from([(CHAT_ID, HashSet::from([default_repo_entity()]))]))
+    });
+    mock_repo_storage
+        .expect_get_all_owner_subscriptions()
+        .times(1)
+        .returning(|| Ok(HashMap::new()));
+    mock_repo_storage.expect_get_tracked_labels().times(1).returning_st(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_all_search_subscriptions().times(1).returning(|| Ok(Vec::new()));
+
+    let poller = GithubPoller::new(
+        Arc::new(MockGithubClient::new()),
+        Arc::new(mock_repo_storage),
+        Arc::new(MockMessagingService::new()),
+        10,
+        10,
+    );
+
+    let result = poller.run(shutdown).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_expand_owner_subscriptions() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let other_chat = ChatId(456);
+
+    // Both chats track the owner, one also tracks one of its repositories directly
+    mock_repo_storage.expect_get_all_owner_subscriptions().times(2).returning(move || {
+        Ok(HashMap::from([
+            (CHAT_ID, HashSet::from([OWNER.to_string()])),
+            (other_chat, HashSet::from(["OWNER".to_string()])),
+        ]))
+    });
+    // The repositories are listed once and reused by the next cycle
+    mock_github_client
+        .expect_owner_repositories()
+        .withf(|login| login.eq_ignore_ascii_case(OWNER))
+        .times(1)
+        .returning(|_| {
+            Ok(Some(OwnerRepos {
+                login: OWNER.to_string(),
+                repos: vec![REPO_NAME_WITH_OWNER.to_string(), "owner/other".to_string()],
+            }))
+        });
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(MockMessagingService::new()),
+        10,
+        10,
+    );
+
+    let owner_repos =
+        HashSet::from([default_repo_entity(), RepoEntity::from_str("owner/other").unwrap()]);
+    for _ in 0..2 {
+        let mut repos_by_chat_id =
+            HashMap::from([(CHAT_ID, HashSet::from([default_repo_entity()]))]);
+
+        poller.expand_owner_subscriptions(&mut repos_by_chat_id).await.unwrap();
+
+        assert_eq!(repos_by_chat_id[&CHAT_ID], owner_repos);
+        assert_eq!(repos_by_chat_id[&other_chat], owner_repos);
+    }
+}
+
+#[tokio::test]
+async fn test_expand_owner_subscriptions_github_errors() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+
+    mock_repo_storage
+        .expect_get_all_owner_subscriptions()
+        .returning(|| Ok(HashMap::from([(CHAT_ID, HashSet::from([OWNER.to_string()]))])));
+    mock_github_client
+        .expect_owner_repositories()
+        .times(1)
+        .returning(|_| Err(GithubError::RateLimited));
+    mock_github_client
+        .expect_owner_repositories()
+        .times(1)
+        .returning(|_| Err(GithubError::Unauthorized));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(MockMessagingService::new()),
+        10,
+        10,
+    );
+
+    // A transient error skips the owner for this cycle
+    let mut repos_by_chat_id = HashMap::new();
+    poller.expand_owner_subscriptions(&mut repos_by_chat_id).await.unwrap();
+    assert!(repos_by_chat_id[&CHAT_ID].is_empty());
+
+    // A fatal one stops the poller
+    let result = poller.expand_owner_subscriptions(&mut HashMap::new()).await;
+    assert!(matches!(result, Err(PollerError::Github(GithubError::Unauthorized))));
+}
+
+// Helper to create a search result opened at the given time
+fn search_issue(id: &str, repo: &str, stars: i64, created_at: DateTime<Utc>) -> SearchIssue {
+    SearchIssue {
+        repo_name_with_owner: repo.to_string(),
+        repo_stars: stars,
+        repo_topics: vec!["cli".to_string()],
+        issue: issues::IssuesRepositoryIssuesNodes {
+            id: id.to_string(),
+            created_at: created_at.to_rfc3339(),
+            ..Default::default()
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_poll_searches() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging = MockMessagingService::new();
+    let filter = SearchFilter { min_stars: Some(100), ..Default::default() };
+    let subscription = SearchSubscription {
+        id: 1,
+        chat_id: CHAT_ID,
+        filter: filter.clone(),
+        created_at: *LAST_POLL_TIME,
+        last_poll_time: *LAST_POLL_TIME,
+    };
+
+    mock_repo_storage
+        .expect_get_all_search_subscriptions()
+        .times(1)
+        .returning(move || Ok(vec![subscription.clone()]));
+    let query = filter.query();
+    mock_github_client
+        .expect_search_issues()
+        .withf(move |q, since| q == query && *since == Some(last_poll_time() - LAST_POLL_OVERLAP))
+        .times(1)
+        .returning(|_, _| {
+            let after = last_poll_time() + chrono::Duration::hours(1);
+            let before = last_poll_time() - chrono::Duration::hours(1);
+            Ok(vec![
+                search_issue("new", REPO_NAME_WITH_OWNER, 150, after),
+                search_issue("notified", REPO_NAME_WITH_OWNER, 150, after),
+                search_issue("too-few-stars", "owner/small", 10, after),
+                search_issue("before-subscribing", REPO_NAME_WITH_OWNER, 150, before),
+            ])
+        });
+    mock_repo_storage
+        .expect_get_notified_issues()
+        .with(eq(CHAT_ID), eq(default_repo_entity()))
+        .times(1)
+        .returning(|_, _| Ok(HashSet::from(["notified".to_string()])));
+    mock_messaging
+        .expect_send_search_issues_msg()
+        .withf(|&chat_id, _, issues, _, _| {
+            chat_id == CHAT_ID && issues.len() == 1 && issues[0].issue.id == "new"
+        })
+        .times(1)
+        .returning(|_, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .with(eq(CHAT_ID), eq(default_repo_entity()), eq(vec!["new".to_string()]))
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage.expect_set_search_poll_time().with(eq(1)).times(1).returning(|_| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_searches().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_searches_skips_pruned_issue_updated_later() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+    let opened = Utc::now() - NOTIFIED_ISSUE_RETENTION - chrono::Duration::days(1);
+    let subscription = SearchSubscription {
+        id: 1,
+        chat_id: CHAT_ID,
+        filter: SearchFilter::default(),
+        created_at: (opened - chrono::Duration::days(1)).timestamp(),
+        last_poll_time: *LAST_POLL_TIME,
+    };
+
+    mock_repo_storage
+        .expect_get_all_search_subscriptions()
+        .returning(move || Ok(vec![subscription.clone()]));
+    // The issue was notified once, got pruned from the ledger and was updated
+    // since the last poll
+    mock_github_client
+        .expect_search_issues()
+        .times(1)
+        .returning(move |_, _| Ok(vec![search_issue("pruned", REPO_NAME_WITH_OWNER, 0, opened)]));
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_mark_issues_notified().never();
+    mock_messaging.expect_send_search_issues_msg().never();
+    mock_repo_storage.expect_set_search_poll_time().with(eq(1)).times(1).returning(|_| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_searches().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_searches_only_sends_results_it_marked() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging = MockMessagingService::new();
+    let subscription = SearchSubscription {
+        id: 1,
+        chat_id: CHAT_ID,
+        filter: SearchFilter::default(),
+        created_at: *LAST_POLL_TIME,
+        last_poll_time: *LAST_POLL_TIME,
+    };
+
+    mock_repo_storage
+        .expect_get_all_search_subscriptions()
+        .returning(move || Ok(vec![subscription.clone()]));
+    mock_github_client.expect_search_issues().times(1).returning(|_, _| {
+        let after = last_poll_time() + chrono::Duration::hours(1);
+        Ok(vec![
+            search_issue("first", REPO_NAME_WITH_OWNER, 0, after),
+            search_issue("pushed", REPO_NAME_WITH_OWNER, 0, after),
+        ])
+    });
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    // A webhook recorded one of the results after the ledger was read
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .withf(|_, _, issue_ids| issue_ids.len() == 2)
+        .times(1)
+        .returning(|_, _, _| Ok(vec!["first".to_string()]));
+    mock_messaging
+        .expect_send_search_issues_msg()
+        .withf(|_, _, issues, _, _| issues.len() == 1 && issues[0].issue.id == "first")
+        .times(1)
+        .returning(|_, _, _, _, _| Ok(()));
+    mock_repo_storage.expect_set_search_poll_time().with(eq(1)).times(1).returning(|_| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_searches().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_searches_keeps_poll_time_when_sending_and_queueing_fail() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging = MockMessagingService::new();
+    let subscription = SearchSubscription {
+        id: 1,
+        chat_id: CHAT_ID,
+        filter: SearchFilter::default(),
+        created_at: *LAST_POLL_TIME,
+        last_poll_time: *LAST_POLL_TIME,
+    };
+
+    mock_repo_storage
+        .expect_get_all_search_subscriptions()
+        .returning(move || Ok(vec![subscription.clone()]));
+    mock_github_client.expect_search_issues().times(1).returning(|_, _| {
+        let after = last_poll_time() + chrono::Duration::hours(1);
+        Ok(vec![search_issue("new", REPO_NAME_WITH_OWNER, 0, after)])
+    });
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    mock_messaging.expect_send_search_issues_msg().times(1).returning(|_, _, _, _, _| {
+        Err(MessagingError::TeloxideRequest(teloxide::RequestError::Io(
+            std::io::Error::other("network down").into(),
+        )))
+    });
+    mock_repo_storage
+        .expect_enqueue_notification()
+        .times(1)
+        .returning(|_, _, _, _| Err(StorageError::DbError("database is locked".to_string())));
+    // The results are released from the ledger and sent again next cycle
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage
+        .expect_unmark_issues_notified()
+        .with(eq(CHAT_ID), eq(default_repo_entity()), eq(vec!["new".to_string()]))
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+    mock_repo_storage.expect_set_search_poll_time().never();
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_searches().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_repos_queues_failed_notification() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning(|_| Ok(vec![Ok(vec![issue_with_id("new_id")])]));
+    mock_repo_storage.expect_get_tracked_labels().returning(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    // Only the first part of the message went out
+    mock_messaging.expect_send_new_issues_msg().times(1).returning(|_, _, _, _, _, _| {
+        Err(MessagingError::PartiallySent { sent_parts: 1, source: network_error() })
+    });
+    let now = Utc::now().timestamp();
+    mock_repo_storage
+        .expect_enqueue_notification()
+        .withf(move |chat_id, notification, &next_retry_at, &sent_parts| {
+            *chat_id == CHAT_ID
+                && next_retry_at > now
+                && sent_parts == 1
+                && matches!(
+                    notification,
+                    Notification::NewIssues { repo_name_with_owner, new_issues, .. }
+                        if repo_name_with_owner == REPO_NAME_WITH_OWNER
+                            && new_issues.len() == 1
+                )
+        })
+        .times(1)
+        .returning(|_, _, _, _| Ok(()));
+    // The outbox takes over, so the issues are not derived again next cycle
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage.expect_set_last_poll_time().times(1).returning(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_repos_deactivates_blocked_chat() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning(|_| Ok(vec![Ok(vec![issue_with_id("new_id")])]));
+    mock_repo_storage.expect_get_tracked_labels().returning(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    mock_messaging.expect_send_new_issues_msg().times(1).returning(|_, _, _, _, _, _| {
+        Err(MessagingError::TeloxideRequest(teloxide::RequestError::Api(
+            teloxide::ApiError::BotBlocked,
+        )))
+    });
+    mock_repo_storage.expect_deactivate_chat().with(eq(CHAT_ID)).times(1).returning(|_| Ok(true));
+    // Nothing stays recorded for a chat that is no longer polled
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage
+        .expect_unmark_issues_notified()
+        .withf(|&chat_id, _, issue_ids| chat_id == CHAT_ID && issue_ids == ["new_id".to_string()])
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+    mock_repo_storage.expect_set_last_poll_time().never();
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_repos_adds_issues_to_digest() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning(|_| Ok(vec![Ok(vec![issue_with_id("new_id")])]));
+    mock_repo_storage.expect_get_tracked_labels().returning(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_user_settings().returning(|_| {
+        Ok(UserSettings { delivery_mode: DeliveryMode::Hourly, ..Default::default() })
+    });
+    mock_repo_storage
+        .expect_add_digest_entries()
+        .withf(|&chat_id, entries| {
+            chat_id == CHAT_ID
+                && entries.len() == 1
+                && entries[0].repo_name_with_owner == REPO_NAME_WITH_OWNER
+                && entries[0].issue.id == "new_id"
+        })
+        .times(1)
+        .returning(|_, _| Ok(()));
+    // The issue waits for the digest instead of being sent right away
+    mock_messaging.expect_send_new_issues_msg().never();
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage.expect_set_last_poll_time().times(1).returning(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_poll_repos_queues_notification_in_quiet_hours() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .returning(|_| Ok(vec![Ok(vec![issue_with_id("new_id")])]));
+    mock_repo_storage.expect_get_tracked_labels().returning(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_notified_issues().returning(|_, _| Ok(HashSet::new()));
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(quiet_settings()));
+    // The notification waits in the outbox until the quiet hours end
+    mock_messaging.expect_send_new_issues_msg().never();
+    let now = Utc::now().timestamp();
+    mock_repo_storage
+        .expect_enqueue_notification()
+        .withf(move |&chat_id, _, &next_retry_at, _| chat_id == CHAT_ID && next_retry_at > now)
+        .times(1)
+        .returning(|_, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_mark_issues_notified()
+        .times(1)
+        .returning(|_, _, issue_ids| Ok(issue_ids.to_vec()));
+    mock_repo_storage.expect_set_last_poll_time().times(1).returning(|_, _| Ok(()));
+
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_messaging),
+        10,
+        10,
+    );
+
+    let result = poller.poll_repos(vec![(default_repo_entity(), vec![CHAT_ID])]).await;
+
+    assert!(result.is_ok());
+}
+
+// Helper to supervise a poller with restart delays short enough for tests
+fn supervisor(
+    mock_github_client: MockGithubClient,
+    mock_repo_storage: MockRepoStorage,
+    mock_messaging: MockMessagingService,
+    max_restarts: u32,
+) -> PollerSupervisor {
+    let messaging: Arc<dyn MessagingService> = Arc::new(mock_messaging);
+    let poller = GithubPoller::new(
+        Arc::new(mock_github_client),
+        Arc::new(mock_repo_storage),
+        messaging.clone(),
+        10,
+        10,
+    );
+    PollerSupervisor::new(poller, messaging, Some(CHAT_ID), max_restarts)
+        .with_restart_delay(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+#[tokio::test]
+async fn test_supervisor_restarts_until_max_restarts() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    // The first run and two restarts fail, then the supervisor gives up
+    mock_repo_storage
+        .expect_get_all_repos()
+        .times(3)
+        .returning(|| Err(StorageError::DbError("database is locked".to_string())));
+    mock_messaging
+        .expect_send_poller_stopped_msg()
+        .with(eq(CHAT_ID), eq("Failed to access storage: Database error: database is locked"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let supervisor = supervisor(MockGithubClient::new(), mock_repo_storage, mock_messaging, 2);
+    let status = supervisor.status();
+
+    supervisor.run(CancellationToken::new()).await;
+
+    assert_eq!(
+        *status.borrow(),
+        PollerStatus::Stopped {
+            last_error: Some(
+                "Failed to access storage: Database error: database is locked".to_string()
+            )
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_supervisor_stops_on_unauthorized() {
+    let mut mock_github_client = MockGithubClient::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_repo_storage
+        .expect_get_all_repos()
+        .times(1)
+        .returning(|| Ok(HashMap::from([(CHAT_ID, HashSet::from([default_repo_entity()]))])));
+    mock_repo_storage
+        .expect_get_all_owner_subscriptions()
+        .times(1)
+        .returning(|| Ok(HashMap::new()));
+    mock_repo_storage
+        .expect_get_tracked_labels()
+        .times(1)
+        .returning(|_, _| Ok(default_tracked_labels()));
+    mock_repo_storage.expect_get_muted_until().returning(|_, _| Ok(None));
+    mock_repo_storage.expect_get_issue_filters().returning(|_, _| Ok(IssueFilters::default()));
+    mock_repo_storage.expect_get_last_poll_time().times(1).returning(|_, _| Ok(None));
+    mock_github_client
+        .expect_repos_issues_by_label_batch()
+        .times(1)
+        .returning(|_| Err(GithubError::Unauthorized));
+    mock_messaging.expect_send_poller_stopped_msg().times(1).returning(|_, _| Ok(()));
+
+    // Restarting does not help with a bad token, so no restart is attempted
+    let supervisor = supervisor(mock_github_client, mock_repo_storage, mock_messaging, 5);
+    let status = supervisor.status();
+
+    supervisor.run(CancellationToken::new()).await;
+
+    assert_eq!(
+        *status.borrow(),
+        PollerStatus::Stopped {
+            last_error: Some(
+                "Failed to poll GitHub issues: GitHub authentication failed".to_string()
+            )
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_supervisor_is_degraded_while_waiting_to_restart() {
+    let shutdown = CancellationToken::new();
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_repo_storage
+        .expect_get_all_repos()
+        .times(1)
+        .returning(|| Err(StorageError::DbError("database is locked".to_string())));
+    mock_messaging.expect_send_poller_stopped_msg().times(0);
+
+    let supervisor = supervisor(MockGithubClient::new(), mock_repo_storage, mock_messaging, 5)
+        .with_restart_delay(Duration::from_secs(60), Duration::from_secs(60));
+    let mut status = supervisor.status();
+
+    let run = supervisor.run(shutdown.clone());
+    let observe = async {
+        status.wait_for(|s| matches!(s, PollerStatus::Degraded { restarts: 1, .. })).await.unwrap();
+        shutdown.cancel();
+    };
+    tokio::join!(run, observe);
+
+    // Shutdown during the restart delay stops the poller without alerting
+    assert_eq!(
+        *supervisor.status().borrow(),
+        PollerStatus::Stopped {
+            last_error: Some(
+                "Failed to access storage: Database error: database is locked".to_string()
+            )
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_supervisor_stops_cleanly_on_shutdown() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_all_repos().times(0);
+
+    let supervisor =
+        supervisor(MockGithubClient::new(), mock_repo_storage, MockMessagingService::new(), 5);
+    let shutdown = CancellationToken::new();
+    shutdown.cancel();
+
+    supervisor.run(shutdown).await;
+
+    assert_eq!(*supervisor.status().borrow(), PollerStatus::Stopped { last_error: None });
+}
+
+// Helper to create a notification waiting in the outbox
+fn pending_notification(attempts: u32) -> PendingNotification {
+    PendingNotification {
+        id: 1,
+        chat_id: CHAT_ID,
+        notification: Notification::NewIssues {
+            repo_name_with_owner: REPO_NAME_WITH_OWNER.to_string(),
+            new_issues: vec![issue_with_id("new_id")],
+            labeled_issues: Vec::new(),
+        },
+        attempts,
+        sent_parts: 0,
+    }
+}
+
+// Helper to make sending new issues fail with the given error
+fn failing_messaging(error: fn() -> teloxide::RequestError) -> MockMessagingService {
+    let mut mock_messaging = MockMessagingService::new();
+    mock_messaging
+        .expect_send_new_issues_msg()
+        .times(1)
+        .returning(move |_, _, _, _, _, _| Err(MessagingError::TeloxideRequest(error())));
+    mock_messaging
+}
+
+fn network_error() -> teloxide::RequestError {
+    teloxide::RequestError::Io(std::io::Error::other("network down").into())
+}
+
+#[tokio::test]
+async fn test_outbox_deletes_delivered_notification() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    mock_repo_storage
+        .expect_get_due_notifications()
+        .returning(|_, _| Ok(vec![pending_notification(0)]));
+    mock_messaging
+        .expect_send_new_issues_msg()
+        .withf(|chat_id, repo_name, new_issues, _, _, _| {
+            *chat_id == CHAT_ID && repo_name == REPO_NAME_WITH_OWNER && new_issues.len() == 1
+        })
+        .times(1)
+        .returning(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage.expect_delete_notification().with(eq(1)).times(1).returning(|_| Ok(()));
+
+    let worker = OutboxWorker::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(worker.drain().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_outbox_reschedules_failed_notification_with_backoff() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    mock_repo_storage
+        .expect_get_due_notifications()
+        .returning(|_, _| Ok(vec![pending_notification(2)]));
+    let now = Utc::now().timestamp();
+    mock_repo_storage
+        .expect_reschedule_notification()
+        .withf(move |&id, &next_retry_at, _, _| {
+            // The third failed attempt waits four times the initial delay
+            let delay = outbox::retry_delay(3).as_secs() as i64;
+            id == 1 && (now + delay..=now + delay + 1).contains(&next_retry_at)
+        })
+        .times(1)
+        .returning(|_, _, _, _| Ok(()));
+    mock_repo_storage.expect_dead_letter_notification().never();
+
+    let worker =
+        OutboxWorker::new(Arc::new(mock_repo_storage), Arc::new(failing_messaging(network_error)));
+
+    assert!(worker.drain().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_outbox_dead_letters_notification_out_of_attempts() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    mock_repo_storage
+        .expect_get_due_notifications()
+        .returning(|_, _| Ok(vec![pending_notification(4)]));
+    mock_repo_storage.expect_reschedule_notification().never();
+    mock_repo_storage
+        .expect_dead_letter_notification()
+        .withf(|&id, error| id == 1 && error.contains("network down"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let worker =
+        OutboxWorker::new(Arc::new(mock_repo_storage), Arc::new(failing_messaging(network_error)));
+
+    assert!(worker.drain().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_outbox_dead_letters_notification_to_blocked_chat() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    mock_repo_storage
+        .expect_get_due_notifications()
+        .returning(|_, _| Ok(vec![pending_notification(0)]));
+    mock_repo_storage.expect_deactivate_chat().with(eq(CHAT_ID)).times(1).returning(|_| Ok(true));
+    mock_repo_storage.expect_reschedule_notification().never();
+    mock_repo_storage
+        .expect_dead_letter_notification()
+        .withf(|&id, error| id == 1 && error.starts_with("Chat unreachable: "))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let worker = OutboxWorker::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(failing_messaging(|| teloxide::RequestError::Api(teloxide::ApiError::BotBlocked))),
+    );
+
+    assert!(worker.drain().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_outbox_skips_parts_sent_before() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    mock_repo_storage.expect_get_due_notifications().returning(|_, _| {
+        Ok(vec![PendingNotification { sent_parts: 1, ..pending_notification(0) }])
+    });
+    // The second part was sent this time, but the third failed
+    mock_messaging
+        .expect_send_new_issues_msg()
+        .withf(|_, _, _, _, _, &sent_parts| sent_parts == 1)
+        .times(1)
+        .returning(|_, _, _, _, _, _| {
+            Err(MessagingError::PartiallySent { sent_parts: 2, source: network_error() })
+        });
+    mock_repo_storage
+        .expect_reschedule_notification()
+        .withf(|&id, _, _, &sent_parts| id == 1 && sent_parts == 2)
+        .times(1)
+        .returning(|_, _, _, _| Ok(()));
+
+    let worker = OutboxWorker::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(worker.drain().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_outbox_keeps_draining_after_storage_error() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
+    mock_repo_storage.expect_get_due_notifications().returning(|_, _| {
+        Ok(vec![pending_notification(0), PendingNotification { id: 2, ..pending_notification(0) }])
+    });
+    mock_messaging.expect_send_new_issues_msg().times(2).returning(|_, _, _, _, _, _| Ok(()));
+    mock_repo_storage
+        .expect_delete_notification()
+        .with(eq(1))
+        .times(1)
+        .returning(|_| Err(StorageError::DbError("database is locked".to_string())));
+    mock_repo_storage.expect_delete_notification().with(eq(2)).times(1).returning(|_| Ok(()));
+
+    let worker = OutboxWorker::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(worker.drain().await.is_ok());
+}
+
+// Helper to create settings whose quiet hours started an hour ago and end in
+// an hour
+fn quiet_settings() -> UserSettings {
+    let now = Utc::now().time();
+    let quiet_hours = QuietHours {
+        start: now - chrono::Duration::hours(1),
+        end: now + chrono::Duration::hours(1),
+    };
+    UserSettings { quiet_hours: Some(quiet_hours), ..Default::default() }
+}
+
+#[tokio::test]
+async fn test_outbox_postpones_notification_in_quiet_hours() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(quiet_settings()));
+    mock_repo_storage
+        .expect_get_due_notifications()
+        .returning(|_, _| Ok(vec![pending_notification(0)]));
+    mock_messaging.expect_send_new_issues_msg().never();
+    let now = Utc::now().timestamp();
+    mock_repo_storage
+        .expect_postpone_notification()
+        .withf(move |&id, &next_retry_at| id == 1 && next_retry_at > now)
+        .times(1)
+        .returning(|_, _| Ok(()));
+    mock_repo_storage.expect_reschedule_notification().never();
+
+    let worker = OutboxWorker::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(worker.drain().await.is_ok());
+}
+
+// Helper to create a chat waiting for its hourly digest, last sent at
+// `last_digest_at`
+fn pending_digest(last_digest_at: DateTime<Utc>) -> PendingDigest {
+    PendingDigest {
+        chat_id: CHAT_ID,
+        settings: UserSettings { delivery_mode: DeliveryMode::Hourly, ..Default::default() },
+        last_digest_at: Some(last_digest_at.timestamp()),
+    }
+}
+
+fn digest_entry(id: &str) -> DigestEntry {
+    DigestEntry { repo_name_with_owner: REPO_NAME_WITH_OWNER.to_string(), issue: issue_with_id(id) }
+}
+
+#[tokio::test]
+async fn test_digest_scheduler_sends_due_digest() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+    let now = last_poll_time();
+
+    mock_repo_storage
+        .expect_get_pending_digests()
+        .returning(move || Ok(vec![pending_digest(now - chrono::Duration::hours(1))]));
+    mock_repo_storage
+        .expect_get_digest_entries()
+        .with(eq(CHAT_ID))
+        .returning(|_| Ok(vec![(1, digest_entry("first")), (2, digest_entry("second"))]));
+    mock_messaging
+        .expect_send_digest_msg()
+        .withf(|&chat_id, settings, entries| {
+            chat_id == CHAT_ID
+                && settings.delivery_mode == DeliveryMode::Hourly
+                && entries.len() == 2
+        })
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+    mock_repo_storage
+        .expect_complete_digest()
+        .with(eq(CHAT_ID), eq(2))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let scheduler = DigestScheduler::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(scheduler.send_due_digests(now).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_digest_scheduler_waits_for_next_slot() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+    let now = last_poll_time();
+
+    // Already sent within the current hour
+    mock_repo_storage.expect_get_pending_digests().returning(move || Ok(vec![pending_digest(now)]));
+    mock_repo_storage.expect_get_digest_entries().never();
+    mock_messaging.expect_send_digest_msg().never();
+
+    let scheduler = DigestScheduler::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(scheduler.send_due_digests(now).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_digest_scheduler_waits_for_quiet_hours_to_end() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+    let now = Utc::now();
+
+    mock_repo_storage.expect_get_pending_digests().returning(move || {
+        let mut pending = pending_digest(now - chrono::Duration::hours(2));
+        pending.settings.quiet_hours = quiet_settings().quiet_hours;
+        Ok(vec![pending])
+    });
+    mock_repo_storage.expect_get_digest_entries().never();
+    mock_messaging.expect_send_digest_msg().never();
+
+    let scheduler = DigestScheduler::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(scheduler.send_due_digests(now).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_digest_scheduler_keeps_entries_when_sending_fails() {
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_messaging = MockMessagingService::new();
+    let now = last_poll_time();
+
+    mock_repo_storage
+        .expect_get_pending_digests()
+        .returning(move || Ok(vec![pending_digest(now - chrono::Duration::hours(1))]));
+    mock_repo_storage
+        .expect_get_digest_entries()
+        .returning(|_| Ok(vec![(1, digest_entry("first"))]));
+    mock_messaging
+        .expect_send_digest_msg()
+        .times(1)
+        .returning(|_, _, _| Err(MessagingError::TeloxideRequest(network_error())));
+    // The entries are sent with the next attempt
+    mock_repo_storage.expect_complete_digest().never();
+    mock_repo_storage.expect_deactivate_chat().never();
+
+    let scheduler = DigestScheduler::new(Arc::new(mock_repo_storage), Arc::new(mock_messaging));
+
+    assert!(scheduler.send_due_digests(now).await.is_ok());
+}
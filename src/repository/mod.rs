@@ -1,17 +1,24 @@
 #[cfg(test)]
 mod tests;
 
-use std::sync::Arc;
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
+use chrono::Utc;
+use chrono_tz::Tz;
 use mockall::automock;
 use teloxide::types::ChatId;
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     github::{GithubClient, GithubError},
     pagination::Paginated,
-    storage::{RepoEntity, RepoStorage, StorageError},
+    storage::{
+        DeliveryMode, IssueFilter, IssueFilters, Language, NOTIFIED_ISSUE_RETENTION,
+        NotificationOption, OwnerSubscription, QuietHours, RepoEntity, RepoStorage, SearchFilter,
+        SearchSubscription, StorageError, UserSettings,
+    },
 };
 
 /// Represents errors that can occur in the repository service.
@@ -32,6 +39,14 @@ pub enum RepositoryServiceError {
 
 type Result<T> = std::result::Result<T, RepositoryServiceError>;
 
+/// How often chats are checked for having been inactive for longer than the
+/// grace period.
+const INACTIVE_CHAT_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How often issues that left the retention window are pruned from the
+/// ledger of notified issues.
+const NOTIFIED_ISSUE_PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// Represents a normalized label with its name, color, count, and selection
 /// status.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,8 +65,10 @@ pub struct LabelNormalized {
 #[automock]
 #[async_trait]
 pub trait RepositoryService: Send + Sync {
-    /// Check if a repository exists on GitHub.
-    async fn repo_exists(&self, owner: &str, name: &str) -> Result<bool>;
+    /// Look a repository up on GitHub. Returns the repository under its
+    /// canonical name, which follows renames and transfers and fixes the case,
+    /// or `None` if it does not exist.
+    async fn resolve_repo(&self, owner: &str, name: &str) -> Result<Option<RepoEntity>>;
 
     /// Add a repository to the user's tracked repositories.
     /// Returns `true` if the repository was added, `false` if it was already
@@ -84,6 +101,105 @@ pub trait RepositoryService: Send + Sync {
         repo: &RepoEntity,
         label_name: &str,
     ) -> Result<bool>;
+
+    /// Get the issue filters of a repository tracked by the user.
+    async fn get_issue_filters(&self, chat_id: ChatId, repo: &RepoEntity) -> Result<IssueFilters>;
+
+    /// Turn an issue filter of a repository on or off. Returns whether the
+    /// filter is now on.
+    async fn toggle_issue_filter(
+        &self,
+        chat_id: ChatId,
+        repo: &RepoEntity,
+        filter: IssueFilter,
+    ) -> Result<bool>;
+
+    /// Stop notifying the user about a tracked repository for the given time.
+    /// Returns `false` if the user does not track the repository directly.
+    async fn mute_repo(
+        &self,
+        chat_id: ChatId,
+        repo: &RepoEntity,
+        duration: Duration,
+    ) -> Result<bool>;
+
+    /// Look every tracked repository up on GitHub, in batches, and rename the
+    /// ones that were renamed or transferred since. Returns the number of
+    /// renamed repositories.
+    async fn sync_repo_names(&self) -> Result<usize>;
+
+    /// Look an organization or user up on GitHub. Returns its canonical login,
+    /// or `None` if it does not exist.
+    async fn resolve_owner(&self, login: &str) -> Result<Option<String>>;
+
+    /// Track every repository of an organization or user. Returns `true` if
+    /// the owner was added, `false` if it was already tracked.
+    async fn add_owner(&self, chat_id: ChatId, owner: &str) -> Result<bool>;
+
+    /// Stop tracking the repositories of an organization or user.
+    async fn remove_owner(&self, chat_id: ChatId, owner: &str) -> Result<bool>;
+
+    /// Get the organizations and users tracked by the user.
+    async fn get_user_owners(&self, chat_id: ChatId) -> Result<Vec<OwnerSubscription>>;
+
+    /// Replace the labels tracked across the repositories of an owner. Returns
+    /// `false` if the owner is not tracked.
+    async fn set_owner_labels(
+        &self,
+        chat_id: ChatId,
+        owner: &str,
+        labels: HashSet<String>,
+    ) -> Result<bool>;
+
+    /// Subscribe the user to new issues matching a GitHub-wide search. Returns
+    /// `true` if the search was added, `false` if it already exists.
+    async fn add_search(&self, chat_id: ChatId, filter: &SearchFilter) -> Result<bool>;
+
+    /// Remove one of the user's search subscriptions.
+    async fn remove_search(&self, chat_id: ChatId, id: i64) -> Result<bool>;
+
+    /// Get the user's search subscriptions.
+    async fn get_user_searches(&self, chat_id: ChatId) -> Result<Vec<SearchSubscription>>;
+
+    /// Resume or stop polling for a chat, e.g. when the user unblocks or
+    /// blocks the bot. Returns `false` if the chat already was in that state.
+    async fn set_chat_active(&self, chat_id: ChatId, active: bool) -> Result<bool>;
+
+    /// Delete the subscriptions of chats that have been inactive for longer
+    /// than the grace period. Returns the number of purged chats.
+    async fn purge_inactive_chats(&self, grace_period: Duration) -> Result<usize>;
+
+    /// Forget the issues notified longer than `retention` ago, so the ledger
+    /// of notified issues does not grow forever. Returns the number of
+    /// forgotten issues.
+    async fn prune_notified_issues(&self, retention: Duration) -> Result<u64>;
+
+    /// Get the user's preferences.
+    async fn get_user_settings(&self, chat_id: ChatId) -> Result<UserSettings>;
+
+    /// Set how notifications are delivered to the user.
+    async fn set_delivery_mode(&self, chat_id: ChatId, delivery_mode: DeliveryMode) -> Result<()>;
+
+    /// Set the timezone of the user's digest schedule and quiet hours.
+    async fn set_timezone(&self, chat_id: ChatId, timezone: Tz) -> Result<()>;
+
+    /// Set or, with `None`, remove the user's quiet hours.
+    async fn set_quiet_hours(&self, chat_id: ChatId, quiet_hours: Option<QuietHours>)
+    -> Result<()>;
+
+    /// Turn one of the user's notification options on or off. Returns `true`
+    /// if the option is now on.
+    async fn toggle_notification_option(
+        &self,
+        chat_id: ChatId,
+        option: NotificationOption,
+    ) -> Result<bool>;
+
+    /// Set the language the user's notifications are sent in.
+    async fn set_language(&self, chat_id: ChatId, language: Language) -> Result<()>;
+
+    /// Set the number of items per page of the user's lists.
+    async fn set_page_size(&self, chat_id: ChatId, page_size: usize) -> Result<()>;
 }
 
 /// The default implementation of the `RepositoryService` trait.
@@ -108,8 +224,17 @@ impl DefaultRepositoryService {
 
 #[async_trait]
 impl RepositoryService for DefaultRepositoryService {
-    async fn repo_exists(&self, owner: &str, name: &str) -> Result<bool> {
-        self.github_client.repo_exists(owner, name).await.map_err(RepositoryServiceError::from)
+    async fn resolve_repo(&self, owner: &str, name: &str) -> Result<Option<RepoEntity>> {
+        let Some(name_with_owner) = self.github_client.repo_name_with_owner(owner, name).await?
+        else {
+            return Ok(None);
+        };
+
+        RepoEntity::from_str(&name_with_owner).map(Some).map_err(|e| {
+            RepositoryServiceError::GithubClientError(GithubError::GraphQLApiError(format!(
+                "Unexpected repository name {name_with_owner}: {e}"
+            )))
+        })
     }
 
     async fn add_repo(&self, chat_id: ChatId, repo: RepoEntity) -> Result<bool> {
@@ -133,9 +258,9 @@ impl RepositoryService for DefaultRepositoryService {
     }
 
     async fn get_user_repos(&self, chat_id: ChatId, page: usize) -> Result<Paginated<RepoEntity>> {
-        let repos =
-            self.storage.get_repos_per_user(chat_id).await.map_err(RepositoryServiceError::from);
-        Ok(Paginated::new(repos?, page))
+        let repos = self.storage.get_repos_per_user(chat_id).await?;
+        let page_size = self.storage.get_user_settings(chat_id).await?.page_size;
+        Ok(Paginated::with_page_size(repos, page, page_size))
     }
 
     async fn get_repo_github_labels(
@@ -174,7 +299,8 @@ impl RepositoryService for DefaultRepositoryService {
             })
             .collect();
 
-        Ok(Paginated::new(normalized, page))
+        let page_size = self.storage.get_user_settings(chat_id).await?.page_size;
+        Ok(Paginated::with_page_size(normalized, page, page_size))
     }
 
     async fn toggle_label(
@@ -220,4 +346,300 @@ impl RepositoryService for DefaultRepositoryService {
 
         Ok(tracked_labels.into_iter().collect())
     }
+
+    async fn get_issue_filters(&self, chat_id: ChatId, repo: &RepoEntity) -> Result<IssueFilters> {
+        self.storage.get_issue_filters(chat_id, repo).await.map_err(RepositoryServiceError::from)
+    }
+
+    async fn toggle_issue_filter(
+        &self,
+        chat_id: ChatId,
+        repo: &RepoEntity,
+        filter: IssueFilter,
+    ) -> Result<bool> {
+        self.storage
+            .toggle_issue_filter(chat_id, repo, filter)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn mute_repo(
+        &self,
+        chat_id: ChatId,
+        repo: &RepoEntity,
+        duration: Duration,
+    ) -> Result<bool> {
+        let until = Utc::now().timestamp().saturating_add_unsigned(duration.as_secs());
+        self.storage
+            .mute_repository(chat_id, repo, until)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn sync_repo_names(&self) -> Result<usize> {
+        let repos: Vec<RepoEntity> = self
+            .storage
+            .get_all_repos()
+            .await?
+            .into_values()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let lookups = repos.iter().map(|repo| (repo.owner.clone(), repo.name.clone())).collect();
+        // A failed lookup is retried on the next run
+        let canonical_names = self.github_client.repos_name_with_owner_batch(lookups).await?;
+        let mut renamed = 0;
+
+        for (repo, canonical_name) in repos.iter().zip(canonical_names) {
+            match canonical_name {
+                Some(name) if name != repo.name_with_owner => {
+                    let canonical = match RepoEntity::from_str(&name) {
+                        Ok(canonical) => canonical,
+                        Err(e) => {
+                            tracing::error!(
+                                "Unexpected name {name} of repository {}: {e:?}",
+                                repo.name_with_owner
+                            );
+                            continue;
+                        }
+                    };
+                    tracing::info!(
+                        "Repository {} is now {name}, renaming it",
+                        repo.name_with_owner
+                    );
+                    self.storage.rename_repository(&repo.name_with_owner, &canonical).await?;
+                    renamed += 1;
+                }
+                Some(_) => {}
+                None => {
+                    tracing::warn!("Repository {} no longer exists on GitHub", repo.name_with_owner)
+                }
+            }
+        }
+
+        Ok(renamed)
+    }
+
+    async fn resolve_owner(&self, login: &str) -> Result<Option<String>> {
+        Ok(self.github_client.owner_repositories(login).await?.map(|owner| owner.login))
+    }
+
+    async fn add_owner(&self, chat_id: ChatId, owner: &str) -> Result<bool> {
+        let user_owners = self.storage.get_owner_subscriptions(chat_id).await?;
+        if user_owners.iter().any(|o| o.owner.eq_ignore_ascii_case(owner)) {
+            return Ok(false);
+        }
+
+        // Owner subscriptions count against the same limit as repositories
+        if user_owners.len() >= self.max_repos_per_user {
+            return Err(RepositoryServiceError::LimitExceeded(format!(
+                "User {} has reached the maximum number of organizations and users: {}",
+                chat_id, self.max_repos_per_user
+            )));
+        }
+
+        self.storage
+            .add_owner_subscription(chat_id, owner)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn remove_owner(&self, chat_id: ChatId, owner: &str) -> Result<bool> {
+        self.storage
+            .remove_owner_subscription(chat_id, owner)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn get_user_owners(&self, chat_id: ChatId) -> Result<Vec<OwnerSubscription>> {
+        self.storage.get_owner_subscriptions(chat_id).await.map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_owner_labels(
+        &self,
+        chat_id: ChatId,
+        owner: &str,
+        labels: HashSet<String>,
+    ) -> Result<bool> {
+        if labels.len() > self.max_labels_per_repo {
+            return Err(RepositoryServiceError::LimitExceeded(format!(
+                "User {} has reached the maximum number of labels per repository: {}",
+                chat_id, self.max_labels_per_repo
+            )));
+        }
+
+        self.storage
+            .set_owner_tracked_labels(chat_id, owner, &labels)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn add_search(&self, chat_id: ChatId, filter: &SearchFilter) -> Result<bool> {
+        // Searches count against the same limit as repositories
+        let user_search_count = self.storage.get_search_subscriptions(chat_id).await?.len();
+
+        if user_search_count >= self.max_repos_per_user {
+            return Err(RepositoryServiceError::LimitExceeded(format!(
+                "User {} has reached the maximum number of searches: {}",
+                chat_id, self.max_repos_per_user
+            )));
+        }
+
+        self.storage
+            .add_search_subscription(chat_id, filter)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn remove_search(&self, chat_id: ChatId, id: i64) -> Result<bool> {
+        self.storage
+            .remove_search_subscription(chat_id, id)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn get_user_searches(&self, chat_id: ChatId) -> Result<Vec<SearchSubscription>> {
+        self.storage.get_search_subscriptions(chat_id).await.map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_chat_active(&self, chat_id: ChatId, active: bool) -> Result<bool> {
+        let changed = if active {
+            self.storage.reactivate_chat(chat_id).await?
+        } else {
+            self.storage.deactivate_chat(chat_id).await?
+        };
+        Ok(changed)
+    }
+
+    async fn purge_inactive_chats(&self, grace_period: Duration) -> Result<usize> {
+        let deactivated_before =
+            Utc::now().timestamp().saturating_sub_unsigned(grace_period.as_secs());
+        let purged = self.storage.purge_inactive_chats(deactivated_before).await?;
+        for chat_id in &purged {
+            tracing::info!("Purged the subscriptions of inactive chat {chat_id}");
+        }
+        Ok(purged.len())
+    }
+
+    async fn prune_notified_issues(&self, retention: Duration) -> Result<u64> {
+        let notified_before = Utc::now().timestamp().saturating_sub_unsigned(retention.as_secs());
+        Ok(self.storage.prune_notified_issues(notified_before).await?)
+    }
+
+    async fn get_user_settings(&self, chat_id: ChatId) -> Result<UserSettings> {
+        self.storage.get_user_settings(chat_id).await.map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_delivery_mode(&self, chat_id: ChatId, delivery_mode: DeliveryMode) -> Result<()> {
+        self.storage
+            .set_delivery_mode(chat_id, delivery_mode)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_timezone(&self, chat_id: ChatId, timezone: Tz) -> Result<()> {
+        self.storage.set_timezone(chat_id, timezone).await.map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_quiet_hours(
+        &self,
+        chat_id: ChatId,
+        quiet_hours: Option<QuietHours>,
+    ) -> Result<()> {
+        self.storage
+            .set_quiet_hours(chat_id, quiet_hours)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn toggle_notification_option(
+        &self,
+        chat_id: ChatId,
+        option: NotificationOption,
+    ) -> Result<bool> {
+        self.storage
+            .toggle_notification_option(chat_id, option)
+            .await
+            .map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_language(&self, chat_id: ChatId, language: Language) -> Result<()> {
+        self.storage.set_language(chat_id, language).await.map_err(RepositoryServiceError::from)
+    }
+
+    async fn set_page_size(&self, chat_id: ChatId, page_size: usize) -> Result<()> {
+        self.storage.set_page_size(chat_id, page_size).await.map_err(RepositoryServiceError::from)
+    }
+}
+
+/// Keep the names of tracked repositories in sync with GitHub, checking every
+/// `interval` until `shutdown` is cancelled. The first check runs right away.
+pub async fn run_repo_name_sync(
+    repository_service: Arc<dyn RepositoryService>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        match repository_service.sync_repo_names().await {
+            Ok(0) => tracing::debug!("All tracked repository names are up to date"),
+            Ok(renamed) => tracing::info!("Renamed {renamed} tracked repositories"),
+            Err(e) => tracing::error!("Failed to sync repository names: {e:?}"),
+        }
+    }
+}
+
+/// Purge the data of chats that stayed inactive for longer than
+/// `grace_period`, checking every hour until `shutdown` is cancelled.
+pub async fn run_inactive_chat_purge(
+    repository_service: Arc<dyn RepositoryService>,
+    grace_period: Duration,
+    shutdown: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(INACTIVE_CHAT_PURGE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        match repository_service.purge_inactive_chats(grace_period).await {
+            Ok(0) => tracing::debug!("No inactive chats to purge"),
+            Ok(purged) => tracing::info!("Purged {purged} inactive chats"),
+            Err(e) => tracing::error!("Failed to purge inactive chats: {e:?}"),
+        }
+    }
+}
+
+/// Forget the notified issues that left the retention window, checking once a
+/// day until `shutdown` is cancelled.
+pub async fn run_notified_issue_prune(
+    repository_service: Arc<dyn RepositoryService>,
+    shutdown: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(NOTIFIED_ISSUE_PRUNE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+
+        match repository_service.prune_notified_issues(NOTIFIED_ISSUE_RETENTION).await {
+            Ok(0) => tracing::debug!("No notified issues to prune"),
+            Ok(pruned) => tracing::info!("Pruned {pruned} old notified issues"),
+            Err(e) => tracing::error!("Failed to prune notified issues: {e:?}"),
+        }
+    }
 }
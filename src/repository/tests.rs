@@ -1,21 +1,28 @@
-use std::{collections::HashSet, str::FromStr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+};
 
 use mockall::predicate::eq;
 
 use super::*;
 use crate::{
-    github::{GithubError, MockGithubClient, labels},
-    storage::{MockRepoStorage, RepoEntity},
+    github::{GithubError, MockGithubClient, OwnerRepos, labels},
+    storage::{MockRepoStorage, OwnerSubscription, RepoEntity},
 };
 
 const MAX_REPOS_PER_USER: usize = 10;
 const MAX_LABELS_PER_REPO: usize = 5;
 
 #[tokio::test]
-async fn test_repo_exists() {
+async fn test_resolve_repo() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
-    mock_github_client.expect_repo_exists().returning(|_, _| Ok(true));
+    mock_github_client
+        .expect_repo_name_with_owner()
+        .with(eq("Owner"), eq("REPO"))
+        .returning(|_, _| Ok(Some("owner/repo".to_string())));
     let mock_repo_storage = MockRepoStorage::new();
     let repository_service = DefaultRepositoryService::new(
         Arc::new(mock_repo_storage),
@@ -25,11 +32,29 @@ async fn test_repo_exists() {
     );
 
     // Act
-    let repo_exists = repository_service.repo_exists("owner", "repo").await;
+    let repo = repository_service.resolve_repo("Owner", "REPO").await;
+
+    // Assert
+    assert_eq!(repo.unwrap(), Some(RepoEntity::from_str("owner/repo").unwrap()));
+}
+
+#[tokio::test]
+async fn test_resolve_repo_not_found() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    mock_github_client.expect_repo_name_with_owner().returning(|_, _| Ok(None));
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(MockRepoStorage::new()),
+        Arc::new(mock_github_client),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let repo = repository_service.resolve_repo("owner", "missing").await;
 
     // Assert
-    assert!(repo_exists.is_ok());
-    assert!(repo_exists.unwrap());
+    assert_eq!(repo.unwrap(), None);
 }
 
 #[tokio::test]
@@ -76,19 +101,45 @@ async fn test_remove_repo() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_mute_repo() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let now = chrono::Utc::now().timestamp();
+    mock_repo_storage
+        .expect_mute_repository()
+        .withf(move |_, _, &until| (now + 3600..=now + 3601).contains(&until))
+        .times(1)
+        .returning(|_, _, _| Ok(true));
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(MockGithubClient::new()),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+    let result = repository_service.mute_repo(ChatId(1), &repo, Duration::from_secs(3600)).await;
+
+    // Assert
+    assert!(result.unwrap());
+}
+
 #[tokio::test]
 async fn test_get_user_repos() {
     // Arrange
     let repo1 = RepoEntity::from_str("owner/repo").unwrap();
     let repo2 = RepoEntity::from_str("owner/repo2").unwrap();
-    let mut repos = vec![];
-    repos.push(repo1);
-    repos.push(repo2);
+    let repos = vec![repo1, repo2];
 
     let repos_clone = repos.clone();
 
     let mut mock_repo_storage = MockRepoStorage::new();
     mock_repo_storage.expect_get_repos_per_user().returning(move |_| Ok(repos.clone()));
+    mock_repo_storage
+        .expect_get_user_settings()
+        .returning(|_| Ok(UserSettings { page_size: 5, ..Default::default() }));
     let mock_github_client = MockGithubClient::new();
     let repository_service = DefaultRepositoryService::new(
         Arc::new(mock_repo_storage),
@@ -103,7 +154,7 @@ async fn test_get_user_repos() {
 
     // Assert
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), Paginated::new(repos_clone, page));
+    assert_eq!(result.unwrap(), Paginated::with_page_size(repos_clone, page, 5));
 }
 
 #[tokio::test]
@@ -122,6 +173,7 @@ async fn test_get_repo_labels() {
         .with(eq(chat_id), eq(repo.clone()))
         .times(1)
         .returning(move |_, _| Ok(tracked_labels.clone()));
+    mock_repo_storage.expect_get_user_settings().returning(|_| Ok(UserSettings::default()));
 
     mock_github_client.expect_repo_labels().returning(|_, _| {
         Ok(vec![labels::LabelsRepositoryLabelsNodes {
@@ -150,10 +202,12 @@ async fn test_get_repo_labels() {
 }
 
 #[tokio::test]
-async fn test_repo_exists_error() {
+async fn test_resolve_repo_error() {
     // Arrange
     let mut mock_github_client = MockGithubClient::new();
-    mock_github_client.expect_repo_exists().returning(|_, _| Err(GithubError::Unauthorized));
+    mock_github_client
+        .expect_repo_name_with_owner()
+        .returning(|_, _| Err(GithubError::Unauthorized));
     let mock_repo_storage = MockRepoStorage::new();
     let repository_service = DefaultRepositoryService::new(
         Arc::new(mock_repo_storage),
@@ -163,7 +217,7 @@ async fn test_repo_exists_error() {
     );
 
     // Act
-    let result = repository_service.repo_exists("owner", "repo").await;
+    let result = repository_service.resolve_repo("owner", "repo").await;
 
     // Assert
     assert!(result.is_err());
@@ -446,3 +500,201 @@ async fn test_get_user_repo_labels() {
     assert!(labels.contains(&"bug".to_string()));
     assert!(labels.contains(&"enhancement".to_string()));
 }
+
+#[tokio::test]
+async fn test_sync_repo_names() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let mut mock_github_client = MockGithubClient::new();
+    let renamed = RepoEntity::from_str("old-owner/old-name").unwrap();
+    let unchanged = RepoEntity::from_str("owner/repo").unwrap();
+    let deleted = RepoEntity::from_str("owner/deleted").unwrap();
+
+    let tracked = HashMap::from([
+        (ChatId(1), HashSet::from([renamed.clone(), unchanged.clone()])),
+        (ChatId(2), HashSet::from([renamed.clone(), deleted.clone()])),
+    ]);
+    mock_repo_storage.expect_get_all_repos().times(1).returning(move || Ok(tracked.clone()));
+    // All repositories are looked up in one batch
+    mock_github_client.expect_repos_name_with_owner_batch().times(1).returning(|repos| {
+        assert_eq!(repos.len(), 3);
+        Ok(repos
+            .into_iter()
+            .map(|(owner, name)| match (owner.as_str(), name.as_str()) {
+                ("old-owner", "old-name") => Some("new-owner/new-name".to_string()),
+                ("owner", "repo") => Some("owner/repo".to_string()),
+                _ => None,
+            })
+            .collect())
+    });
+    mock_repo_storage
+        .expect_rename_repository()
+        .with(eq("old-owner/old-name"), eq(RepoEntity::from_str("new-owner/new-name").unwrap()))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(mock_github_client),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let result = repository_service.sync_repo_names().await;
+
+    // Assert
+    assert_eq!(result.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_resolve_owner() {
+    // Arrange
+    let mut mock_github_client = MockGithubClient::new();
+    mock_github_client.expect_owner_repositories().with(eq("Rust-Lang")).returning(|_| {
+        Ok(Some(OwnerRepos {
+            login: "rust-lang".to_string(),
+            repos: vec!["rust-lang/rust".to_string()],
+        }))
+    });
+    mock_github_client.expect_owner_repositories().with(eq("missing")).returning(|_| Ok(None));
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(MockRepoStorage::new()),
+        Arc::new(mock_github_client),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let found = repository_service.resolve_owner("Rust-Lang").await;
+    let missing = repository_service.resolve_owner("missing").await;
+
+    // Assert
+    assert_eq!(found.unwrap().as_deref(), Some("rust-lang"));
+    assert_eq!(missing.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_add_owner_limit_exceeded() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_owner_subscriptions().returning(|_| {
+        Ok((0..MAX_REPOS_PER_USER)
+            .map(|i| OwnerSubscription {
+                owner: format!("owner{i}"),
+                tracked_labels: HashSet::new(),
+            })
+            .collect())
+    });
+    mock_repo_storage.expect_add_owner_subscription().never();
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(MockGithubClient::new()),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let result = repository_service.add_owner(ChatId(1), "rust-lang").await;
+
+    // Assert
+    assert!(matches!(result, Err(RepositoryServiceError::LimitExceeded(_))));
+}
+
+#[tokio::test]
+async fn test_set_owner_labels_limit_exceeded() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_set_owner_tracked_labels().never();
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(MockGithubClient::new()),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+    let labels = (0..=MAX_LABELS_PER_REPO).map(|i| format!("label{i}")).collect();
+
+    // Act
+    let result = repository_service.set_owner_labels(ChatId(1), "rust-lang", labels).await;
+
+    // Assert
+    assert!(matches!(result, Err(RepositoryServiceError::LimitExceeded(_))));
+}
+
+#[tokio::test]
+async fn test_add_search_limit_exceeded() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    mock_repo_storage.expect_get_search_subscriptions().returning(|chat_id| {
+        Ok((0..MAX_REPOS_PER_USER as i64)
+            .map(|id| SearchSubscription {
+                id,
+                chat_id,
+                filter: SearchFilter::default(),
+                created_at: 0,
+                last_poll_time: 0,
+            })
+            .collect())
+    });
+    mock_repo_storage.expect_add_search_subscription().never();
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(MockGithubClient::new()),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let result = repository_service.add_search(ChatId(1), &SearchFilter::default()).await;
+
+    // Assert
+    assert!(matches!(result, Err(RepositoryServiceError::LimitExceeded(_))));
+}
+
+#[tokio::test]
+async fn test_purge_inactive_chats() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let now = chrono::Utc::now().timestamp();
+    mock_repo_storage
+        .expect_purge_inactive_chats()
+        .withf(move |&before| (now - 86400 - 1..=now - 86400).contains(&before))
+        .times(1)
+        .returning(|_| Ok(vec![ChatId(1), ChatId(2)]));
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(MockGithubClient::new()),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let result = repository_service.purge_inactive_chats(Duration::from_secs(86400)).await;
+
+    // Assert
+    assert_eq!(result.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_prune_notified_issues() {
+    // Arrange
+    let mut mock_repo_storage = MockRepoStorage::new();
+    let now = chrono::Utc::now().timestamp();
+    mock_repo_storage
+        .expect_prune_notified_issues()
+        .withf(move |&before| (now - 86400 - 1..=now - 86400).contains(&before))
+        .times(1)
+        .returning(|_| Ok(5));
+    let repository_service = DefaultRepositoryService::new(
+        Arc::new(mock_repo_storage),
+        Arc::new(MockGithubClient::new()),
+        MAX_REPOS_PER_USER,
+        MAX_LABELS_PER_REPO,
+    );
+
+    // Act
+    let result = repository_service.prune_notified_issues(Duration::from_secs(86400)).await;
+
+    // Assert
+    assert_eq!(result.unwrap(), 5);
+}
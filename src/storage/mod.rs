@@ -1,15 +1,29 @@
 mod repo_entity;
+mod search_filter;
 pub mod sqlite;
 #[cfg(test)]
 mod tests;
+mod user_settings;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use async_trait::async_trait;
+use chrono_tz::Tz;
 use mockall::automock;
-pub use repo_entity::RepoEntity;
+pub use repo_entity::{RepoEntity, RepoInputNormalization};
+pub use search_filter::{DEFAULT_SEARCH_LABEL, SearchFilter, SearchFilterError};
+use serde::{Deserialize, Serialize};
 use teloxide::types::ChatId;
 use thiserror::Error;
+pub use user_settings::{
+    DEFAULT_DIGEST_TIME, DeliveryMode, DeliveryModeError, Language, NotificationOption, PAGE_SIZES,
+    QuietHours, SettingsError, UserSettings, parse_timezone,
+};
+
+use crate::github::{SearchIssue, issues::IssuesRepositoryIssuesNodes};
 
 /// Represents errors that can occur in the storage layer.
 #[derive(Debug, Error)]
@@ -25,6 +39,138 @@ pub enum StorageError {
 /// A convenience type alias for `Result<T, StorageError>`.
 pub type StorageResult<T> = Result<T, StorageError>;
 
+/// How long an issue stays in the ledger of notified issues. The poller only
+/// notifies issues that were opened or labeled within this window, so an issue
+/// that was forgotten is only notified again if it is labeled again after its
+/// notification.
+pub const NOTIFIED_ISSUE_RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// Options that skip issues someone is already working on. Repositories that
+/// are only tracked through an owner subscription use the defaults.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IssueFilters {
+    /// Skip issues that are assigned to someone.
+    pub exclude_assigned: bool,
+    /// Skip issues with an open pull request linked to them.
+    pub exclude_linked_prs: bool,
+}
+
+/// One of the options of `IssueFilters`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueFilter {
+    /// See `IssueFilters::exclude_assigned`.
+    #[serde(rename = "a")]
+    Assigned,
+    /// See `IssueFilters::exclude_linked_prs`.
+    #[serde(rename = "pr")]
+    LinkedPr,
+}
+
+/// A chat's subscription to every repository of a GitHub organization or user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerSubscription {
+    /// The login of the organization or user.
+    pub owner: String,
+    /// The labels tracked across all repositories of the owner.
+    pub tracked_labels: HashSet<String>,
+}
+
+/// A chat's subscription to new issues matching a GitHub-wide search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchSubscription {
+    /// The id of the subscription.
+    pub id: i64,
+    /// The chat that is notified.
+    pub chat_id: ChatId,
+    /// What to search for.
+    pub filter: SearchFilter,
+    /// When the subscription was created, as a Unix timestamp. Issues opened
+    /// before are never notified.
+    pub created_at: i64,
+    /// When the search was last polled, as a Unix timestamp.
+    pub last_poll_time: i64,
+}
+
+/// A notification that is delivered through the outbox.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Notification {
+    /// New and newly labeled issues of a tracked repository.
+    NewIssues {
+        /// The `owner/name` of the repository.
+        repo_name_with_owner: String,
+        /// The issues opened since the last poll.
+        new_issues: Vec<IssuesRepositoryIssuesNodes>,
+        /// The older issues that gained a tracked label since the last poll.
+        labeled_issues: Vec<IssuesRepositoryIssuesNodes>,
+    },
+    /// New results of a search subscription.
+    SearchIssues {
+        /// The search the results match.
+        filter: SearchFilter,
+        /// The matching issues.
+        issues: Vec<SearchIssue>,
+    },
+}
+
+impl Notification {
+    /// Split the notification into the entries of a digest, one per issue.
+    pub fn into_digest_entries(self) -> Vec<DigestEntry> {
+        match self {
+            Self::NewIssues { repo_name_with_owner, new_issues, labeled_issues } => new_issues
+                .into_iter()
+                .chain(labeled_issues)
+                .map(|issue| DigestEntry {
+                    repo_name_with_owner: repo_name_with_owner.clone(),
+                    issue,
+                })
+                .collect(),
+            Self::SearchIssues { issues, .. } => issues
+                .into_iter()
+                .map(|result| DigestEntry {
+                    repo_name_with_owner: result.repo_name_with_owner,
+                    issue: result.issue,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A notification waiting in the outbox for its next delivery attempt.
+#[derive(Debug, Clone)]
+pub struct PendingNotification {
+    /// The id of the outbox entry.
+    pub id: i64,
+    /// The chat that is notified.
+    pub chat_id: ChatId,
+    /// What to deliver.
+    pub notification: Notification,
+    /// How many times delivering it from the outbox failed.
+    pub attempts: u32,
+    /// How many parts of a message too long for one were already delivered.
+    pub sent_parts: usize,
+}
+
+/// An issue waiting to be included in a chat's next digest.
+#[derive(Debug, Clone)]
+pub struct DigestEntry {
+    /// The `owner/name` of the repository the issue belongs to.
+    pub repo_name_with_owner: String,
+    /// The issue.
+    pub issue: IssuesRepositoryIssuesNodes,
+}
+
+/// A chat with entries waiting for its next digest.
+#[derive(Debug, Clone)]
+pub struct PendingDigest {
+    /// The chat the digest is sent to.
+    pub chat_id: ChatId,
+    /// The preferences of the chat.
+    pub settings: UserSettings,
+    /// When the chat last received a digest or changed its delivery mode, as a
+    /// Unix timestamp.
+    pub last_digest_at: Option<i64>,
+}
+
 /// A trait for storing and retrieving repository data.
 #[automock]
 #[async_trait]
@@ -47,6 +193,23 @@ pub trait RepoStorage: Send + Sync {
     /// Get all repositories from the storage.
     async fn get_all_repos(&self) -> StorageResult<HashMap<ChatId, HashSet<RepoEntity>>>;
 
+    /// Get the chats subscribed to a repository, together with the repository
+    /// as stored for each chat. The name is matched case-insensitively.
+    async fn get_repo_subscribers(
+        &self,
+        repo_name_with_owner: &str,
+    ) -> StorageResult<Vec<(ChatId, RepoEntity)>>;
+
+    /// Rename a repository for every chat tracking it, e.g. after it was
+    /// renamed or transferred on GitHub. The polling state and notification
+    /// ledger move along. Chats already tracking the new name keep their
+    /// entry, and the one under the old name is dropped.
+    async fn rename_repository(
+        &self,
+        old_name_with_owner: &str,
+        repository: &RepoEntity,
+    ) -> StorageResult<()>;
+
     /// Get the last poll time for a repository.
     async fn get_last_poll_time(
         &self,
@@ -61,7 +224,8 @@ pub trait RepoStorage: Send + Sync {
         repository: &RepoEntity,
     ) -> StorageResult<()>;
 
-    /// Get tracked labels by for user and repository.
+    /// Get tracked labels by for user and repository. Repositories that are
+    /// only tracked through an owner subscription get the owner's labels.
     async fn get_tracked_labels(
         &self,
         chat_id: ChatId,
@@ -76,6 +240,232 @@ pub trait RepoStorage: Send + Sync {
         label_name: &str,
     ) -> StorageResult<bool>;
 
+    /// Get the issue filters of a repository tracked by the user.
+    async fn get_issue_filters(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+    ) -> StorageResult<IssueFilters>;
+
+    /// Turn an issue filter of a tracked repository on or off. Returns whether
+    /// the filter is now on.
+    async fn toggle_issue_filter(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        filter: IssueFilter,
+    ) -> StorageResult<bool>;
+
+    /// Mute a tracked repository until the given Unix timestamp. Issues opened
+    /// or labeled while muted are never notified. Returns `false` if the user
+    /// does not track the repository directly.
+    async fn mute_repository(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        until: i64,
+    ) -> StorageResult<bool>;
+
+    /// Get the Unix timestamp until which a tracked repository is muted, if it
+    /// was ever muted.
+    async fn get_muted_until(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+    ) -> StorageResult<Option<i64>>;
+
     /// Get the number of repositories per user.
     async fn count_repos_per_user(&self, chat_id: ChatId) -> StorageResult<usize>;
+
+    /// Get the ids of issues that have already been notified to the user for a
+    /// repository.
+    async fn get_notified_issues(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+    ) -> StorageResult<HashSet<String>>;
+
+    /// Record that the given issues have been notified to the user for a
+    /// repository. Returns the issues that were not recorded yet, so when two
+    /// callers race to notify the same issue only one of them claims it.
+    async fn mark_issues_notified(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        issue_ids: &[String],
+    ) -> StorageResult<Vec<String>>;
+
+    /// Remove the given issues from the ledger again, e.g. when their
+    /// notification could not be delivered after all.
+    async fn unmark_issues_notified(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        issue_ids: &[String],
+    ) -> StorageResult<()>;
+
+    /// Forget the issues that were notified before the given Unix timestamp.
+    /// Returns the number of forgotten issues.
+    async fn prune_notified_issues(&self, notified_before: i64) -> StorageResult<u64>;
+
+    /// Subscribe a chat to every repository of an organization or user.
+    /// Returns `true` if the subscription was added, `false` if it was already
+    /// present.
+    async fn add_owner_subscription(&self, chat_id: ChatId, owner: &str) -> StorageResult<bool>;
+
+    /// Remove an owner subscription. Owners are matched case-insensitively.
+    async fn remove_owner_subscription(&self, chat_id: ChatId, owner: &str) -> StorageResult<bool>;
+
+    /// Get the owner subscriptions of a chat, ordered by owner.
+    async fn get_owner_subscriptions(
+        &self,
+        chat_id: ChatId,
+    ) -> StorageResult<Vec<OwnerSubscription>>;
+
+    /// Get the owners every chat is subscribed to.
+    async fn get_all_owner_subscriptions(&self) -> StorageResult<HashMap<ChatId, HashSet<String>>>;
+
+    /// Get the active chats subscribed to an organization or user. The owner
+    /// is matched case-insensitively.
+    async fn get_owner_subscribers(&self, owner: &str) -> StorageResult<Vec<ChatId>>;
+
+    /// Replace the labels tracked across the repositories of an owner. Returns
+    /// `false` if the chat is not subscribed to the owner.
+    async fn set_owner_tracked_labels(
+        &self,
+        chat_id: ChatId,
+        owner: &str,
+        labels: &HashSet<String>,
+    ) -> StorageResult<bool>;
+
+    /// Subscribe a chat to new issues matching a search. Returns `true` if the
+    /// subscription was added, `false` if the chat already has the same one.
+    async fn add_search_subscription(
+        &self,
+        chat_id: ChatId,
+        filter: &SearchFilter,
+    ) -> StorageResult<bool>;
+
+    /// Remove a search subscription of a chat.
+    async fn remove_search_subscription(&self, chat_id: ChatId, id: i64) -> StorageResult<bool>;
+
+    /// Get the search subscriptions of a chat, oldest first.
+    async fn get_search_subscriptions(
+        &self,
+        chat_id: ChatId,
+    ) -> StorageResult<Vec<SearchSubscription>>;
+
+    /// Get the search subscriptions of all chats.
+    async fn get_all_search_subscriptions(&self) -> StorageResult<Vec<SearchSubscription>>;
+
+    /// Set the last poll time of a search subscription to now.
+    async fn set_search_poll_time(&self, id: i64) -> StorageResult<()>;
+
+    /// Mark a chat as inactive, e.g. after the user blocked the bot. The
+    /// subscriptions of inactive chats are not polled. Returns `false` if the
+    /// chat was already inactive.
+    async fn deactivate_chat(&self, chat_id: ChatId) -> StorageResult<bool>;
+
+    /// Mark an inactive chat as active again. Returns `false` if the chat was
+    /// not inactive.
+    async fn reactivate_chat(&self, chat_id: ChatId) -> StorageResult<bool>;
+
+    /// Delete all subscriptions and state of the chats that were deactivated
+    /// before the given Unix timestamp. Returns the purged chats.
+    async fn purge_inactive_chats(&self, deactivated_before: i64) -> StorageResult<Vec<ChatId>>;
+
+    /// Add a notification that could not be delivered to the outbox, to be
+    /// retried at the given Unix timestamp. `sent_parts` parts of it were
+    /// delivered already.
+    async fn enqueue_notification(
+        &self,
+        chat_id: ChatId,
+        notification: &Notification,
+        next_retry_at: i64,
+        sent_parts: usize,
+    ) -> StorageResult<()>;
+
+    /// Get up to `limit` notifications of active chats that are due for a
+    /// delivery attempt at the given Unix timestamp, the longest waiting first.
+    async fn get_due_notifications(
+        &self,
+        now: i64,
+        limit: u32,
+    ) -> StorageResult<Vec<PendingNotification>>;
+
+    /// Remove a delivered notification from the outbox.
+    async fn delete_notification(&self, id: i64) -> StorageResult<()>;
+
+    /// Deliver a notification at the given Unix timestamp instead, without
+    /// counting a failed attempt.
+    async fn postpone_notification(&self, id: i64, next_retry_at: i64) -> StorageResult<()>;
+
+    /// Record a failed delivery attempt of a notification, after which
+    /// `sent_parts` parts of it were delivered, and retry it at the given Unix
+    /// timestamp.
+    async fn reschedule_notification(
+        &self,
+        id: i64,
+        next_retry_at: i64,
+        error: &str,
+        sent_parts: usize,
+    ) -> StorageResult<()>;
+
+    /// Move a notification that will not be retried anymore from the outbox to
+    /// the dead-letter table.
+    async fn dead_letter_notification(&self, id: i64, error: &str) -> StorageResult<()>;
+
+    /// Get the preferences of a chat, or the defaults if it never changed them.
+    async fn get_user_settings(&self, chat_id: ChatId) -> StorageResult<UserSettings>;
+
+    /// Set how notifications are delivered to a chat. The next digest
+    /// collects the issues found from now on.
+    async fn set_delivery_mode(
+        &self,
+        chat_id: ChatId,
+        delivery_mode: DeliveryMode,
+    ) -> StorageResult<()>;
+
+    /// Set the timezone the digest schedule and quiet hours of a chat are in.
+    async fn set_timezone(&self, chat_id: ChatId, timezone: Tz) -> StorageResult<()>;
+
+    /// Set or, with `None`, remove the quiet hours of a chat.
+    async fn set_quiet_hours(
+        &self,
+        chat_id: ChatId,
+        quiet_hours: Option<QuietHours>,
+    ) -> StorageResult<()>;
+
+    /// Turn a notification option of a chat on or off. Returns `true` if the
+    /// option is now on.
+    async fn toggle_notification_option(
+        &self,
+        chat_id: ChatId,
+        option: NotificationOption,
+    ) -> StorageResult<bool>;
+
+    /// Set the language notifications are sent to a chat in.
+    async fn set_language(&self, chat_id: ChatId, language: Language) -> StorageResult<()>;
+
+    /// Set the number of items per page of a chat's lists.
+    async fn set_page_size(&self, chat_id: ChatId, page_size: usize) -> StorageResult<()>;
+
+    /// Add issues to a chat's next digest. Issues that are already waiting are
+    /// ignored.
+    async fn add_digest_entries(
+        &self,
+        chat_id: ChatId,
+        entries: &[DigestEntry],
+    ) -> StorageResult<()>;
+
+    /// Get the active chats with entries waiting for their next digest.
+    async fn get_pending_digests(&self) -> StorageResult<Vec<PendingDigest>>;
+
+    /// Get the entries waiting for a chat's next digest with their ids, the
+    /// oldest first.
+    async fn get_digest_entries(&self, chat_id: ChatId) -> StorageResult<Vec<(i64, DigestEntry)>>;
+
+    /// Record that a digest was sent to a chat, removing its entries up to
+    /// and including `last_entry_id`.
+    async fn complete_digest(&self, chat_id: ChatId, last_entry_id: i64) -> StorageResult<()>;
 }
@@ -31,6 +31,53 @@ pub struct RepoEntity {
 }
 
 const GITHUB_URL: &str = "https://github.com";
+const GITHUB_HOST: &str = "github.com";
+const GITHUB_WWW_HOST: &str = "www.github.com";
+const SSH_PREFIX: &str = "git@github.com:";
+
+/// A normalization applied to user input to turn it into a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepoInputNormalization {
+    /// The input was an `owner/repo` shorthand.
+    Shorthand,
+    /// The input was an SSH clone URL, e.g. `git@github.com:owner/repo.git`.
+    SshUrl,
+    /// A `.git` suffix was removed from the repository name.
+    GitSuffix,
+    /// The `www.` prefix was removed from the host.
+    WwwHost,
+    /// The input had no `https://` scheme.
+    MissingScheme,
+    /// The repository was extracted from an issue URL.
+    IssueUrl,
+    /// The repository was extracted from a pull request URL.
+    PullRequestUrl,
+    /// The repository was extracted from a tree or file URL.
+    TreeUrl,
+    /// The repository was extracted from a URL pointing into it.
+    SubpageUrl,
+    /// GitHub knows the repository under a different name, because it was
+    /// renamed or transferred, or the case differs.
+    CanonicalName,
+}
+
+impl fmt::Display for RepoInputNormalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::Shorthand => "owner/repo shorthand",
+            Self::SshUrl => "SSH URL",
+            Self::GitSuffix => ".git suffix removed",
+            Self::WwwHost => "www. removed",
+            Self::MissingScheme => "https:// added",
+            Self::IssueUrl => "taken from issue URL",
+            Self::PullRequestUrl => "taken from pull request URL",
+            Self::TreeUrl => "taken from tree URL",
+            Self::SubpageUrl => "taken from repository page URL",
+            Self::CanonicalName => "canonical name on GitHub",
+        };
+        f.write_str(description)
+    }
+}
 
 impl RepoEntity {
     /// Returns the URL of the repository on GitHub.
@@ -61,6 +108,88 @@ impl RepoEntity {
     }
 }
 
+impl RepoEntity {
+    /// Parses free-form user input into a repository. Besides repository URLs
+    /// this accepts the `owner/repo` shorthand, SSH clone URLs, `.git`
+    /// suffixes, `www.github.com`, URLs without a scheme and URLs pointing into
+    /// the repository, e.g. to an issue. Returns the normalizations that were
+    /// applied, in the order they were applied.
+    pub fn from_input(input: &str) -> Result<(Self, Vec<RepoInputNormalization>)> {
+        let input = input.trim();
+        let mut normalizations = Vec::new();
+
+        let path = if let Some(path) = input.strip_prefix(SSH_PREFIX) {
+            normalizations.push(RepoInputNormalization::SshUrl);
+            path.to_string()
+        } else if input.contains("://") {
+            Self::url_path(input, &mut normalizations)?
+        } else if input.starts_with(&format!("{GITHUB_HOST}/"))
+            || input.starts_with(&format!("{GITHUB_WWW_HOST}/"))
+        {
+            normalizations.push(RepoInputNormalization::MissingScheme);
+            Self::url_path(&format!("https://{input}"), &mut normalizations)?
+        } else {
+            let repo = Self::from_str(input)?;
+            if !is_valid_name(&repo.owner) || !is_valid_name(&repo.name) {
+                return Err(RepoEntityError::Format(input.to_string()));
+            }
+            normalizations.push(RepoInputNormalization::Shorthand);
+            repo.name_with_owner
+        };
+
+        let mut segments = path.trim_matches('/').split('/');
+        let owner = segments.next().unwrap_or_default();
+        let mut name = segments.next().unwrap_or_default();
+        if let Some(stripped) = name.strip_suffix(".git") {
+            normalizations.push(RepoInputNormalization::GitSuffix);
+            name = stripped;
+        }
+        if owner.is_empty() || name.is_empty() {
+            return Err(RepoEntityError::NameWithOwner);
+        }
+
+        match segments.next() {
+            None | Some("") => {}
+            Some("issues") if segments.next().is_some() =>
+                normalizations.push(RepoInputNormalization::IssueUrl),
+            Some("pull") => normalizations.push(RepoInputNormalization::PullRequestUrl),
+            Some("tree" | "blob") => normalizations.push(RepoInputNormalization::TreeUrl),
+            Some(_) => normalizations.push(RepoInputNormalization::SubpageUrl),
+        }
+
+        let name_with_owner = format!("{owner}/{name}");
+        Ok((
+            Self { owner: owner.to_string(), name: name.to_string(), name_with_owner },
+            normalizations,
+        ))
+    }
+
+    /// The path of a GitHub URL, without the query and fragment.
+    fn url_path(url_str: &str, normalizations: &mut Vec<RepoInputNormalization>) -> Result<String> {
+        let url = Url::parse(url_str).map_err(|_| RepoEntityError::Url(url_str.to_string()))?;
+        if !matches!(url.scheme(), "https" | "http" | "ssh") {
+            return Err(RepoEntityError::Url(url_str.to_string()));
+        }
+
+        match url.domain() {
+            Some(GITHUB_HOST) => {}
+            Some(GITHUB_WWW_HOST) => normalizations.push(RepoInputNormalization::WwwHost),
+            _ => return Err(RepoEntityError::Url(url_str.to_string())),
+        }
+        if url.scheme() == "ssh" {
+            normalizations.push(RepoInputNormalization::SshUrl);
+        }
+
+        Ok(url.path().to_string())
+    }
+}
+
+/// Returns `true` if `name` only contains characters GitHub allows in owner
+/// and repository names.
+fn is_valid_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
 impl fmt::Display for RepoEntity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} ({})", self.name_with_owner, self.url())
@@ -175,6 +304,58 @@ mod tests {
         assert!(matches!(result, Err(RepoEntityError::NameWithOwner)));
     }
 
+    #[test]
+    fn test_from_input_plain_url() {
+        let (repo, normalizations) =
+            RepoEntity::from_input("https://github.com/rust-lang/rust").unwrap();
+
+        assert_eq!(repo.name_with_owner, "rust-lang/rust");
+        assert!(normalizations.is_empty());
+    }
+
+    #[test]
+    fn test_from_input_normalizations() {
+        use RepoInputNormalization::*;
+
+        let cases: &[(&str, &[RepoInputNormalization])] = &[
+            ("rust-lang/rust", &[Shorthand]),
+            ("git@github.com:rust-lang/rust.git", &[SshUrl, GitSuffix]),
+            ("ssh://git@github.com/rust-lang/rust.git", &[SshUrl, GitSuffix]),
+            ("https://github.com/rust-lang/rust.git", &[GitSuffix]),
+            ("https://www.github.com/rust-lang/rust", &[WwwHost]),
+            ("github.com/rust-lang/rust", &[MissingScheme]),
+            ("www.github.com/rust-lang/rust/", &[MissingScheme, WwwHost]),
+            ("https://github.com/rust-lang/rust/issues/42", &[IssueUrl]),
+            ("https://github.com/rust-lang/rust/pull/42/files", &[PullRequestUrl]),
+            ("https://github.com/rust-lang/rust/tree/master/src", &[TreeUrl]),
+            ("https://github.com/rust-lang/rust/blob/master/README.md", &[TreeUrl]),
+            ("https://github.com/rust-lang/rust/issues?q=is%3Aopen", &[SubpageUrl]),
+            ("https://github.com/rust-lang/rust?tab=readme", &[]),
+        ];
+
+        for (input, expected) in cases {
+            let (repo, normalizations) = RepoEntity::from_input(input).unwrap();
+            assert_eq!(repo.name_with_owner, "rust-lang/rust", "{input}");
+            assert_eq!(&normalizations, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_from_input_invalid() {
+        for input in [
+            "this_is_not_a_url",
+            "invalid-url",
+            "https://gitlab.com/rust-lang/rust",
+            "ftp://github.com/rust-lang/rust",
+            "git@gitlab.com:rust-lang/rust.git",
+            "https://github.com/rust-lang",
+            "rust lang/rust",
+            "<b>owner</b>/repo",
+        ] {
+            assert!(RepoEntity::from_input(input).is_err(), "{input}");
+        }
+    }
+
     #[test]
     fn test_from_str_name_contains_slash() {
         let result = RepoEntity::from_str("owner/repo/extra");
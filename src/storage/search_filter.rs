@@ -0,0 +1,186 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Represents errors that can occur when building a `SearchFilter`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SearchFilterError {
+    /// A value contains characters that cannot be used in a search query.
+    #[error("Invalid {0}: {1}")]
+    InvalidValue(&'static str, String),
+    /// The minimum number of stars is not a number.
+    #[error("Invalid number of stars: {0}")]
+    InvalidStars(String),
+}
+
+type Result<T> = std::result::Result<T, SearchFilterError>;
+
+/// The label searched for when none is given.
+pub const DEFAULT_SEARCH_LABEL: &str = "good first issue";
+
+/// The longest label, language or topic accepted.
+const MAX_VALUE_LEN: usize = 50;
+
+/// The criteria of a GitHub-wide issue search.
+///
+/// The label and language are part of the search query. GitHub cannot search
+/// issues by the stars or topics of their repository, so those are matched
+/// against the results instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SearchFilter {
+    /// The label the issues must carry.
+    pub label: String,
+    /// The primary language of the repositories, lowercase.
+    pub language: Option<String>,
+    /// A topic of the repositories, lowercase.
+    pub topic: Option<String>,
+    /// The minimum number of stars of the repositories.
+    pub min_stars: Option<u32>,
+}
+
+impl Default for SearchFilter {
+    fn default() -> Self {
+        Self {
+            label: DEFAULT_SEARCH_LABEL.to_string(),
+            language: None,
+            topic: None,
+            min_stars: None,
+        }
+    }
+}
+
+impl SearchFilter {
+    /// Set the label, or the default label if `input` is `None`.
+    pub fn set_label(&mut self, input: Option<&str>) -> Result<()> {
+        self.label = match input {
+            Some(label) => parse_value("label", label)?,
+            None => DEFAULT_SEARCH_LABEL.to_string(),
+        };
+        Ok(())
+    }
+
+    /// Set the language, or search all languages if `input` is `None`.
+    pub fn set_language(&mut self, input: Option<&str>) -> Result<()> {
+        self.language = input.map(|language| parse_value("language", language)).transpose()?;
+        self.language = self.language.take().map(|language| language.to_lowercase());
+        Ok(())
+    }
+
+    /// Set the topic, or search all topics if `input` is `None`.
+    pub fn set_topic(&mut self, input: Option<&str>) -> Result<()> {
+        self.topic = input.map(|topic| parse_value("topic", topic)).transpose()?;
+        self.topic = self.topic.take().map(|topic| topic.to_lowercase());
+        Ok(())
+    }
+
+    /// Set the minimum number of stars, or accept any if `input` is `None`.
+    pub fn set_min_stars(&mut self, input: Option<&str>) -> Result<()> {
+        self.min_stars = input
+            .map(|stars| {
+                let stars = stars.trim().trim_start_matches(">=").trim_start_matches('>');
+                stars.parse().map_err(|_| SearchFilterError::InvalidStars(stars.to_string()))
+            })
+            .transpose()?
+            .filter(|&stars| stars > 0);
+        Ok(())
+    }
+
+    /// The GitHub search query for open issues matching the label and
+    /// language.
+    pub fn query(&self) -> String {
+        let mut query = format!("is:issue is:open archived:false label:\"{}\"", self.label);
+        if let Some(language) = &self.language {
+            query.push_str(&format!(" language:\"{language}\""));
+        }
+        query
+    }
+
+    /// Returns `true` if a repository with the given stars and topics
+    /// satisfies the filter.
+    pub fn matches_repo(&self, stars: i64, topics: &[String]) -> bool {
+        let enough_stars = self.min_stars.is_none_or(|min_stars| stars >= i64::from(min_stars));
+        let has_topic = self
+            .topic
+            .as_ref()
+            .is_none_or(|topic| topics.iter().any(|t| t.eq_ignore_ascii_case(topic)));
+        enough_stars && has_topic
+    }
+}
+
+/// Trim a value and check that it can be quoted in a search query.
+fn parse_value(kind: &'static str, input: &str) -> Result<String> {
+    let value = input.trim();
+    if value.is_empty() || value.len() > MAX_VALUE_LEN || value.contains(['"', '\n']) {
+        return Err(SearchFilterError::InvalidValue(kind, value.to_string()));
+    }
+    Ok(value.to_string())
+}
+
+impl fmt::Display for SearchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "label \"{}\"", self.label)?;
+        if let Some(language) = &self.language {
+            write!(f, ", language {language}")?;
+        }
+        if let Some(min_stars) = self.min_stars {
+            write!(f, ", {min_stars}+ stars")?;
+        }
+        if let Some(topic) = &self.topic {
+            write!(f, ", topic {topic}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let mut filter = SearchFilter::default();
+        assert_eq!(filter.query(), "is:issue is:open archived:false label:\"good first issue\"");
+
+        filter.set_label(Some(" help wanted ")).unwrap();
+        filter.set_language(Some("Rust")).unwrap();
+        assert_eq!(
+            filter.query(),
+            "is:issue is:open archived:false label:\"help wanted\" language:\"rust\""
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let mut filter = SearchFilter::default();
+        assert!(filter.set_label(Some("say \"hi\"")).is_err());
+        assert!(filter.set_topic(Some("  ")).is_err());
+        assert!(filter.set_min_stars(Some("many")).is_err());
+    }
+
+    #[test]
+    fn test_matches_repo() {
+        let mut filter = SearchFilter::default();
+        filter.set_min_stars(Some(">100")).unwrap();
+        filter.set_topic(Some("CLI")).unwrap();
+        let topics = vec!["cli".to_string(), "rust".to_string()];
+
+        assert!(filter.matches_repo(100, &topics));
+        assert!(!filter.matches_repo(99, &topics));
+        assert!(!filter.matches_repo(1000, &["web".to_string()]));
+        assert!(SearchFilter::default().matches_repo(0, &[]));
+    }
+
+    #[test]
+    fn test_display() {
+        let mut filter = SearchFilter::default();
+        filter.set_language(Some("rust")).unwrap();
+        filter.set_min_stars(Some("100")).unwrap();
+        filter.set_topic(Some("cli")).unwrap();
+
+        assert_eq!(
+            filter.to_string(),
+            "label \"good first issue\", language rust, 100+ stars, topic cli"
+        );
+    }
+}
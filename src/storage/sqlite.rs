@@ -8,15 +8,105 @@ use std::{
 
 use async_trait::async_trait;
 use chrono::Utc;
+use chrono_tz::Tz;
 use serde_json;
-use sqlx::{Pool, Sqlite, SqlitePool, migrate, query};
+use sqlx::{Pool, Sqlite, SqlitePool, migrate, query, query_as, query_scalar};
 use teloxide::types::ChatId;
 
-use crate::storage::{RepoEntity, RepoStorage, StorageError, StorageResult};
+use crate::storage::{
+    DeliveryMode, DigestEntry, IssueFilter, IssueFilters, Language, Notification,
+    NotificationOption, OwnerSubscription, PendingDigest, PendingNotification, QuietHours,
+    RepoEntity, RepoStorage, SearchFilter, SearchSubscription, StorageError, StorageResult,
+    UserSettings,
+};
 
 const INITIAL_DEFAULT_LABELS_JSON: &str =
     r#"["good first issue","beginner-friendly","help wanted"]"#;
 
+/// A row of the `search_subscriptions` table. Optional criteria are stored as
+/// empty values, so that the unique constraint covers them.
+struct SearchSubscriptionRow {
+    id: i64,
+    chat_id: i64,
+    label: String,
+    language: String,
+    topic: String,
+    min_stars: i64,
+    created_at: i64,
+    last_poll_time: i64,
+}
+
+impl From<SearchSubscriptionRow> for SearchSubscription {
+    fn from(row: SearchSubscriptionRow) -> Self {
+        let filter = SearchFilter {
+            label: row.label,
+            language: Some(row.language).filter(|language| !language.is_empty()),
+            topic: Some(row.topic).filter(|topic| !topic.is_empty()),
+            min_stars: u32::try_from(row.min_stars).ok().filter(|&stars| stars > 0),
+        };
+        Self {
+            id: row.id,
+            chat_id: ChatId(row.chat_id),
+            filter,
+            created_at: row.created_at,
+            last_poll_time: row.last_poll_time,
+        }
+    }
+}
+
+/// A row of the `user_settings` table.
+struct UserSettingsRow {
+    chat_id: i64,
+    delivery_mode: String,
+    timezone: String,
+    quiet_hours: Option<String>,
+    silent_notifications: bool,
+    link_previews: bool,
+    language: String,
+    page_size: i64,
+}
+
+/// Invalid values fall back to the defaults.
+impl From<UserSettingsRow> for UserSettings {
+    fn from(row: UserSettingsRow) -> Self {
+        let chat_id = row.chat_id;
+        let defaults = UserSettings::default();
+        UserSettings {
+            delivery_mode: row.delivery_mode.parse().unwrap_or_else(|e| {
+                tracing::warn!("Invalid delivery mode stored for chat {chat_id}: {e}");
+                defaults.delivery_mode
+            }),
+            timezone: row.timezone.parse().unwrap_or_else(|e| {
+                tracing::warn!("Invalid timezone stored for chat {chat_id}: {e}");
+                defaults.timezone
+            }),
+            quiet_hours: row.quiet_hours.and_then(|quiet_hours| match quiet_hours.parse() {
+                Ok(quiet_hours) => Some(quiet_hours),
+                Err(e) => {
+                    tracing::warn!("Invalid quiet hours stored for chat {chat_id}: {e}");
+                    None
+                }
+            }),
+            silent_notifications: row.silent_notifications,
+            link_previews: row.link_previews,
+            language: row.language.parse().unwrap_or_else(|e| {
+                tracing::warn!("Invalid language stored for chat {chat_id}: {e}");
+                defaults.language
+            }),
+            page_size: usize::try_from(row.page_size)
+                .ok()
+                .filter(|&page_size| page_size > 0)
+                .unwrap_or_else(|| {
+                    tracing::warn!(
+                        "Invalid page size stored for chat {chat_id}: {}",
+                        row.page_size
+                    );
+                    defaults.page_size
+                }),
+        }
+    }
+}
+
 /// An implementation of `RepoStorage` that uses SQLite as the backing store.
 pub struct SqliteStorage {
     pool: Pool<Sqlite>,
@@ -37,6 +127,12 @@ impl SqliteStorage {
 
         Ok(Self { pool })
     }
+
+    /// Closes the connection pool, waiting for in-flight queries to finish.
+    pub async fn close(&self) {
+        tracing::debug!("Closing SQLite connection pool");
+        self.pool.close().await;
+    }
 }
 
 #[async_trait]
@@ -71,17 +167,37 @@ impl RepoStorage for SqliteStorage {
 
         let chat_id = chat_id.0;
 
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
         let result = query!(
             "DELETE FROM repositories WHERE chat_id = ? AND name_with_owner = ?",
             chat_id,
             name_with_owner,
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| {
             StorageError::DbError(format!("Failed to remove repository from SQLite: {e}"))
         })?;
 
+        // The notification ledger is only meaningful while the repository is tracked.
+        query!(
+            "DELETE FROM notified_issues WHERE chat_id = ? AND repository_full_name = ?",
+            chat_id,
+            name_with_owner,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to remove notified issues from SQLite: {e}"))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
         Ok(result.rows_affected() > 0)
     }
 
@@ -116,12 +232,15 @@ impl RepoStorage for SqliteStorage {
     async fn get_all_repos(&self) -> StorageResult<HashMap<ChatId, HashSet<RepoEntity>>> {
         tracing::debug!("Getting all repositories from SQLite");
 
-        let repos = query!("SELECT chat_id, owner, name, name_with_owner FROM repositories",)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| {
-                StorageError::DbError(format!("Failed to get all repositories from SQLite: {e}"))
-            })?;
+        let repos = query!(
+            "SELECT chat_id, owner, name, name_with_owner FROM repositories WHERE chat_id NOT IN \
+             (SELECT chat_id FROM inactive_chats)",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get all repositories from SQLite: {e}"))
+        })?;
 
         let mut result = HashMap::new();
         for r in repos {
@@ -134,6 +253,117 @@ impl RepoStorage for SqliteStorage {
         Ok(result)
     }
 
+    async fn get_repo_subscribers(
+        &self,
+        repo_name_with_owner: &str,
+    ) -> StorageResult<Vec<(ChatId, RepoEntity)>> {
+        tracing::debug!("Getting subscribers of repository: {}", repo_name_with_owner);
+
+        let rows = query!(
+            "SELECT chat_id, owner, name, name_with_owner FROM repositories WHERE \
+             LOWER(name_with_owner) = LOWER(?) AND chat_id NOT IN (SELECT chat_id FROM \
+             inactive_chats)",
+            repo_name_with_owner,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get repository subscribers from SQLite: {e}"))
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let repo =
+                    RepoEntity { owner: r.owner, name: r.name, name_with_owner: r.name_with_owner };
+                (ChatId(r.chat_id), repo)
+            })
+            .collect())
+    }
+
+    async fn rename_repository(
+        &self,
+        old_name_with_owner: &str,
+        repository: &RepoEntity,
+    ) -> StorageResult<()> {
+        tracing::debug!(
+            "Renaming repository in SQLite: {} -> {}",
+            old_name_with_owner,
+            repository.name_with_owner
+        );
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        // Rows that would clash with an existing entry for the new name are left
+        // behind by the updates and removed afterwards.
+        query!(
+            "UPDATE OR IGNORE repositories SET owner = ?, name = ?, name_with_owner = ? WHERE \
+             name_with_owner = ?",
+            repository.owner,
+            repository.name,
+            repository.name_with_owner,
+            old_name_with_owner,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to rename repository in SQLite: {e}"))
+        })?;
+
+        query!("DELETE FROM repositories WHERE name_with_owner = ?", old_name_with_owner)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to rename repository in SQLite: {e}"))
+            })?;
+
+        query!(
+            "UPDATE OR IGNORE poller_states SET repository_full_name = ? WHERE \
+             repository_full_name = ?",
+            repository.name_with_owner,
+            old_name_with_owner,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to rename poller state in SQLite: {e}"))
+        })?;
+
+        query!("DELETE FROM poller_states WHERE repository_full_name = ?", old_name_with_owner)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to rename poller state in SQLite: {e}"))
+            })?;
+
+        query!(
+            "UPDATE OR IGNORE notified_issues SET repository_full_name = ? WHERE \
+             repository_full_name = ?",
+            repository.name_with_owner,
+            old_name_with_owner,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to rename notified issues in SQLite: {e}"))
+        })?;
+
+        query!("DELETE FROM notified_issues WHERE repository_full_name = ?", old_name_with_owner)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to rename notified issues in SQLite: {e}"))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
+
     async fn get_last_poll_time(
         &self,
         chat_id: ChatId,
@@ -197,13 +427,27 @@ impl RepoStorage for SqliteStorage {
             chat_id_i64,
             repository.name_with_owner,
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await
         .map_err(|e| {
             StorageError::DbError(format!("Failed to get tracked labels from SQLite: {e}"))
         })?;
 
-        let labels_str = raw_result.tracked_labels.unwrap_or("[]".to_string());
+        let labels_str = match raw_result {
+            Some(r) => r.tracked_labels.unwrap_or("[]".to_string()),
+            // Not tracked directly, fall back to the labels of an owner subscription
+            None => query!(
+                "SELECT tracked_labels FROM owner_subscriptions WHERE chat_id = ? AND owner = ?",
+                chat_id_i64,
+                repository.owner,
+            )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to get tracked labels from SQLite: {e}"))
+            })?
+            .map_or("[]".to_string(), |r| r.tracked_labels),
+        };
         let labels: HashSet<String> = serde_json::from_str(&labels_str).map_err(|e| {
             StorageError::DataIntegrityError(repository.name_with_owner.clone(), e.into())
         })?;
@@ -250,6 +494,138 @@ impl RepoStorage for SqliteStorage {
         Ok(tracked_labels.contains(label_name))
     }
 
+    async fn get_issue_filters(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+    ) -> StorageResult<IssueFilters> {
+        let chat_id_i64 = chat_id.0;
+
+        let filters = query_as!(
+            IssueFilters,
+            "SELECT exclude_assigned, exclude_linked_prs FROM repositories WHERE chat_id = ? AND \
+             name_with_owner = ?",
+            chat_id_i64,
+            repository.name_with_owner,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get issue filters from SQLite: {e}"))
+        })?;
+
+        Ok(filters.unwrap_or_default())
+    }
+
+    async fn toggle_issue_filter(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        filter: IssueFilter,
+    ) -> StorageResult<bool> {
+        tracing::debug!(
+            "Toggling issue filter {filter:?} for repository: {}",
+            repository.name_with_owner
+        );
+        let chat_id_i64 = chat_id.0;
+
+        let enabled = match filter {
+            IssueFilter::Assigned =>
+                query_scalar!(
+                    "UPDATE repositories SET exclude_assigned = NOT exclude_assigned WHERE \
+                     chat_id = ? AND name_with_owner = ? RETURNING exclude_assigned",
+                    chat_id_i64,
+                    repository.name_with_owner,
+                )
+                .fetch_optional(&self.pool)
+                .await,
+            IssueFilter::LinkedPr =>
+                query_scalar!(
+                    "UPDATE repositories SET exclude_linked_prs = NOT exclude_linked_prs WHERE \
+                     chat_id = ? AND name_with_owner = ? RETURNING exclude_linked_prs",
+                    chat_id_i64,
+                    repository.name_with_owner,
+                )
+                .fetch_optional(&self.pool)
+                .await,
+        }
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to toggle issue filter in SQLite: {e}"))
+        })?;
+
+        Ok(enabled.unwrap_or(false))
+    }
+
+    async fn mute_repository(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        until: i64,
+    ) -> StorageResult<bool> {
+        tracing::debug!("Muting repository {} until {until}", repository.name_with_owner);
+        let chat_id = chat_id.0;
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        let result = query!(
+            "UPDATE repositories SET muted_until = ? WHERE chat_id = ? AND name_with_owner = ?",
+            until,
+            chat_id,
+            repository.name_with_owner,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to mute repository in SQLite: {e}")))?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        // Start the next polling window when the mute ends, so issues from while it
+        // was muted are skipped instead of caught up on.
+        query!(
+            "INSERT OR REPLACE INTO poller_states (chat_id, repository_full_name, last_poll_time) \
+             VALUES (?, ?, ?)",
+            chat_id,
+            repository.name_with_owner,
+            until,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to set last poll time in SQLite: {e}"))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(true)
+    }
+
+    async fn get_muted_until(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+    ) -> StorageResult<Option<i64>> {
+        let chat_id = chat_id.0;
+
+        let muted_until = query_scalar!(
+            "SELECT muted_until FROM repositories WHERE chat_id = ? AND name_with_owner = ?",
+            chat_id,
+            repository.name_with_owner,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get muted until from SQLite: {e}"))
+        })?;
+
+        Ok(muted_until.flatten())
+    }
+
     async fn count_repos_per_user(&self, chat_id: ChatId) -> StorageResult<usize> {
         tracing::debug!("Counting repositories for user: {}", chat_id);
 
@@ -266,4 +642,977 @@ impl RepoStorage for SqliteStorage {
 
         Ok(result.count.try_into().unwrap_or(0))
     }
+
+    async fn get_notified_issues(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+    ) -> StorageResult<HashSet<String>> {
+        tracing::debug!("Getting notified issues for repository: {}", repository.name_with_owner);
+        let chat_id = chat_id.0;
+
+        let rows = query!(
+            "SELECT issue_id FROM notified_issues WHERE chat_id = ? AND repository_full_name = ?",
+            chat_id,
+            repository.name_with_owner,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get notified issues from SQLite: {e}"))
+        })?;
+
+        Ok(rows.into_iter().map(|r| r.issue_id).collect())
+    }
+
+    async fn mark_issues_notified(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        issue_ids: &[String],
+    ) -> StorageResult<Vec<String>> {
+        tracing::debug!(
+            "Marking {} issues as notified for repository: {}",
+            issue_ids.len(),
+            repository.name_with_owner
+        );
+        let chat_id = chat_id.0;
+        let current_time = Utc::now().timestamp();
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        let mut marked = Vec::with_capacity(issue_ids.len());
+        for issue_id in issue_ids {
+            let result = query!(
+                "INSERT INTO notified_issues (chat_id, repository_full_name, issue_id, \
+                 notified_at) VALUES (?, ?, ?, ?) ON CONFLICT DO NOTHING",
+                chat_id,
+                repository.name_with_owner,
+                issue_id,
+                current_time,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to mark issue as notified in SQLite: {e}"))
+            })?;
+
+            if result.rows_affected() > 0 {
+                marked.push(issue_id.clone());
+            }
+        }
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(marked)
+    }
+
+    async fn unmark_issues_notified(
+        &self,
+        chat_id: ChatId,
+        repository: &RepoEntity,
+        issue_ids: &[String],
+    ) -> StorageResult<()> {
+        tracing::debug!(
+            "Unmarking {} notified issues for repository: {}",
+            issue_ids.len(),
+            repository.name_with_owner
+        );
+        let chat_id = chat_id.0;
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        for issue_id in issue_ids {
+            query!(
+                "DELETE FROM notified_issues WHERE chat_id = ? AND repository_full_name = ? AND \
+                 issue_id = ?",
+                chat_id,
+                repository.name_with_owner,
+                issue_id,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to unmark notified issue in SQLite: {e}"))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
+
+    async fn prune_notified_issues(&self, notified_before: i64) -> StorageResult<u64> {
+        tracing::debug!("Pruning issues notified before {notified_before}");
+
+        let result = query!("DELETE FROM notified_issues WHERE notified_at < ?", notified_before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to prune notified issues from SQLite: {e}"))
+            })?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn add_owner_subscription(&self, chat_id: ChatId, owner: &str) -> StorageResult<bool> {
+        tracing::debug!("Adding owner subscription to SQLite: {}", owner);
+
+        let chat_id = chat_id.0;
+
+        let result = query!(
+            "INSERT OR IGNORE INTO owner_subscriptions (chat_id, owner, tracked_labels) VALUES \
+             (?, ?, ?)",
+            chat_id,
+            owner,
+            INITIAL_DEFAULT_LABELS_JSON,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to add owner subscription to SQLite: {e}"))
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn remove_owner_subscription(&self, chat_id: ChatId, owner: &str) -> StorageResult<bool> {
+        tracing::debug!("Removing owner subscription from SQLite: {}", owner);
+
+        let chat_id = chat_id.0;
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        let result = query!(
+            "DELETE FROM owner_subscriptions WHERE chat_id = ? AND owner = ?",
+            chat_id,
+            owner,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to remove owner subscription from SQLite: {e}"))
+        })?;
+
+        // Repositories the chat also tracks directly keep their ledger.
+        query!(
+            "DELETE FROM notified_issues WHERE chat_id = ? AND LOWER(SUBSTR(repository_full_name, \
+             1, LENGTH(?) + 1)) = LOWER(? || '/') AND repository_full_name NOT IN (SELECT \
+             name_with_owner FROM repositories WHERE chat_id = ?)",
+            chat_id,
+            owner,
+            owner,
+            chat_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to remove notified issues from SQLite: {e}"))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_owner_subscriptions(
+        &self,
+        chat_id: ChatId,
+    ) -> StorageResult<Vec<OwnerSubscription>> {
+        tracing::debug!("Getting owner subscriptions for user: {}", chat_id);
+
+        let rows = query!(
+            "SELECT owner, tracked_labels FROM owner_subscriptions WHERE chat_id = ? ORDER BY \
+             LOWER(owner) ASC",
+            chat_id.0,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!(
+                "Failed to fetch owner subscriptions for user {}: {}",
+                chat_id.0, e
+            ))
+        })?;
+
+        rows.into_iter()
+            .map(|r| {
+                let tracked_labels = serde_json::from_str(&r.tracked_labels)
+                    .map_err(|e| StorageError::DataIntegrityError(r.owner.clone(), e.into()))?;
+                Ok(OwnerSubscription { owner: r.owner, tracked_labels })
+            })
+            .collect()
+    }
+
+    async fn get_all_owner_subscriptions(&self) -> StorageResult<HashMap<ChatId, HashSet<String>>> {
+        tracing::debug!("Getting all owner subscriptions from SQLite");
+
+        let rows = query!(
+            "SELECT chat_id, owner FROM owner_subscriptions WHERE chat_id NOT IN (SELECT chat_id \
+             FROM inactive_chats)"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get all owner subscriptions from SQLite: {e}"))
+        })?;
+
+        let mut result = HashMap::new();
+        for r in rows {
+            result.entry(ChatId(r.chat_id)).or_insert_with(HashSet::new).insert(r.owner);
+        }
+
+        Ok(result)
+    }
+
+    async fn get_owner_subscribers(&self, owner: &str) -> StorageResult<Vec<ChatId>> {
+        tracing::debug!("Getting subscribers of owner: {}", owner);
+
+        let chat_ids = query_scalar!(
+            "SELECT chat_id FROM owner_subscriptions WHERE owner = ? AND chat_id NOT IN (SELECT \
+             chat_id FROM inactive_chats)",
+            owner
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get owner subscribers from SQLite: {e}"))
+        })?;
+
+        Ok(chat_ids.into_iter().map(ChatId).collect())
+    }
+
+    async fn set_owner_tracked_labels(
+        &self,
+        chat_id: ChatId,
+        owner: &str,
+        labels: &HashSet<String>,
+    ) -> StorageResult<bool> {
+        tracing::debug!("Setting tracked labels for owner: {}", owner);
+
+        let chat_id = chat_id.0;
+        let labels_str = serde_json::to_string(labels)
+            .map_err(|e| StorageError::DataIntegrityError(owner.to_string(), e.into()))?;
+
+        let result = query!(
+            "UPDATE owner_subscriptions SET tracked_labels = ? WHERE chat_id = ? AND owner = ?",
+            labels_str,
+            chat_id,
+            owner,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to set owner tracked labels in SQLite: {e}"))
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_search_subscription(
+        &self,
+        chat_id: ChatId,
+        filter: &SearchFilter,
+    ) -> StorageResult<bool> {
+        tracing::debug!("Adding search subscription to SQLite: {}", filter);
+
+        let chat_id = chat_id.0;
+        let language = filter.language.as_deref().unwrap_or_default();
+        let topic = filter.topic.as_deref().unwrap_or_default();
+        let min_stars = filter.min_stars.unwrap_or_default();
+        let current_time = Utc::now().timestamp();
+
+        let result = query!(
+            "INSERT OR IGNORE INTO search_subscriptions (chat_id, label, language, topic, \
+             min_stars, created_at, last_poll_time) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            chat_id,
+            filter.label,
+            language,
+            topic,
+            min_stars,
+            current_time,
+            current_time,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to add search subscription to SQLite: {e}"))
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn remove_search_subscription(&self, chat_id: ChatId, id: i64) -> StorageResult<bool> {
+        tracing::debug!("Removing search subscription from SQLite: {}", id);
+
+        let chat_id = chat_id.0;
+
+        let result =
+            query!("DELETE FROM search_subscriptions WHERE chat_id = ? AND id = ?", chat_id, id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove search subscription from SQLite: {e}"
+                    ))
+                })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_search_subscriptions(
+        &self,
+        chat_id: ChatId,
+    ) -> StorageResult<Vec<SearchSubscription>> {
+        tracing::debug!("Getting search subscriptions for user: {}", chat_id);
+
+        let rows = query_as!(
+            SearchSubscriptionRow,
+            "SELECT id AS \"id!\", chat_id, label, language, topic, min_stars, created_at, \
+             last_poll_time FROM search_subscriptions WHERE chat_id = ? ORDER BY id ASC",
+            chat_id.0,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!(
+                "Failed to fetch search subscriptions for user {}: {}",
+                chat_id.0, e
+            ))
+        })?;
+
+        Ok(rows.into_iter().map(SearchSubscription::from).collect())
+    }
+
+    async fn get_all_search_subscriptions(&self) -> StorageResult<Vec<SearchSubscription>> {
+        tracing::debug!("Getting all search subscriptions from SQLite");
+
+        let rows = query_as!(
+            SearchSubscriptionRow,
+            "SELECT id AS \"id!\", chat_id, label, language, topic, min_stars, created_at, \
+             last_poll_time FROM search_subscriptions WHERE chat_id NOT IN (SELECT chat_id FROM \
+             inactive_chats)",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!(
+                "Failed to get all search subscriptions from SQLite: {e}"
+            ))
+        })?;
+
+        Ok(rows.into_iter().map(SearchSubscription::from).collect())
+    }
+
+    async fn set_search_poll_time(&self, id: i64) -> StorageResult<()> {
+        tracing::debug!("Setting last poll time for search subscription: {}", id);
+
+        let current_time = Utc::now().timestamp();
+
+        query!("UPDATE search_subscriptions SET last_poll_time = ? WHERE id = ?", current_time, id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!(
+                    "Failed to set search subscription poll time in SQLite: {e}"
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    async fn deactivate_chat(&self, chat_id: ChatId) -> StorageResult<bool> {
+        tracing::debug!("Deactivating chat: {}", chat_id);
+
+        let chat_id = chat_id.0;
+        let current_time = Utc::now().timestamp();
+
+        let result = query!(
+            "INSERT OR IGNORE INTO inactive_chats (chat_id, deactivated_at) VALUES (?, ?)",
+            chat_id,
+            current_time,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to deactivate chat in SQLite: {e}")))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn reactivate_chat(&self, chat_id: ChatId) -> StorageResult<bool> {
+        tracing::debug!("Reactivating chat: {}", chat_id);
+
+        let chat_id = chat_id.0;
+
+        let result = query!("DELETE FROM inactive_chats WHERE chat_id = ?", chat_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to reactivate chat in SQLite: {e}"))
+            })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn purge_inactive_chats(&self, deactivated_before: i64) -> StorageResult<Vec<ChatId>> {
+        tracing::debug!("Purging chats deactivated before {deactivated_before}");
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        let chat_ids = query_scalar!(
+            "SELECT chat_id AS \"chat_id!\" FROM inactive_chats WHERE deactivated_at < ?",
+            deactivated_before
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get inactive chats from SQLite: {e}"))
+        })?;
+
+        for &chat_id in &chat_ids {
+            query!("DELETE FROM repositories WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                StorageError::DbError(format!("Failed to remove repositories from SQLite: {e}"))
+            })?;
+            query!("DELETE FROM poller_states WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove poller states from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM notified_issues WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove notified issues from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM owner_subscriptions WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove owner subscriptions from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM search_subscriptions WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove search subscriptions from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM notification_outbox WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove notifications from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM dead_letters WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                StorageError::DbError(format!("Failed to remove dead letters from SQLite: {e}"))
+            })?;
+            query!("DELETE FROM user_settings WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove user settings from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM digest_entries WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove digest entries from SQLite: {e}"
+                    ))
+                })?;
+            query!("DELETE FROM inactive_chats WHERE chat_id = ?", chat_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StorageError::DbError(format!(
+                        "Failed to remove inactive chat from SQLite: {e}"
+                    ))
+                })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(chat_ids.into_iter().map(ChatId).collect())
+    }
+
+    async fn enqueue_notification(
+        &self,
+        chat_id: ChatId,
+        notification: &Notification,
+        next_retry_at: i64,
+        sent_parts: usize,
+    ) -> StorageResult<()> {
+        tracing::debug!("Adding notification for chat {} to the outbox", chat_id);
+
+        let chat_id = chat_id.0;
+        let payload = serde_json::to_string(notification)
+            .map_err(|e| StorageError::DbError(format!("Failed to serialize notification: {e}")))?;
+        let sent_parts = i64::try_from(sent_parts).unwrap_or(i64::MAX);
+        let current_time = Utc::now().timestamp();
+
+        query!(
+            "INSERT INTO notification_outbox (chat_id, payload, next_retry_at, sent_parts, \
+             created_at) VALUES (?, ?, ?, ?, ?)",
+            chat_id,
+            payload,
+            next_retry_at,
+            sent_parts,
+            current_time,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to add notification to the outbox: {e}"))
+        })?;
+
+        Ok(())
+    }
+
+    async fn get_due_notifications(
+        &self,
+        now: i64,
+        limit: u32,
+    ) -> StorageResult<Vec<PendingNotification>> {
+        let rows = query!(
+            "SELECT id AS \"id!\", chat_id, payload, attempts, sent_parts FROM \
+             notification_outbox WHERE next_retry_at <= ? AND chat_id NOT IN (SELECT chat_id FROM \
+             inactive_chats) ORDER BY next_retry_at ASC LIMIT ?",
+            now,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get due notifications from SQLite: {e}"))
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| match serde_json::from_str(&r.payload) {
+                Ok(notification) => Some(PendingNotification {
+                    id: r.id,
+                    chat_id: ChatId(r.chat_id),
+                    notification,
+                    attempts: u32::try_from(r.attempts).unwrap_or_default(),
+                    sent_parts: usize::try_from(r.sent_parts).unwrap_or_default(),
+                }),
+                Err(e) => {
+                    tracing::warn!("Skipping notification {} with an invalid payload: {e}", r.id);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    async fn delete_notification(&self, id: i64) -> StorageResult<()> {
+        tracing::debug!("Removing notification {} from the outbox", id);
+
+        query!("DELETE FROM notification_outbox WHERE id = ?", id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to remove notification from SQLite: {e}"))
+            })?;
+
+        Ok(())
+    }
+
+    async fn postpone_notification(&self, id: i64, next_retry_at: i64) -> StorageResult<()> {
+        tracing::debug!("Postponing notification {} to {}", id, next_retry_at);
+
+        query!("UPDATE notification_outbox SET next_retry_at = ? WHERE id = ?", next_retry_at, id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to postpone notification in SQLite: {e}"))
+            })?;
+
+        Ok(())
+    }
+
+    async fn reschedule_notification(
+        &self,
+        id: i64,
+        next_retry_at: i64,
+        error: &str,
+        sent_parts: usize,
+    ) -> StorageResult<()> {
+        tracing::debug!("Retrying notification {} at {}", id, next_retry_at);
+
+        let sent_parts = i64::try_from(sent_parts).unwrap_or(i64::MAX);
+        query!(
+            "UPDATE notification_outbox SET attempts = attempts + 1, next_retry_at = ?, \
+             last_error = ?, sent_parts = ? WHERE id = ?",
+            next_retry_at,
+            error,
+            sent_parts,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to reschedule notification in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
+
+    async fn dead_letter_notification(&self, id: i64, error: &str) -> StorageResult<()> {
+        tracing::debug!("Moving notification {} to the dead-letter table", id);
+
+        let current_time = Utc::now().timestamp();
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        query!(
+            "INSERT INTO dead_letters (chat_id, payload, attempts, last_error, created_at, \
+             failed_at) SELECT chat_id, payload, attempts + 1, ?, created_at, ? FROM \
+             notification_outbox WHERE id = ?",
+            error,
+            current_time,
+            id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to add dead letter to SQLite: {e}")))?;
+
+        query!("DELETE FROM notification_outbox WHERE id = ?", id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to remove notification from SQLite: {e}"))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
+
+    async fn get_user_settings(&self, chat_id: ChatId) -> StorageResult<UserSettings> {
+        let chat_id = chat_id.0;
+
+        let row = query_as!(
+            UserSettingsRow,
+            "SELECT chat_id AS \"chat_id!\", delivery_mode, timezone, quiet_hours, \
+             silent_notifications, link_previews, language, page_size FROM user_settings WHERE \
+             chat_id = ?",
+            chat_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get user settings from SQLite: {e}"))
+        })?;
+
+        Ok(row.map_or_else(UserSettings::default, UserSettings::from))
+    }
+
+    async fn set_delivery_mode(
+        &self,
+        chat_id: ChatId,
+        delivery_mode: DeliveryMode,
+    ) -> StorageResult<()> {
+        tracing::debug!("Setting delivery mode of chat {} to {}", chat_id, delivery_mode);
+
+        let chat_id = chat_id.0;
+        let delivery_mode = delivery_mode.to_string();
+        let current_time = Utc::now().timestamp();
+
+        query!(
+            "INSERT INTO user_settings (chat_id, delivery_mode, last_digest_at) VALUES (?, ?, ?) \
+             ON CONFLICT(chat_id) DO UPDATE SET delivery_mode = excluded.delivery_mode, \
+             last_digest_at = excluded.last_digest_at",
+            chat_id,
+            delivery_mode,
+            current_time,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to set delivery mode in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
+
+    async fn set_timezone(&self, chat_id: ChatId, timezone: Tz) -> StorageResult<()> {
+        tracing::debug!("Setting timezone of chat {} to {}", chat_id, timezone);
+
+        let chat_id = chat_id.0;
+        let timezone = timezone.name();
+
+        query!(
+            "INSERT INTO user_settings (chat_id, timezone) VALUES (?, ?) ON CONFLICT(chat_id) DO \
+             UPDATE SET timezone = excluded.timezone",
+            chat_id,
+            timezone,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to set timezone in SQLite: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn set_quiet_hours(
+        &self,
+        chat_id: ChatId,
+        quiet_hours: Option<QuietHours>,
+    ) -> StorageResult<()> {
+        tracing::debug!("Setting quiet hours of chat {} to {:?}", chat_id, quiet_hours);
+
+        let chat_id = chat_id.0;
+        let quiet_hours = quiet_hours.map(|quiet_hours| quiet_hours.to_string());
+
+        query!(
+            "INSERT INTO user_settings (chat_id, quiet_hours) VALUES (?, ?) ON CONFLICT(chat_id) \
+             DO UPDATE SET quiet_hours = excluded.quiet_hours",
+            chat_id,
+            quiet_hours,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to set quiet hours in SQLite: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn toggle_notification_option(
+        &self,
+        chat_id: ChatId,
+        option: NotificationOption,
+    ) -> StorageResult<bool> {
+        tracing::debug!("Toggling notification option {option:?} of chat {chat_id}");
+        let chat_id = chat_id.0;
+
+        let enabled = match option {
+            NotificationOption::Silent =>
+                query_scalar!(
+                    "INSERT INTO user_settings (chat_id, silent_notifications) VALUES (?, TRUE) \
+                     ON CONFLICT(chat_id) DO UPDATE SET silent_notifications = NOT \
+                     silent_notifications RETURNING silent_notifications",
+                    chat_id,
+                )
+                .fetch_one(&self.pool)
+                .await,
+            NotificationOption::LinkPreviews =>
+                query_scalar!(
+                    "INSERT INTO user_settings (chat_id, link_previews) VALUES (?, TRUE) ON \
+                     CONFLICT(chat_id) DO UPDATE SET link_previews = NOT link_previews RETURNING \
+                     link_previews",
+                    chat_id,
+                )
+                .fetch_one(&self.pool)
+                .await,
+        }
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to toggle notification option in SQLite: {e}"))
+        })?;
+
+        Ok(enabled)
+    }
+
+    async fn set_language(&self, chat_id: ChatId, language: Language) -> StorageResult<()> {
+        tracing::debug!("Setting language of chat {} to {}", chat_id, language);
+
+        let chat_id = chat_id.0;
+        let language = language.code();
+
+        query!(
+            "INSERT INTO user_settings (chat_id, language) VALUES (?, ?) ON CONFLICT(chat_id) DO \
+             UPDATE SET language = excluded.language",
+            chat_id,
+            language,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to set language in SQLite: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn set_page_size(&self, chat_id: ChatId, page_size: usize) -> StorageResult<()> {
+        tracing::debug!("Setting page size of chat {} to {}", chat_id, page_size);
+
+        let chat_id = chat_id.0;
+        let page_size = i64::try_from(page_size)
+            .map_err(|e| StorageError::DbError(format!("Invalid page size {page_size}: {e}")))?;
+
+        query!(
+            "INSERT INTO user_settings (chat_id, page_size) VALUES (?, ?) ON CONFLICT(chat_id) DO \
+             UPDATE SET page_size = excluded.page_size",
+            chat_id,
+            page_size,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::DbError(format!("Failed to set page size in SQLite: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn add_digest_entries(
+        &self,
+        chat_id: ChatId,
+        entries: &[DigestEntry],
+    ) -> StorageResult<()> {
+        tracing::debug!("Adding {} issues to the digest of chat {}", entries.len(), chat_id);
+
+        let chat_id = chat_id.0;
+        let current_time = Utc::now().timestamp();
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        for entry in entries {
+            let payload = serde_json::to_string(&entry.issue)
+                .map_err(|e| StorageError::DbError(format!("Failed to serialize issue: {e}")))?;
+            query!(
+                "INSERT OR IGNORE INTO digest_entries (chat_id, repository_full_name, issue_id, \
+                 payload, added_at) VALUES (?, ?, ?, ?, ?)",
+                chat_id,
+                entry.repo_name_with_owner,
+                entry.issue.id,
+                payload,
+                current_time,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to add digest entry to SQLite: {e}"))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
+
+    async fn get_pending_digests(&self) -> StorageResult<Vec<PendingDigest>> {
+        let rows = query!(
+            "SELECT DISTINCT d.chat_id AS \"chat_id!\", s.last_digest_at AS \"last_digest_at?\" \
+             FROM digest_entries d LEFT JOIN user_settings s ON s.chat_id = d.chat_id WHERE \
+             d.chat_id NOT IN (SELECT chat_id FROM inactive_chats)"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get pending digests from SQLite: {e}"))
+        })?;
+
+        let mut pending = Vec::with_capacity(rows.len());
+        for r in rows {
+            let chat_id = ChatId(r.chat_id);
+            let settings = self.get_user_settings(chat_id).await?;
+            pending.push(PendingDigest { chat_id, settings, last_digest_at: r.last_digest_at });
+        }
+
+        Ok(pending)
+    }
+
+    async fn get_digest_entries(&self, chat_id: ChatId) -> StorageResult<Vec<(i64, DigestEntry)>> {
+        let chat_id = chat_id.0;
+
+        let rows = query!(
+            "SELECT id AS \"id!\", repository_full_name, payload FROM digest_entries WHERE \
+             chat_id = ? ORDER BY id ASC",
+            chat_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to get digest entries from SQLite: {e}"))
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| match serde_json::from_str(&r.payload) {
+                Ok(issue) => Some((
+                    r.id,
+                    DigestEntry { repo_name_with_owner: r.repository_full_name, issue },
+                )),
+                Err(e) => {
+                    tracing::warn!("Skipping digest entry {} with an invalid payload: {e}", r.id);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    async fn complete_digest(&self, chat_id: ChatId, last_entry_id: i64) -> StorageResult<()> {
+        tracing::debug!("Completing digest of chat {}", chat_id);
+
+        let chat_id = chat_id.0;
+        let current_time = Utc::now().timestamp();
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to begin transaction in SQLite: {e}"))
+        })?;
+
+        query!("DELETE FROM digest_entries WHERE chat_id = ? AND id <= ?", chat_id, last_entry_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StorageError::DbError(format!("Failed to remove digest entries from SQLite: {e}"))
+            })?;
+
+        query!(
+            "INSERT INTO user_settings (chat_id, last_digest_at) VALUES (?, ?) ON \
+             CONFLICT(chat_id) DO UPDATE SET last_digest_at = excluded.last_digest_at",
+            chat_id,
+            current_time,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            StorageError::DbError(format!("Failed to record digest time in SQLite: {e}"))
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            StorageError::DbError(format!("Failed to commit transaction in SQLite: {e}"))
+        })?;
+
+        Ok(())
+    }
 }
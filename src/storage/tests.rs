@@ -1,8 +1,19 @@
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use teloxide::types::ChatId;
 
-use super::{RepoEntity, RepoStorage, sqlite::SqliteStorage};
+use super::{
+    DeliveryMode, DigestEntry, IssueFilter, IssueFilters, Language, Notification,
+    NotificationOption, OwnerSubscription, QuietHours, RepoEntity, RepoStorage, SearchFilter,
+    UserSettings, parse_timezone, sqlite::SqliteStorage,
+};
+use crate::github::issues::{
+    IssuesRepositoryIssuesNodes, IssuesRepositoryIssuesNodesLabels,
+    IssuesRepositoryIssuesNodesLabelsNodes,
+};
 
 async fn create_in_memory_storage() -> SqliteStorage {
     SqliteStorage::new("sqlite::memory:").await.unwrap()
@@ -73,6 +84,22 @@ async fn test_get_all_repos() {
     assert!(all_repos.get(&chat_id2).unwrap().contains(&repo2));
 }
 
+#[tokio::test]
+async fn test_get_repo_subscribers() {
+    let storage = create_in_memory_storage().await;
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+    let other_repo = RepoEntity::from_str("owner/other").unwrap();
+
+    storage.add_repository(ChatId(1), repo.clone()).await.unwrap();
+    storage.add_repository(ChatId(2), repo.clone()).await.unwrap();
+    storage.add_repository(ChatId(3), other_repo).await.unwrap();
+
+    let mut subscribers = storage.get_repo_subscribers("Owner/Repo").await.unwrap();
+    subscribers.sort_by_key(|(chat_id, _)| chat_id.0);
+
+    assert_eq!(subscribers, vec![(ChatId(1), repo.clone()), (ChatId(2), repo)]);
+}
+
 #[tokio::test]
 async fn test_poll_time() {
     let storage = create_in_memory_storage().await;
@@ -120,3 +147,498 @@ async fn test_count_repos_per_user() {
     let count = storage.count_repos_per_user(chat_id).await.unwrap();
     assert_eq!(count, 2);
 }
+
+#[tokio::test]
+async fn test_notified_issues() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+
+    storage.add_repository(chat_id, repo.clone()).await.unwrap();
+    let notified = storage.get_notified_issues(chat_id, &repo).await.unwrap();
+    assert!(notified.is_empty());
+
+    let issue_ids = vec!["issue1".to_string(), "issue2".to_string()];
+    assert_eq!(storage.mark_issues_notified(chat_id, &repo, &issue_ids).await.unwrap(), issue_ids);
+    // Marking the same issue twice is a no-op, only the new one is returned
+    let all_ids = vec!["issue1".to_string(), "issue3".to_string()];
+    let marked = storage.mark_issues_notified(chat_id, &repo, &all_ids).await.unwrap();
+    assert_eq!(marked, vec!["issue3".to_string()]);
+    storage.unmark_issues_notified(chat_id, &repo, &marked).await.unwrap();
+
+    let notified = storage.get_notified_issues(chat_id, &repo).await.unwrap();
+    assert_eq!(notified.len(), 2);
+    assert!(notified.contains("issue1"));
+    assert!(notified.contains("issue2"));
+
+    // The ledger is per chat
+    let other_notified = storage.get_notified_issues(ChatId(2), &repo).await.unwrap();
+    assert!(other_notified.is_empty());
+}
+
+#[tokio::test]
+async fn test_remove_repository_clears_notified_issues() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+
+    storage.add_repository(chat_id, repo.clone()).await.unwrap();
+    storage.mark_issues_notified(chat_id, &repo, &["issue1".to_string()]).await.unwrap();
+    storage.remove_repository(chat_id, &repo.name_with_owner).await.unwrap();
+
+    let notified = storage.get_notified_issues(chat_id, &repo).await.unwrap();
+    assert!(notified.is_empty());
+}
+
+#[tokio::test]
+async fn test_rename_repository() {
+    let storage = create_in_memory_storage().await;
+    let old_repo = RepoEntity::from_str("Owner/Old").unwrap();
+    let new_repo = RepoEntity::from_str("owner/new").unwrap();
+
+    // Chat 1 only tracks the old name, chat 2 already tracks both
+    storage.add_repository(ChatId(1), old_repo.clone()).await.unwrap();
+    storage.toggle_label(ChatId(1), &old_repo, "bug").await.unwrap();
+    storage.set_last_poll_time(ChatId(1), &old_repo).await.unwrap();
+    storage.mark_issues_notified(ChatId(1), &old_repo, &["issue1".to_string()]).await.unwrap();
+    storage.add_repository(ChatId(2), old_repo.clone()).await.unwrap();
+    storage.add_repository(ChatId(2), new_repo.clone()).await.unwrap();
+
+    storage.rename_repository(&old_repo.name_with_owner, &new_repo).await.unwrap();
+
+    assert_eq!(storage.get_repos_per_user(ChatId(1)).await.unwrap(), vec![new_repo.clone()]);
+    assert_eq!(storage.get_repos_per_user(ChatId(2)).await.unwrap(), vec![new_repo.clone()]);
+    assert!(storage.get_tracked_labels(ChatId(1), &new_repo).await.unwrap().contains("bug"));
+    assert!(storage.get_last_poll_time(ChatId(1), &new_repo).await.unwrap().is_some());
+    assert!(storage.get_last_poll_time(ChatId(1), &old_repo).await.unwrap().is_none());
+    assert!(storage.get_notified_issues(ChatId(1), &new_repo).await.unwrap().contains("issue1"));
+    assert!(storage.get_notified_issues(ChatId(1), &old_repo).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_owner_subscriptions() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+
+    assert!(storage.add_owner_subscription(chat_id, "rust-lang").await.unwrap());
+    // Owners are matched case-insensitively
+    assert!(!storage.add_owner_subscription(chat_id, "Rust-Lang").await.unwrap());
+    storage.add_owner_subscription(ChatId(2), "tokio-rs").await.unwrap();
+
+    let labels = HashSet::from(["E-easy".to_string()]);
+    assert!(storage.set_owner_tracked_labels(chat_id, "RUST-LANG", &labels).await.unwrap());
+    assert!(!storage.set_owner_tracked_labels(chat_id, "tokio-rs", &labels).await.unwrap());
+
+    assert_eq!(
+        storage.get_owner_subscriptions(chat_id).await.unwrap(),
+        vec![OwnerSubscription { owner: "rust-lang".to_string(), tracked_labels: labels.clone() }]
+    );
+    assert_eq!(
+        storage.get_all_owner_subscriptions().await.unwrap(),
+        HashMap::from([
+            (chat_id, HashSet::from(["rust-lang".to_string()])),
+            (ChatId(2), HashSet::from(["tokio-rs".to_string()])),
+        ])
+    );
+
+    assert!(storage.remove_owner_subscription(chat_id, "rust-lang").await.unwrap());
+    assert!(storage.get_owner_subscriptions(chat_id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_owner_subscribers() {
+    let storage = create_in_memory_storage().await;
+
+    storage.add_owner_subscription(ChatId(1), "rust-lang").await.unwrap();
+    storage.add_owner_subscription(ChatId(2), "rust-lang").await.unwrap();
+    storage.add_owner_subscription(ChatId(3), "tokio-rs").await.unwrap();
+    storage.deactivate_chat(ChatId(2)).await.unwrap();
+
+    // Owners are matched case-insensitively, inactive chats are skipped
+    assert_eq!(storage.get_owner_subscribers("Rust-Lang").await.unwrap(), vec![ChatId(1)]);
+    assert!(storage.get_owner_subscribers("serde-rs").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_tracked_labels_falls_back_to_owner() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let direct = RepoEntity::from_str("rust-lang/rust").unwrap();
+    let via_owner = RepoEntity::from_str("rust-lang/cargo").unwrap();
+    let untracked = RepoEntity::from_str("tokio-rs/tokio").unwrap();
+    let owner_labels = HashSet::from(["E-easy".to_string()]);
+
+    storage.add_repository(chat_id, direct.clone()).await.unwrap();
+    storage.add_owner_subscription(chat_id, "rust-lang").await.unwrap();
+    storage.set_owner_tracked_labels(chat_id, "rust-lang", &owner_labels).await.unwrap();
+
+    // A direct subscription keeps its own labels
+    assert!(!storage.get_tracked_labels(chat_id, &direct).await.unwrap().contains("E-easy"));
+    assert_eq!(storage.get_tracked_labels(chat_id, &via_owner).await.unwrap(), owner_labels);
+    assert!(storage.get_tracked_labels(chat_id, &untracked).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_remove_owner_subscription_clears_notified_issues() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let direct = RepoEntity::from_str("rust-lang/rust").unwrap();
+    let via_owner = RepoEntity::from_str("rust-lang/cargo").unwrap();
+    let issue_ids = ["issue1".to_string()];
+
+    storage.add_repository(chat_id, direct.clone()).await.unwrap();
+    storage.add_owner_subscription(chat_id, "rust-lang").await.unwrap();
+    storage.mark_issues_notified(chat_id, &direct, &issue_ids).await.unwrap();
+    storage.mark_issues_notified(chat_id, &via_owner, &issue_ids).await.unwrap();
+
+    storage.remove_owner_subscription(chat_id, "Rust-Lang").await.unwrap();
+
+    // Repositories that are still tracked directly keep their ledger
+    assert!(!storage.get_notified_issues(chat_id, &direct).await.unwrap().is_empty());
+    assert!(storage.get_notified_issues(chat_id, &via_owner).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_search_subscriptions() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let filter = SearchFilter {
+        language: Some("rust".to_string()),
+        min_stars: Some(100),
+        ..Default::default()
+    };
+
+    assert!(storage.add_search_subscription(chat_id, &filter).await.unwrap());
+    assert!(!storage.add_search_subscription(chat_id, &filter).await.unwrap());
+    storage.add_search_subscription(ChatId(2), &SearchFilter::default()).await.unwrap();
+
+    let searches = storage.get_search_subscriptions(chat_id).await.unwrap();
+    assert_eq!(searches.len(), 1);
+    assert_eq!(searches[0].filter, filter);
+    assert_eq!(searches[0].created_at, searches[0].last_poll_time);
+    assert_eq!(storage.get_all_search_subscriptions().await.unwrap().len(), 2);
+
+    storage.set_search_poll_time(searches[0].id).await.unwrap();
+
+    // Searches can only be removed by the chat that owns them
+    assert!(!storage.remove_search_subscription(ChatId(2), searches[0].id).await.unwrap());
+    assert!(storage.remove_search_subscription(chat_id, searches[0].id).await.unwrap());
+    assert!(storage.get_search_subscriptions(chat_id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_toggle_issue_filter() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+    let untracked = RepoEntity::from_str("owner/other").unwrap();
+
+    storage.add_repository(chat_id, repo.clone()).await.unwrap();
+    assert_eq!(storage.get_issue_filters(chat_id, &repo).await.unwrap(), IssueFilters::default());
+
+    assert!(storage.toggle_issue_filter(chat_id, &repo, IssueFilter::Assigned).await.unwrap());
+    assert!(storage.toggle_issue_filter(chat_id, &repo, IssueFilter::LinkedPr).await.unwrap());
+    assert!(!storage.toggle_issue_filter(chat_id, &repo, IssueFilter::LinkedPr).await.unwrap());
+    assert_eq!(
+        storage.get_issue_filters(chat_id, &repo).await.unwrap(),
+        IssueFilters { exclude_assigned: true, exclude_linked_prs: false }
+    );
+
+    // Repositories that are not tracked directly keep the defaults
+    assert!(
+        !storage.toggle_issue_filter(chat_id, &untracked, IssueFilter::Assigned).await.unwrap()
+    );
+    assert_eq!(
+        storage.get_issue_filters(chat_id, &untracked).await.unwrap(),
+        IssueFilters::default()
+    );
+}
+
+#[tokio::test]
+async fn test_mute_repository() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+    let untracked = RepoEntity::from_str("owner/other").unwrap();
+    let until = 1_900_000_000;
+
+    storage.add_repository(chat_id, repo.clone()).await.unwrap();
+    assert_eq!(storage.get_muted_until(chat_id, &repo).await.unwrap(), None);
+
+    assert!(storage.mute_repository(chat_id, &repo, until).await.unwrap());
+    assert_eq!(storage.get_muted_until(chat_id, &repo).await.unwrap(), Some(until));
+    // The next poll picks up where the mute ends
+    assert_eq!(storage.get_last_poll_time(chat_id, &repo).await.unwrap(), Some(until));
+
+    // Repositories that are not tracked directly can not be muted
+    assert!(!storage.mute_repository(chat_id, &untracked, until).await.unwrap());
+    assert_eq!(storage.get_last_poll_time(chat_id, &untracked).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_inactive_chats_are_not_polled() {
+    let storage = create_in_memory_storage().await;
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+    let filter = SearchFilter::default();
+
+    for chat_id in [ChatId(1), ChatId(2)] {
+        storage.add_repository(chat_id, repo.clone()).await.unwrap();
+        storage.add_owner_subscription(chat_id, "rust-lang").await.unwrap();
+        storage.add_search_subscription(chat_id, &filter).await.unwrap();
+    }
+
+    assert!(storage.deactivate_chat(ChatId(1)).await.unwrap());
+    assert!(!storage.deactivate_chat(ChatId(1)).await.unwrap());
+
+    let all_repos = storage.get_all_repos().await.unwrap();
+    assert_eq!(all_repos.keys().collect::<Vec<_>>(), vec![&ChatId(2)]);
+    let subscribers = storage.get_repo_subscribers(&repo.name_with_owner).await.unwrap();
+    assert_eq!(subscribers, vec![(ChatId(2), repo.clone())]);
+    let owners = storage.get_all_owner_subscriptions().await.unwrap();
+    assert_eq!(owners.keys().collect::<Vec<_>>(), vec![&ChatId(2)]);
+    let searches = storage.get_all_search_subscriptions().await.unwrap();
+    assert_eq!(searches.iter().map(|s| s.chat_id).collect::<Vec<_>>(), vec![ChatId(2)]);
+
+    // The subscriptions are kept until the chat is purged
+    assert_eq!(storage.get_repos_per_user(ChatId(1)).await.unwrap(), vec![repo.clone()]);
+
+    assert!(storage.reactivate_chat(ChatId(1)).await.unwrap());
+    assert!(!storage.reactivate_chat(ChatId(1)).await.unwrap());
+    assert_eq!(storage.get_all_repos().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_purge_inactive_chats() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+
+    storage.add_repository(chat_id, repo.clone()).await.unwrap();
+    storage.set_last_poll_time(chat_id, &repo).await.unwrap();
+    storage.mark_issues_notified(chat_id, &repo, &["issue1".to_string()]).await.unwrap();
+    storage.add_owner_subscription(chat_id, "rust-lang").await.unwrap();
+    storage.add_search_subscription(chat_id, &SearchFilter::default()).await.unwrap();
+    storage.set_delivery_mode(chat_id, DeliveryMode::Hourly).await.unwrap();
+    storage.deactivate_chat(chat_id).await.unwrap();
+
+    // Chats deactivated within the grace period are kept
+    let now = chrono::Utc::now().timestamp();
+    assert!(storage.purge_inactive_chats(now - 60).await.unwrap().is_empty());
+    assert_eq!(storage.get_repos_per_user(chat_id).await.unwrap().len(), 1);
+
+    let purged = storage.purge_inactive_chats(now + 60).await.unwrap();
+
+    assert_eq!(purged, vec![chat_id]);
+    assert!(storage.get_repos_per_user(chat_id).await.unwrap().is_empty());
+    assert_eq!(storage.get_last_poll_time(chat_id, &repo).await.unwrap(), None);
+    assert!(storage.get_notified_issues(chat_id, &repo).await.unwrap().is_empty());
+    assert!(storage.get_owner_subscriptions(chat_id).await.unwrap().is_empty());
+    assert!(storage.get_search_subscriptions(chat_id).await.unwrap().is_empty());
+    assert_eq!(storage.get_user_settings(chat_id).await.unwrap(), UserSettings::default());
+    // The chat is active again, should it come back
+    assert!(!storage.reactivate_chat(chat_id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_prune_notified_issues() {
+    let storage = create_in_memory_storage().await;
+    let repo = RepoEntity::from_str("owner/repo").unwrap();
+    let issue_ids = vec!["issue1".to_string(), "issue2".to_string()];
+
+    storage.mark_issues_notified(ChatId(1), &repo, &issue_ids).await.unwrap();
+    storage.mark_issues_notified(ChatId(2), &repo, &issue_ids[..1]).await.unwrap();
+
+    // Issues notified after the cutoff are kept
+    let now = chrono::Utc::now().timestamp();
+    assert_eq!(storage.prune_notified_issues(now - 60).await.unwrap(), 0);
+    assert_eq!(storage.get_notified_issues(ChatId(1), &repo).await.unwrap().len(), 2);
+
+    let pruned = storage.prune_notified_issues(now + 60).await.unwrap();
+
+    assert_eq!(pruned, 3);
+    assert!(storage.get_notified_issues(ChatId(1), &repo).await.unwrap().is_empty());
+    assert!(storage.get_notified_issues(ChatId(2), &repo).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_notification_outbox() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let now = chrono::Utc::now().timestamp();
+    let issue = IssuesRepositoryIssuesNodes {
+        id: "issue1".to_string(),
+        title: "Fix typo".to_string(),
+        labels: Some(IssuesRepositoryIssuesNodesLabels {
+            nodes: Some(vec![IssuesRepositoryIssuesNodesLabelsNodes {
+                name: "good first issue".to_string(),
+                color: "7057ff".to_string(),
+            }]),
+        }),
+        ..Default::default()
+    };
+    let notification = Notification::NewIssues {
+        repo_name_with_owner: "owner/repo".to_string(),
+        new_issues: vec![issue],
+        labeled_issues: Vec::new(),
+    };
+
+    storage.enqueue_notification(chat_id, &notification, now + 60, 0).await.unwrap();
+
+    // Not due yet
+    assert!(storage.get_due_notifications(now, 10).await.unwrap().is_empty());
+
+    let due = storage.get_due_notifications(now + 60, 10).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].chat_id, chat_id);
+    assert_eq!(due[0].attempts, 0);
+    assert_eq!(
+        serde_json::to_value(&due[0].notification).unwrap(),
+        serde_json::to_value(&notification).unwrap()
+    );
+    assert_eq!(due[0].sent_parts, 0);
+
+    let id = due[0].id;
+    // The parts delivered before the failure are not sent again
+    storage.reschedule_notification(id, now + 120, "network down", 1).await.unwrap();
+    assert!(storage.get_due_notifications(now + 60, 10).await.unwrap().is_empty());
+    let due = storage.get_due_notifications(now + 120, 10).await.unwrap();
+    assert_eq!(due[0].attempts, 1);
+    assert_eq!(due[0].sent_parts, 1);
+
+    // Notifications of inactive chats wait until the chat is active again
+    storage.deactivate_chat(chat_id).await.unwrap();
+    assert!(storage.get_due_notifications(now + 120, 10).await.unwrap().is_empty());
+    storage.reactivate_chat(chat_id).await.unwrap();
+
+    // Postponing a notification does not count as an attempt
+    storage.postpone_notification(id, now + 180).await.unwrap();
+    assert!(storage.get_due_notifications(now + 120, 10).await.unwrap().is_empty());
+    let due = storage.get_due_notifications(now + 180, 10).await.unwrap();
+    assert_eq!(due[0].attempts, 1);
+
+    storage.dead_letter_notification(id, "network down").await.unwrap();
+    assert!(storage.get_due_notifications(now + 120, 10).await.unwrap().is_empty());
+
+    storage.enqueue_notification(chat_id, &notification, now, 2).await.unwrap();
+    let due = storage.get_due_notifications(now, 10).await.unwrap();
+    assert_eq!(due[0].sent_parts, 2);
+    storage.delete_notification(due[0].id).await.unwrap();
+    assert!(storage.get_due_notifications(now, 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_digest_entries() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let entry = |id: &str| DigestEntry {
+        repo_name_with_owner: "owner/repo".to_string(),
+        issue: IssuesRepositoryIssuesNodes { id: id.to_string(), ..Default::default() },
+    };
+
+    assert_eq!(storage.get_user_settings(chat_id).await.unwrap(), UserSettings::default());
+    storage.set_delivery_mode(chat_id, DeliveryMode::Hourly).await.unwrap();
+    assert_eq!(
+        storage.get_user_settings(chat_id).await.unwrap().delivery_mode,
+        DeliveryMode::Hourly
+    );
+
+    storage.add_digest_entries(chat_id, &[entry("issue1"), entry("issue2")]).await.unwrap();
+    // Issues already waiting are not added twice
+    storage.add_digest_entries(chat_id, &[entry("issue1")]).await.unwrap();
+
+    let pending = storage.get_pending_digests().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].chat_id, chat_id);
+    assert_eq!(pending[0].settings.delivery_mode, DeliveryMode::Hourly);
+    assert!(pending[0].last_digest_at.is_some());
+
+    let entries = storage.get_digest_entries(chat_id).await.unwrap();
+    let ids: Vec<_> = entries.iter().map(|(_, entry)| entry.issue.id.as_str()).collect();
+    assert_eq!(ids, vec!["issue1", "issue2"]);
+
+    // Digests of inactive chats wait until the chat is active again
+    storage.deactivate_chat(chat_id).await.unwrap();
+    assert!(storage.get_pending_digests().await.unwrap().is_empty());
+    storage.reactivate_chat(chat_id).await.unwrap();
+
+    // Entries added while the digest was being sent are kept for the next one
+    let last_entry_id = entries.last().unwrap().0;
+    storage.add_digest_entries(chat_id, &[entry("issue3")]).await.unwrap();
+    storage.complete_digest(chat_id, last_entry_id).await.unwrap();
+
+    let entries = storage.get_digest_entries(chat_id).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].1.issue.id, "issue3");
+}
+
+#[tokio::test]
+async fn test_timezone_and_quiet_hours() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+    let timezone = parse_timezone("Europe/Berlin").unwrap();
+    let quiet_hours = QuietHours::from_str("22:00-07:00").unwrap();
+
+    storage.set_timezone(chat_id, timezone).await.unwrap();
+    storage.set_quiet_hours(chat_id, Some(quiet_hours)).await.unwrap();
+    // Changing the delivery mode keeps the other settings
+    storage.set_delivery_mode(chat_id, DeliveryMode::Hourly).await.unwrap();
+
+    let settings = storage.get_user_settings(chat_id).await.unwrap();
+    assert_eq!(
+        settings,
+        UserSettings {
+            delivery_mode: DeliveryMode::Hourly,
+            timezone,
+            quiet_hours: Some(quiet_hours),
+            ..Default::default()
+        }
+    );
+
+    storage.set_quiet_hours(chat_id, None).await.unwrap();
+    assert_eq!(storage.get_user_settings(chat_id).await.unwrap().quiet_hours, None);
+}
+
+#[tokio::test]
+async fn test_notification_preferences() {
+    let storage = create_in_memory_storage().await;
+    let chat_id = ChatId(1);
+
+    assert!(storage.toggle_notification_option(chat_id, NotificationOption::Silent).await.unwrap());
+    assert!(
+        storage
+            .toggle_notification_option(chat_id, NotificationOption::LinkPreviews)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !storage
+            .toggle_notification_option(chat_id, NotificationOption::LinkPreviews)
+            .await
+            .unwrap()
+    );
+    storage.set_language(chat_id, Language::Spanish).await.unwrap();
+    storage.set_page_size(chat_id, 5).await.unwrap();
+    storage.set_delivery_mode(chat_id, DeliveryMode::Hourly).await.unwrap();
+
+    let settings = storage.get_user_settings(chat_id).await.unwrap();
+    assert_eq!(
+        settings,
+        UserSettings {
+            delivery_mode: DeliveryMode::Hourly,
+            silent_notifications: true,
+            link_previews: false,
+            language: Language::Spanish,
+            page_size: 5,
+            ..Default::default()
+        }
+    );
+
+    // Pending digests carry the settings they are sent with
+    let entry = DigestEntry {
+        repo_name_with_owner: "owner/repo".to_string(),
+        issue: IssuesRepositoryIssuesNodes { id: "issue1".to_string(), ..Default::default() },
+    };
+    storage.add_digest_entries(chat_id, &[entry]).await.unwrap();
+    let pending = storage.get_pending_digests().await.unwrap();
+    assert_eq!(pending[0].settings, settings);
+}
@@ -0,0 +1,432 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::pagination::DEFAULT_PAGE_SIZE;
+
+/// Represents errors that can occur when parsing a `DeliveryMode`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryModeError {
+    /// The mode is not one of instant, hourly, daily or weekly.
+    #[error("Unknown delivery mode: {0}")]
+    UnknownMode(String),
+    /// The time of a daily or weekly digest is not `HH:MM`.
+    #[error("Invalid time, expected HH:MM: {0}")]
+    InvalidTime(String),
+}
+
+/// Represents errors that can occur when parsing the preferences of a chat.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SettingsError {
+    /// The timezone is not an IANA timezone name.
+    #[error("Unknown timezone, expected a name like Europe/Berlin: {0}")]
+    InvalidTimezone(String),
+    /// The quiet hours are not `HH:MM-HH:MM`.
+    #[error("Invalid quiet hours, expected HH:MM-HH:MM: {0}")]
+    InvalidQuietHours(String),
+    /// The language is not one notifications can be sent in.
+    #[error("Unknown language: {0}")]
+    UnknownLanguage(String),
+}
+
+/// The page sizes a chat can choose from for its lists.
+pub const PAGE_SIZES: [usize; 3] = [5, 10, 20];
+
+/// The time of day digests are sent at when none is given.
+pub const DEFAULT_DIGEST_TIME: NaiveTime = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+/// How a chat receives its notifications: one message per poll, or collected
+/// into a digest that is sent on a schedule. Times are in the chat's timezone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryMode {
+    /// Notify about new issues as soon as they are found.
+    #[default]
+    Instant,
+    /// Send a digest at the start of every hour.
+    Hourly,
+    /// Send a digest every day at the given time.
+    Daily(NaiveTime),
+    /// Send a digest every Monday at the given time.
+    Weekly(NaiveTime),
+}
+
+impl DeliveryMode {
+    /// Returns `true` if notifications are collected into a digest.
+    pub fn is_digest(&self) -> bool {
+        !matches!(self, Self::Instant)
+    }
+
+    /// The latest time a digest was scheduled at, at or before `now`, with the
+    /// schedule in the given timezone. `None` for instant delivery.
+    pub fn last_digest_slot(&self, now: DateTime<Utc>, timezone: Tz) -> Option<DateTime<Utc>> {
+        let now = now.with_timezone(&timezone).naive_local();
+        let today = now.date();
+
+        let (slot, period) = match *self {
+            Self::Instant => return None,
+            Self::Hourly => (today.and_hms_opt(now.hour(), 0, 0)?, TimeDelta::hours(1)),
+            Self::Daily(time) => (today.and_time(time), TimeDelta::days(1)),
+            Self::Weekly(time) => {
+                let monday =
+                    today - TimeDelta::days(i64::from(now.weekday().num_days_from_monday()));
+                (monday.and_time(time), TimeDelta::weeks(1))
+            }
+        };
+
+        let slot = if slot <= now { slot } else { slot - period };
+        Some(local_to_utc(timezone, slot))
+    }
+}
+
+impl FromStr for DeliveryMode {
+    type Err = DeliveryModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let mode = parts.next().unwrap_or_default().to_lowercase();
+        let time = parts.next();
+        if parts.next().is_some() {
+            return Err(DeliveryModeError::UnknownMode(s.trim().to_string()));
+        }
+
+        let parse_time = |time: Option<&str>| {
+            time.map_or(Ok(DEFAULT_DIGEST_TIME), |time| {
+                NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| DeliveryModeError::InvalidTime(time.to_string()))
+            })
+        };
+
+        match (mode.as_str(), time) {
+            ("instant", None) => Ok(Self::Instant),
+            ("hourly", None) => Ok(Self::Hourly),
+            ("daily", time) => parse_time(time).map(Self::Daily),
+            ("weekly", time) => parse_time(time).map(Self::Weekly),
+            _ => Err(DeliveryModeError::UnknownMode(s.trim().to_string())),
+        }
+    }
+}
+
+impl fmt::Display for DeliveryMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Instant => write!(f, "instant"),
+            Self::Hourly => write!(f, "hourly"),
+            Self::Daily(time) => write!(f, "daily {}", time.format("%H:%M")),
+            Self::Weekly(time) => write!(f, "weekly {}", time.format("%H:%M")),
+        }
+    }
+}
+
+/// A daily window in which a chat does not want to be notified, in the chat's
+/// timezone. The window may span midnight, e.g. 22:00-07:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    /// When the quiet hours start.
+    pub start: NaiveTime,
+    /// When the quiet hours end.
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Returns `true` if the given time of day is within the quiet hours.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = SettingsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SettingsError::InvalidQuietHours(s.trim().to_string());
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").map_err(|_| invalid())?;
+        let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").map_err(|_| invalid())?;
+        if start == end {
+            return Err(invalid());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// The language notifications are sent in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    /// English.
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    /// Spanish.
+    #[serde(rename = "es")]
+    Spanish,
+}
+
+impl Language {
+    /// All languages notifications can be sent in.
+    pub const ALL: [Self; 2] = [Self::English, Self::Spanish];
+
+    /// The ISO 639-1 code of the language.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Spanish => "es",
+        }
+    }
+
+    /// The name of the language, in the language itself.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Spanish => "Español",
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = SettingsError;
+
+    /// Parse the ISO 639-1 code of a language, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code().eq_ignore_ascii_case(s))
+            .ok_or_else(|| SettingsError::UnknownLanguage(s.to_string()))
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// One of the on/off preferences of `UserSettings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationOption {
+    /// See `UserSettings::silent_notifications`.
+    #[serde(rename = "s")]
+    Silent,
+    /// See `UserSettings::link_previews`.
+    #[serde(rename = "lp")]
+    LinkPreviews,
+}
+
+/// Parse an IANA timezone name, ignoring case.
+pub fn parse_timezone(input: &str) -> Result<Tz, SettingsError> {
+    let input = input.trim();
+    chrono_tz::TZ_VARIANTS
+        .into_iter()
+        .find(|timezone| timezone.name().eq_ignore_ascii_case(input))
+        .ok_or_else(|| SettingsError::InvalidTimezone(input.to_string()))
+}
+
+/// The preferences of a chat. Chats that never changed them use the
+/// defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSettings {
+    /// How notifications are delivered.
+    pub delivery_mode: DeliveryMode,
+    /// The timezone the digest schedule and quiet hours are in.
+    pub timezone: Tz,
+    /// When the chat does not want to be notified, if ever.
+    pub quiet_hours: Option<QuietHours>,
+    /// Send notifications without sound.
+    pub silent_notifications: bool,
+    /// Show a preview of the first link of notifications.
+    pub link_previews: bool,
+    /// The language notifications are sent in.
+    pub language: Language,
+    /// The number of items per page of the repository and label lists.
+    pub page_size: usize,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            delivery_mode: DeliveryMode::default(),
+            timezone: Tz::UTC,
+            quiet_hours: None,
+            silent_notifications: false,
+            link_previews: false,
+            language: Language::default(),
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl UserSettings {
+    /// The end of the quiet hours, if `now` is within them.
+    pub fn quiet_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let quiet_hours = self.quiet_hours?;
+        let now = now.with_timezone(&self.timezone).naive_local();
+        if !quiet_hours.contains(now.time()) {
+            return None;
+        }
+
+        let end = now.date().and_time(quiet_hours.end);
+        let end = if end > now { end } else { end + TimeDelta::days(1) };
+        Some(local_to_utc(self.timezone, end))
+    }
+
+    /// The latest time a digest was scheduled at, at or before `now`. `None`
+    /// for instant delivery.
+    pub fn last_digest_slot(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.delivery_mode.last_digest_slot(now, self.timezone)
+    }
+}
+
+/// Convert a local time to UTC. Times that are skipped when the clocks move
+/// forward are moved forward as well; times that repeat when the clocks move
+/// back resolve to their first occurrence.
+fn local_to_utc(timezone: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| timezone.from_local_datetime(&(local + TimeDelta::hours(1))).earliest())
+        .map_or_else(|| local.and_utc(), |dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for input in ["instant", "hourly", "daily 18:30", "weekly 09:00"] {
+            assert_eq!(input.parse::<DeliveryMode>().unwrap().to_string(), input);
+        }
+
+        assert_eq!(" Daily ".parse(), Ok(DeliveryMode::Daily(DEFAULT_DIGEST_TIME)));
+        assert_eq!("weekly 7:05".parse(), Ok(DeliveryMode::Weekly(time(7, 5))));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            "monthly".parse::<DeliveryMode>(),
+            Err(DeliveryModeError::UnknownMode("monthly".to_string()))
+        );
+        assert_eq!(
+            "daily 25:00".parse::<DeliveryMode>(),
+            Err(DeliveryModeError::InvalidTime("25:00".to_string()))
+        );
+        assert!("hourly 10:00".parse::<DeliveryMode>().is_err());
+        assert!("".parse::<DeliveryMode>().is_err());
+    }
+
+    #[test]
+    fn test_last_digest_slot() {
+        // A Wednesday
+        let now = Utc.with_ymd_and_hms(2025, 6, 11, 10, 15, 0).unwrap();
+
+        assert_eq!(DeliveryMode::Instant.last_digest_slot(now, Tz::UTC), None);
+        assert_eq!(
+            DeliveryMode::Hourly.last_digest_slot(now, Tz::UTC),
+            Some(Utc.with_ymd_and_hms(2025, 6, 11, 10, 0, 0).unwrap())
+        );
+        assert_eq!(
+            DeliveryMode::Daily(time(9, 0)).last_digest_slot(now, Tz::UTC),
+            Some(Utc.with_ymd_and_hms(2025, 6, 11, 9, 0, 0).unwrap())
+        );
+        assert_eq!(
+            DeliveryMode::Daily(time(18, 0)).last_digest_slot(now, Tz::UTC),
+            Some(Utc.with_ymd_and_hms(2025, 6, 10, 18, 0, 0).unwrap())
+        );
+        assert_eq!(
+            DeliveryMode::Weekly(time(9, 0)).last_digest_slot(now, Tz::UTC),
+            Some(Utc.with_ymd_and_hms(2025, 6, 9, 9, 0, 0).unwrap())
+        );
+
+        // Monday morning, before the weekly digest is due
+        let monday = Utc.with_ymd_and_hms(2025, 6, 9, 8, 0, 0).unwrap();
+        assert_eq!(
+            DeliveryMode::Weekly(time(9, 0)).last_digest_slot(monday, Tz::UTC),
+            Some(Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_last_digest_slot_in_timezone() {
+        let settings = UserSettings {
+            delivery_mode: DeliveryMode::Daily(time(9, 0)),
+            timezone: parse_timezone("america/new_york").unwrap(),
+            ..Default::default()
+        };
+
+        // 9:00 in New York is 13:00 UTC in summer
+        let now = Utc.with_ymd_and_hms(2025, 6, 11, 12, 0, 0).unwrap();
+        assert_eq!(
+            settings.last_digest_slot(now),
+            Some(Utc.with_ymd_and_hms(2025, 6, 10, 13, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_quiet_hours() {
+        let quiet_hours: QuietHours = "22:00 - 7:30".parse().unwrap();
+        assert_eq!(quiet_hours, QuietHours { start: time(22, 0), end: time(7, 30) });
+        assert_eq!(quiet_hours.to_string(), "22:00-07:30");
+
+        assert!("22:00".parse::<QuietHours>().is_err());
+        assert!("22:00-22:00".parse::<QuietHours>().is_err());
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!("ES".parse(), Ok(Language::Spanish));
+        for language in Language::ALL {
+            assert_eq!(language.code().parse(), Ok(language));
+        }
+        assert_eq!(
+            "klingon".parse::<Language>(),
+            Err(SettingsError::UnknownLanguage("klingon".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_quiet_until() {
+        let settings = UserSettings {
+            timezone: parse_timezone("Europe/Berlin").unwrap(),
+            quiet_hours: Some(QuietHours { start: time(22, 0), end: time(7, 0) }),
+            ..Default::default()
+        };
+
+        // 23:30 in Berlin, quiet until 7:00 the next morning
+        let night = Utc.with_ymd_and_hms(2025, 6, 11, 21, 30, 0).unwrap();
+        assert_eq!(
+            settings.quiet_until(night),
+            Some(Utc.with_ymd_and_hms(2025, 6, 12, 5, 0, 0).unwrap())
+        );
+        // 6:00 in Berlin, quiet until 7:00
+        let morning = Utc.with_ymd_and_hms(2025, 6, 12, 4, 0, 0).unwrap();
+        assert_eq!(
+            settings.quiet_until(morning),
+            Some(Utc.with_ymd_and_hms(2025, 6, 12, 5, 0, 0).unwrap())
+        );
+        // 12:00 in Berlin
+        let noon = Utc.with_ymd_and_hms(2025, 6, 12, 10, 0, 0).unwrap();
+        assert_eq!(settings.quiet_until(noon), None);
+        assert_eq!(UserSettings::default().quiet_until(night), None);
+    }
+}
@@ -0,0 +1,259 @@
+#[cfg(test)]
+mod tests;
+
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use tokio::net::TcpListener;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::{github::issues, poller::GithubPoller};
+
+/// The path GitHub delivers webhook events to.
+pub const WEBHOOK_PATH: &str = "/github/webhook";
+
+const EVENT_HEADER: &str = "X-GitHub-Event";
+const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+
+/// The shared state of the webhook server.
+#[derive(Clone)]
+pub struct WebhookState {
+    secret: String,
+    poller: GithubPoller,
+    tasks: TaskTracker,
+}
+
+impl WebhookState {
+    /// Create a new `WebhookState`. Deliveries are verified with `secret` and
+    /// fed to `poller` on tasks spawned on `tasks`, so shutdown can wait for
+    /// the notifications still in flight.
+    pub fn new(secret: String, poller: GithubPoller, tasks: TaskTracker) -> Self {
+        Self { secret, poller, tasks }
+    }
+}
+
+/// Build the router accepting GitHub webhook deliveries.
+pub fn router(state: WebhookState) -> Router {
+    Router::new().route(WEBHOOK_PATH, post(handle_github_webhook)).with_state(state)
+}
+
+/// Serve GitHub webhook deliveries on the given listener until `shutdown` is
+/// cancelled.
+pub async fn serve(
+    listener: TcpListener,
+    state: WebhookState,
+    shutdown: CancellationToken,
+) -> std::io::Result<()> {
+    tracing::info!("Listening for GitHub webhooks on {:?}", listener.local_addr());
+    axum::serve(listener, router(state)).with_graceful_shutdown(shutdown.cancelled_owned()).await
+}
+
+/// The parts of an `issues` webhook event we care about.
+#[derive(Debug, Deserialize)]
+pub struct IssuesEvent {
+    /// What happened to the issue, e.g. `opened` or `labeled`.
+    pub action: String,
+    /// The issue itself.
+    pub issue: WebhookIssue,
+    /// The label that was added, for `labeled` events.
+    pub label: Option<WebhookLabel>,
+    /// The repository the issue belongs to.
+    pub repository: WebhookRepository,
+}
+
+/// An issue as delivered in webhook payloads.
+#[derive(Debug, Deserialize)]
+pub struct WebhookIssue {
+    /// The GraphQL node id, which is the id the poller knows issues by.
+    pub node_id: String,
+    /// The title of the issue.
+    pub title: String,
+    /// The URL of the issue on github.com.
+    pub html_url: String,
+    /// Either `open` or `closed`.
+    pub state: String,
+    /// When the issue was opened, in RFC 3339.
+    pub created_at: String,
+    /// When the issue was last updated, in RFC 3339.
+    pub updated_at: String,
+    /// The labels currently on the issue.
+    #[serde(default)]
+    pub labels: Vec<WebhookLabel>,
+    /// The users assigned to the issue.
+    #[serde(default)]
+    pub assignees: Vec<WebhookUser>,
+    /// The user who opened the issue.
+    pub user: Option<WebhookUser>,
+    /// The description of the issue, in Markdown.
+    pub body: Option<String>,
+    /// The number of comments on the issue.
+    #[serde(default)]
+    pub comments: i64,
+    /// The reactions to the issue.
+    pub reactions: Option<WebhookReactions>,
+}
+
+/// The reaction summary of an issue as delivered in webhook payloads.
+#[derive(Debug, Deserialize)]
+pub struct WebhookReactions {
+    /// The number of reactions of any kind.
+    pub total_count: i64,
+}
+
+/// A user as delivered in webhook payloads.
+#[derive(Debug, Deserialize)]
+pub struct WebhookUser {
+    /// The login of the user.
+    pub login: String,
+}
+
+/// A label as delivered in webhook payloads.
+#[derive(Debug, Deserialize)]
+pub struct WebhookLabel {
+    /// The name of the label.
+    pub name: String,
+    /// The color of the label, as a hex code without `#`.
+    #[serde(default)]
+    pub color: String,
+}
+
+/// A repository as delivered in webhook payloads.
+#[derive(Debug, Deserialize)]
+pub struct WebhookRepository {
+    /// The `owner/name` of the repository.
+    pub full_name: String,
+}
+
+impl IssuesEvent {
+    /// Returns `true` if the event may produce a notification: an open issue
+    /// that was opened or labeled.
+    pub fn is_relevant(&self) -> bool {
+        matches!(self.action.as_str(), "opened" | "labeled") && self.issue.state == "open"
+    }
+
+    /// Convert the delivered issue into the shape returned by the issues
+    /// query. A `labeled` event becomes a labeled timeline item, so the issue
+    /// is classified exactly like a polled one. Payloads do not carry linked
+    /// pull requests, so a pushed issue never counts as having one.
+    pub fn into_issue(self) -> issues::IssuesRepositoryIssuesNodes {
+        let timeline_nodes = self.label.filter(|_| self.action == "labeled").map(|label| {
+            vec![Some(issues::IssuesRepositoryIssuesNodesTimelineItemsNodes::LabeledEvent(
+                issues::IssuesRepositoryIssuesNodesTimelineItemsNodesOnLabeledEvent {
+                    created_at: self.issue.updated_at.clone(),
+                    label:
+                        issues::IssuesRepositoryIssuesNodesTimelineItemsNodesOnLabeledEventLabel {
+                            name: label.name,
+                        },
+                },
+            ))]
+        });
+
+        issues::IssuesRepositoryIssuesNodes {
+            id: self.issue.node_id,
+            title: self.issue.title,
+            url: self.issue.html_url,
+            created_at: self.issue.created_at,
+            updated_at: self.issue.updated_at,
+            author: self
+                .issue
+                .user
+                .map(|user| issues::IssuesRepositoryIssuesNodesAuthor { login: user.login }),
+            body_text: self.issue.body.unwrap_or_default(),
+            comments: issues::IssuesRepositoryIssuesNodesComments {
+                total_count: self.issue.comments,
+            },
+            reactions: issues::IssuesRepositoryIssuesNodesReactions {
+                total_count: self.issue.reactions.map_or(0, |reactions| reactions.total_count),
+            },
+            labels: Some(issues::IssuesRepositoryIssuesNodesLabels {
+                nodes: Some(
+                    self.issue
+                        .labels
+                        .into_iter()
+                        .map(|label| issues::IssuesRepositoryIssuesNodesLabelsNodes {
+                            name: label.name,
+                            color: label.color,
+                        })
+                        .collect(),
+                ),
+            }),
+            timeline_items: issues::IssuesRepositoryIssuesNodesTimelineItems {
+                nodes: timeline_nodes,
+            },
+            assignees: issues::IssuesRepositoryIssuesNodesAssignees {
+                total_count: self.issue.assignees.len() as i64,
+            },
+            linked_items: issues::IssuesRepositoryIssuesNodesLinkedItems { nodes: None },
+        }
+    }
+}
+
+/// Verify the `X-Hub-Signature-256` header of a delivery: the hex encoded
+/// HMAC-SHA256 of the raw body, keyed with the webhook secret.
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: Option<&str>) -> bool {
+    let Some(signature) = signature.and_then(|s| s.strip_prefix("sha256=")) else {
+        return false;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret) else {
+        return false;
+    };
+
+    mac.update(body);
+    // Constant time comparison
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Handle a single webhook delivery. Relevant issues are handed to the poller
+/// in the background, so GitHub gets its response right away.
+async fn handle_github_webhook(
+    State(state): State<WebhookState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+    if !verify_signature(state.secret.as_bytes(), &body, signature) {
+        tracing::warn!("Rejected GitHub webhook delivery with an invalid signature");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let event = headers.get(EVENT_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if event != "issues" {
+        tracing::debug!("Ignoring GitHub webhook event: {event}");
+        return StatusCode::NO_CONTENT;
+    }
+
+    let event: IssuesEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::warn!("Failed to parse GitHub issues event: {e}");
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    if !event.is_relevant() {
+        tracing::debug!("Ignoring GitHub issues event with action: {}", event.action);
+        return StatusCode::NO_CONTENT;
+    }
+
+    let repo_name_with_owner = event.repository.full_name.clone();
+    let issue = event.into_issue();
+    state.tasks.spawn(async move {
+        if let Err(e) = state.poller.handle_pushed_issue(&repo_name_with_owner, issue).await {
+            tracing::error!(
+                "Failed to handle GitHub webhook issue for {repo_name_with_owner}: {e:?}"
+            );
+        }
+    });
+
+    StatusCode::ACCEPTED
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use axum::http::HeaderValue;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::*;
+use crate::{github::MockGithubClient, messaging::MockMessagingService, storage::MockRepoStorage};
+
+const SECRET: &str = "It's a Secret to Everybody";
+const OPENED_PAYLOAD: &str = include_str!("fixtures/issues_opened.json");
+const LABELED_PAYLOAD: &str = include_str!("fixtures/issues_labeled.json");
+
+// Helper to sign a payload the way GitHub does
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// Helper to build the state with a poller that only knows the given storage
+fn webhook_state(storage: MockRepoStorage) -> WebhookState {
+    let poller = GithubPoller::new(
+        Arc::new(MockGithubClient::new()),
+        Arc::new(storage),
+        Arc::new(MockMessagingService::new()),
+        10,
+        10,
+    );
+    WebhookState::new(SECRET.to_string(), poller, TaskTracker::new())
+}
+
+fn headers(event: &str, signature: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(EVENT_HEADER, HeaderValue::from_str(event).unwrap());
+    headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(signature).unwrap());
+    headers
+}
+
+#[test]
+fn test_verify_signature() {
+    // The example from GitHub's webhook documentation
+    let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    assert!(verify_signature(SECRET.as_bytes(), b"Hello, World!", Some(signature)));
+    assert!(!verify_signature(SECRET.as_bytes(), b"Hello, World?", Some(signature)));
+    assert!(!verify_signature(b"another secret", b"Hello, World!", Some(signature)));
+    assert!(!verify_signature(SECRET.as_bytes(), b"Hello, World!", Some("sha256=zz")));
+    assert!(!verify_signature(SECRET.as_bytes(), b"Hello, World!", None));
+}
+
+#[test]
+fn test_opened_event_into_issue() {
+    let event: IssuesEvent = serde_json::from_str(OPENED_PAYLOAD).unwrap();
+    assert!(event.is_relevant());
+    assert_eq!(event.repository.full_name, "owner/repo");
+
+    let issue = event.into_issue();
+
+    assert_eq!(issue.id, "I_kwDOAbCdEf5xYz01");
+    assert_eq!(issue.url, "https://github.com/owner/repo/issues/42");
+    assert!(issue.has_any_label(&["good first issue".to_string()].into()));
+    assert!(issue.timeline_items.nodes.is_none());
+}
+
+#[test]
+fn test_labeled_event_into_issue() {
+    let event: IssuesEvent = serde_json::from_str(LABELED_PAYLOAD).unwrap();
+    assert!(event.is_relevant());
+
+    let issue = event.into_issue();
+
+    let labeled_at = issue.last_labeled_at(&["bug".to_string()].into()).unwrap();
+    assert_eq!(labeled_at.to_rfc3339(), "2024-05-16T10:05:00+00:00");
+}
+
+#[test]
+fn test_closed_issue_is_not_relevant() {
+    let payload = LABELED_PAYLOAD.replace(r#""state": "open""#, r#""state": "closed""#);
+    let event: IssuesEvent = serde_json::from_str(&payload).unwrap();
+
+    assert!(!event.is_relevant());
+}
+
+#[tokio::test]
+async fn test_webhook_rejects_invalid_signature() {
+    let state = webhook_state(MockRepoStorage::new());
+    let headers = headers("issues", &sign("wrong secret", OPENED_PAYLOAD));
+
+    let status = handle_github_webhook(State(state), headers, Bytes::from(OPENED_PAYLOAD)).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_webhook_ignores_other_events() {
+    let state = webhook_state(MockRepoStorage::new());
+    let body = r#"{"zen": "Keep it logically awesome."}"#;
+    let headers = headers("ping", &sign(SECRET, body));
+
+    let status = handle_github_webhook(State(state), headers, Bytes::from(body)).await;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_webhook_rejects_malformed_payload() {
+    let state = webhook_state(MockRepoStorage::new());
+    let body = r#"{"action": "opened"}"#;
+    let headers = headers("issues", &sign(SECRET, body));
+
+    let status = handle_github_webhook(State(state), headers, Bytes::from(body)).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_webhook_accepts_opened_issue() {
+    let mut storage = MockRepoStorage::new();
+    storage
+        .expect_get_repo_subscribers()
+        .with(mockall::predicate::eq("owner/repo"))
+        .times(1)
+        .returning(|_| Ok(Vec::new()));
+    storage.expect_get_owner_subscribers().times(1).returning(|_| Ok(Vec::new()));
+    let state = webhook_state(storage);
+    let tasks = state.tasks.clone();
+    let headers = headers("issues", &sign(SECRET, OPENED_PAYLOAD));
+
+    let status = handle_github_webhook(State(state), headers, Bytes::from(OPENED_PAYLOAD)).await;
+
+    assert_eq!(status, StatusCode::ACCEPTED);
+    // The issue is handled on a tracked task
+    tasks.close();
+    tasks.wait().await;
+}